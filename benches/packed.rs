@@ -0,0 +1,38 @@
+#[macro_use]
+extern crate criterion;
+extern crate constructor;
+
+use criterion::{Criterion, black_box};
+
+use constructor::{Sequence, Nucleotide};
+
+fn genome(len: usize) -> String {
+    // A deterministic, non-repetitive stand-in for a chromosome.
+    let mut state: u32 = 0x2545_f491;
+    (0..len).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        ['A', 'C', 'G', 'T'][(state % 4) as usize]
+    }).collect()
+}
+
+fn reverse_complement(c: &mut Criterion) {
+    let seq = Sequence::<Nucleotide>::from_str(&genome(1_000_000)).unwrap();
+    c.bench_function("reverse_complement", |b| b.iter(|| black_box(&seq).reverse_complement()));
+}
+
+fn kmers(c: &mut Criterion) {
+    let seq = Sequence::<Nucleotide>::from_str(&genome(1_000_000)).unwrap();
+    c.bench_function("kmers/k=20", |b| b.iter(|| {
+        black_box(&seq).kmers(20).fold(0u64, |acc, code| acc ^ code)
+    }));
+}
+
+fn codons(c: &mut Criterion) {
+    let seq = Sequence::<Nucleotide>::from_str(&genome(300_000)).unwrap();
+    c.bench_function("codons", |b| b.iter(|| black_box(&seq).codons().count()));
+}
+
+criterion_group!(benches, reverse_complement, kmers, codons);
+criterion_main!(benches);
@@ -0,0 +1,18 @@
+use std::env;
+use std::path::Path;
+
+/// Generate the C header into `OUT_DIR`. The copy in `include/` is checked
+/// against it by the `header_is_current` test and refreshed by hand.
+fn main() {
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let config = cbindgen::Config::from_file(Path::new(&crate_dir).join("cbindgen.toml")).unwrap();
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("unable to generate the C header")
+        .write_to_file(Path::new(&out_dir).join("constructor.h"));
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+}
@@ -0,0 +1,189 @@
+/* Generated by cbindgen from bindings/ffi; do not edit. */
+
+#ifndef CONSTRUCTOR_H
+#define CONSTRUCTOR_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+// The outcome of every call. Anything but `CONSTRUCTOR_STATUS_OK` leaves a
+// message for `constructor_last_error`.
+typedef enum ConstructorStatus {
+  CONSTRUCTOR_STATUS_OK = 0,
+  // A required pointer argument was null.
+  CONSTRUCTOR_STATUS_NULL_ARGUMENT = 1,
+  // A string argument was not valid UTF-8.
+  CONSTRUCTOR_STATUS_INVALID_UTF8 = 2,
+  // Text held a symbol outside the alphabet being parsed.
+  CONSTRUCTOR_STATUS_INVALID_SEQUENCE = 3,
+  // The operation does not apply to the sequence's alphabet, such as
+  // translating a protein.
+  CONSTRUCTOR_STATUS_WRONG_ALPHABET = 4,
+  // No NCBI translation table has the given number.
+  CONSTRUCTOR_STATUS_UNKNOWN_TABLE = 5,
+  // An index at or past the end of a collection.
+  CONSTRUCTOR_STATUS_INDEX_OUT_OF_RANGE = 6,
+  // The file could not be opened, read or written.
+  CONSTRUCTOR_STATUS_IO = 7,
+  // The file does not follow the structure of its format.
+  CONSTRUCTOR_STATUS_MALFORMED = 8,
+  // An internal error; the library caught it rather than unwinding into
+  // the caller.
+  CONSTRUCTOR_STATUS_PANIC = 9,
+  // An argument was out of range, such as an alphabet that is not one of
+  // `ConstructorAlphabet`.
+  CONSTRUCTOR_STATUS_INVALID_ARGUMENT = 10,
+} ConstructorStatus;
+
+// The alphabet a sequence is written in.
+typedef enum ConstructorAlphabet {
+  // A, C, G and T only.
+  CONSTRUCTOR_ALPHABET_DNA = 0,
+  // DNA with IUPAC ambiguity codes.
+  CONSTRUCTOR_ALPHABET_DEGENERATE = 1,
+  // One-letter amino-acid codes, with `*` for stops.
+  CONSTRUCTOR_ALPHABET_PROTEIN = 2,
+} ConstructorAlphabet;
+
+// An opaque handle to the records read from a FASTA file.
+typedef struct ConstructorFasta ConstructorFasta;
+
+// An opaque handle to a sequence of any alphabet.
+typedef struct ConstructorSequence ConstructorSequence;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// The message left by the last failed call on this thread, or null if the
+// last call succeeded. The string is owned by the library and valid until
+// the next call on the same thread.
+const char *constructor_last_error(void);
+
+// Free a string returned by the library.
+//
+// # Safety
+//
+// `text` must be null or a string returned by this library and not yet
+// freed.
+void constructor_string_free(char *text);
+
+// Parse a NUL-terminated string as a sequence of `alphabet`, one of
+// `ConstructorAlphabet`, ignoring case. On success `*out` receives a handle
+// to release with `constructor_sequence_free`.
+//
+// # Safety
+//
+// `text` must be a NUL-terminated string and `out` valid for writes.
+enum ConstructorStatus constructor_sequence_parse(const char *text,
+                                                  uint32_t alphabet,
+                                                  struct ConstructorSequence **out);
+
+// Release a sequence handle; null is ignored.
+//
+// # Safety
+//
+// `sequence` must be null or a handle from this library not yet freed.
+void constructor_sequence_free(struct ConstructorSequence *sequence);
+
+// # Safety
+//
+// `sequence` must be a live handle and `out` valid for writes.
+enum ConstructorStatus constructor_sequence_length(const struct ConstructorSequence *sequence,
+                                                   size_t *out);
+
+// # Safety
+//
+// `sequence` must be a live handle and `out` valid for writes.
+enum ConstructorStatus constructor_sequence_alphabet(const struct ConstructorSequence *sequence,
+                                                     enum ConstructorAlphabet *out);
+
+// The sequence as upper-case text. On success `*out` receives a string to
+// release with `constructor_string_free`.
+//
+// # Safety
+//
+// `sequence` must be a live handle and `out` valid for writes.
+enum ConstructorStatus constructor_sequence_to_string(const struct ConstructorSequence *sequence,
+                                                      char **out);
+
+// The reverse complement of a DNA or degenerate sequence, as a new handle
+// of the same alphabet.
+//
+// # Safety
+//
+// `sequence` must be a live handle and `out` valid for writes.
+enum ConstructorStatus constructor_reverse_complement(const struct ConstructorSequence *sequence,
+                                                      struct ConstructorSequence **out);
+
+// Translate the complete codons of a DNA sequence with NCBI translation
+// table `table` (1 for the standard code) into a new protein handle.
+// Trailing bases that do not fill a codon are ignored.
+//
+// # Safety
+//
+// `sequence` must be a live handle and `out` valid for writes.
+enum ConstructorStatus constructor_translate(const struct ConstructorSequence *sequence,
+                                             uint8_t table,
+                                             struct ConstructorSequence **out);
+
+// Read every record of the FASTA file at `path`, parsing sequences as
+// `alphabet`, one of `ConstructorAlphabet`. On success `*out` receives a
+// handle to release with `constructor_fasta_free`.
+//
+// # Safety
+//
+// `path` must be a NUL-terminated string and `out` valid for writes.
+enum ConstructorStatus constructor_fasta_read(const char *path,
+                                              uint32_t alphabet,
+                                              struct ConstructorFasta **out);
+
+// Release the records of a FASTA file; null is ignored. Identifiers lent
+// by `constructor_fasta_id` are invalid afterwards.
+//
+// # Safety
+//
+// `fasta` must be null or a handle from this library not yet freed.
+void constructor_fasta_free(struct ConstructorFasta *fasta);
+
+// # Safety
+//
+// `fasta` must be a live handle and `out` valid for writes.
+enum ConstructorStatus constructor_fasta_count(const struct ConstructorFasta *fasta, size_t *out);
+
+// The identifier of record `index`, owned by `fasta` and valid until it is
+// freed.
+//
+// # Safety
+//
+// `fasta` must be a live handle and `out` valid for writes.
+enum ConstructorStatus constructor_fasta_id(const struct ConstructorFasta *fasta,
+                                            size_t index,
+                                            const char **out);
+
+// A copy of the sequence of record `index`, as a new handle.
+//
+// # Safety
+//
+// `fasta` must be a live handle and `out` valid for writes.
+enum ConstructorStatus constructor_fasta_sequence(const struct ConstructorFasta *fasta,
+                                                  size_t index,
+                                                  struct ConstructorSequence **out);
+
+// Write `count` records to a FASTA file at `path`, replacing any file
+// there, with sequences wrapped at 60 symbols per line.
+//
+// # Safety
+//
+// `path` must be a NUL-terminated string, and `ids` and `sequences` arrays
+// of `count` NUL-terminated strings and live handles.
+enum ConstructorStatus constructor_fasta_write(const char *path,
+                                               const char *const *ids,
+                                               const struct ConstructorSequence *const *sequences,
+                                               size_t count);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CONSTRUCTOR_H */
@@ -0,0 +1,184 @@
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use constructor::sequence::error::SequenceError;
+use constructor::io::error::FormatError;
+
+/// The outcome of every call. Anything but `CONSTRUCTOR_STATUS_OK` leaves a
+/// message for `constructor_last_error`.
+#[repr(C)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ConstructorStatus {
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullArgument = 1,
+    /// A string argument was not valid UTF-8.
+    InvalidUtf8 = 2,
+    /// Text held a symbol outside the alphabet being parsed.
+    InvalidSequence = 3,
+    /// The operation does not apply to the sequence's alphabet, such as
+    /// translating a protein.
+    WrongAlphabet = 4,
+    /// No NCBI translation table has the given number.
+    UnknownTable = 5,
+    /// An index at or past the end of a collection.
+    IndexOutOfRange = 6,
+    /// The file could not be opened, read or written.
+    Io = 7,
+    /// The file does not follow the structure of its format.
+    Malformed = 8,
+    /// An internal error; the library caught it rather than unwinding into
+    /// the caller.
+    Panic = 9,
+    /// An argument was out of range, such as an alphabet that is not one of
+    /// `ConstructorAlphabet`.
+    InvalidArgument = 10,
+}
+
+/// A failed call: its status and a message for the caller.
+pub(crate) struct Failure {
+    status: ConstructorStatus,
+    message: String,
+}
+
+impl Failure {
+    pub(crate) fn new(status: ConstructorStatus, message: &str) -> Failure {
+        Failure { status, message: String::from(message) }
+    }
+}
+
+impl From<SequenceError> for Failure {
+    fn from(e: SequenceError) -> Failure {
+        let status = match e {
+            SequenceError::IndexOutOfBounds { .. } => ConstructorStatus::IndexOutOfRange,
+            _ => ConstructorStatus::InvalidSequence,
+        };
+        Failure { status, message: e.to_string() }
+    }
+}
+
+impl From<FormatError> for Failure {
+    fn from(e: FormatError) -> Failure {
+        let status = match e {
+            FormatError::Io(_) => ConstructorStatus::Io,
+            FormatError::InvalidSequence { .. } | FormatError::Sequence(_) =>
+                ConstructorStatus::InvalidSequence,
+            _ => ConstructorStatus::Malformed,
+        };
+        Failure { status, message: e.to_string() }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: Option<&str>) {
+    // Messages come from Rust errors and never hold NUL, but drop any rather
+    // than lose the message.
+    let message = message.map(|m| CString::new(m.replace('\0', "")).unwrap());
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+/// Run the body of an exported function, turning failures into a status
+/// and a stored message, and catching panics at the boundary.
+pub(crate) fn guard<F>(body: F) -> ConstructorStatus where F: FnOnce() -> Result<(), Failure> {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => {
+            set_last_error(None);
+            ConstructorStatus::Ok
+        },
+        Ok(Err(failure)) => {
+            set_last_error(Some(&failure.message));
+            failure.status
+        },
+        Err(_) => {
+            set_last_error(Some("internal error"));
+            ConstructorStatus::Panic
+        },
+    }
+}
+
+/// Borrow a string argument.
+///
+/// # Safety
+///
+/// `text` must be null or a NUL-terminated string that outlives the borrow.
+pub(crate) unsafe fn str_argument<'a>(text: *const c_char) -> Result<&'a str, Failure> {
+    if text.is_null() {
+        return Err(Failure::new(ConstructorStatus::NullArgument, "null string argument"));
+    }
+    CStr::from_ptr(text).to_str()
+        .map_err(|_| Failure::new(ConstructorStatus::InvalidUtf8, "string is not valid UTF-8"))
+}
+
+/// Borrow the object behind a handle.
+///
+/// # Safety
+///
+/// `handle` must be null or point to a live object.
+pub(crate) unsafe fn borrow<'a, T>(handle: *const T) -> Result<&'a T, Failure> {
+    handle.as_ref().ok_or_else(|| Failure::new(ConstructorStatus::NullArgument, "null handle"))
+}
+
+/// Store `value` through an out-parameter.
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+pub(crate) unsafe fn write_out<T>(out: *mut T, value: T) -> Result<(), Failure> {
+    if out.is_null() {
+        return Err(Failure::new(ConstructorStatus::NullArgument, "null output argument"));
+    }
+    ptr::write(out, value);
+    Ok(())
+}
+
+/// Move `value` to the heap and store the handle through an out-parameter,
+/// checking the pointer first so nothing leaks if it is null.
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+pub(crate) unsafe fn write_handle<T>(out: *mut *mut T, value: T) -> Result<(), Failure> {
+    if out.is_null() {
+        return Err(Failure::new(ConstructorStatus::NullArgument, "null output argument"));
+    }
+    write_out(out, Box::into_raw(Box::new(value)))
+}
+
+/// Store a string for the caller to release with `constructor_string_free`.
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+pub(crate) unsafe fn write_string(out: *mut *mut c_char, text: &str) -> Result<(), Failure> {
+    if out.is_null() {
+        return Err(Failure::new(ConstructorStatus::NullArgument, "null output argument"));
+    }
+    write_out(out, CString::new(text.replace('\0', "")).unwrap().into_raw())
+}
+
+/// The message left by the last failed call on this thread, or null if the
+/// last call succeeded. The string is owned by the library and valid until
+/// the next call on the same thread.
+#[no_mangle]
+pub extern "C" fn constructor_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |m| m.as_ptr()))
+}
+
+/// Free a string returned by the library.
+///
+/// # Safety
+///
+/// `text` must be null or a string returned by this library and not yet
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn constructor_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
@@ -0,0 +1,215 @@
+use std::ffi::CString;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::os::raw::c_char;
+use std::slice;
+
+use constructor::sequence::sequence::Sequence;
+use constructor::sequence::string_io::StringIO;
+use constructor::io::fasta::{FastaReader, FastaRecord, FastaWriter};
+use constructor::io::error::FormatError;
+
+use crate::error::{ConstructorStatus, Failure, guard, str_argument, borrow, write_out,
+                   write_handle};
+use crate::sequence::{ConstructorAlphabet, ConstructorSequence};
+
+/// An opaque handle to the records read from a FASTA file.
+pub struct ConstructorFasta {
+    /// Identifiers, kept as C strings so they can be lent to the caller.
+    ids: Vec<CString>,
+    sequences: Vec<ConstructorSequence>,
+}
+
+fn read<N, F>(path: &str, wrap: F) -> Result<ConstructorFasta, Failure>
+    where N: StringIO<N=N> + Clone, F: Fn(Sequence<N>) -> ConstructorSequence {
+    let file = File::open(path).map_err(|e| Failure::from(FormatError::Io(e)))?;
+    let mut fasta = ConstructorFasta { ids: Vec::new(), sequences: Vec::new() };
+    for record in FastaReader::<_, N>::new(BufReader::new(file)) {
+        let record = record?;
+        fasta.ids.push(CString::new(record.id.replace('\0', "")).unwrap());
+        fasta.sequences.push(wrap(record.sequence));
+    }
+    Ok(fasta)
+}
+
+fn record(fasta: &ConstructorFasta, index: usize)
+          -> Result<(&CString, &ConstructorSequence), Failure> {
+    match (fasta.ids.get(index), fasta.sequences.get(index)) {
+        (Some(id), Some(sequence)) => Ok((id, sequence)),
+        _ => Err(Failure::new(ConstructorStatus::IndexOutOfRange, &format!(
+            "Record {} out of range for {} records", index, fasta.ids.len()))),
+    }
+}
+
+/// Read every record of the FASTA file at `path`, parsing sequences as
+/// `alphabet`, one of `ConstructorAlphabet`. On success `*out` receives a
+/// handle to release with `constructor_fasta_free`.
+///
+/// # Safety
+///
+/// `path` must be a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn constructor_fasta_read(path: *const c_char, alphabet: u32,
+                                                out: *mut *mut ConstructorFasta)
+                                                -> ConstructorStatus {
+    guard(|| {
+        let path = str_argument(path)?;
+        let fasta = match ConstructorAlphabet::from_raw(alphabet)? {
+            ConstructorAlphabet::Dna => read(path, ConstructorSequence::Dna)?,
+            ConstructorAlphabet::Degenerate => read(path, ConstructorSequence::Degenerate)?,
+            ConstructorAlphabet::Protein => read(path, ConstructorSequence::Protein)?,
+        };
+        write_handle(out, fasta)
+    })
+}
+
+/// Release the records of a FASTA file; null is ignored. Identifiers lent
+/// by `constructor_fasta_id` are invalid afterwards.
+///
+/// # Safety
+///
+/// `fasta` must be null or a handle from this library not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn constructor_fasta_free(fasta: *mut ConstructorFasta) {
+    if !fasta.is_null() {
+        drop(Box::from_raw(fasta));
+    }
+}
+
+/// # Safety
+///
+/// `fasta` must be a live handle and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn constructor_fasta_count(fasta: *const ConstructorFasta, out: *mut usize)
+                                                 -> ConstructorStatus {
+    guard(|| write_out(out, borrow(fasta)?.ids.len()))
+}
+
+/// The identifier of record `index`, owned by `fasta` and valid until it is
+/// freed.
+///
+/// # Safety
+///
+/// `fasta` must be a live handle and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn constructor_fasta_id(fasta: *const ConstructorFasta, index: usize,
+                                              out: *mut *const c_char) -> ConstructorStatus {
+    guard(|| write_out(out, record(borrow(fasta)?, index)?.0.as_ptr()))
+}
+
+/// A copy of the sequence of record `index`, as a new handle.
+///
+/// # Safety
+///
+/// `fasta` must be a live handle and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn constructor_fasta_sequence(fasta: *const ConstructorFasta, index: usize,
+                                                    out: *mut *mut ConstructorSequence)
+                                                    -> ConstructorStatus {
+    guard(|| {
+        let sequence = match *record(borrow(fasta)?, index)?.1 {
+            ConstructorSequence::Dna(ref seq) => ConstructorSequence::Dna(seq.clone()),
+            ConstructorSequence::Degenerate(ref seq) =>
+                ConstructorSequence::Degenerate(seq.clone()),
+            ConstructorSequence::Protein(ref seq) => ConstructorSequence::Protein(seq.clone()),
+        };
+        write_handle(out, sequence)
+    })
+}
+
+/// Write `count` records to a FASTA file at `path`, replacing any file
+/// there, with sequences wrapped at 60 symbols per line.
+///
+/// # Safety
+///
+/// `path` must be a NUL-terminated string, and `ids` and `sequences` arrays
+/// of `count` NUL-terminated strings and live handles.
+#[no_mangle]
+pub unsafe extern "C" fn constructor_fasta_write(path: *const c_char, ids: *const *const c_char,
+                                                 sequences: *const *const ConstructorSequence,
+                                                 count: usize) -> ConstructorStatus {
+    guard(|| {
+        let path = str_argument(path)?;
+        if count > 0 && (ids.is_null() || sequences.is_null()) {
+            return Err(Failure::new(ConstructorStatus::NullArgument, "null record array"));
+        }
+        let (ids, sequences) = if count == 0 {
+            (&[][..], &[][..])
+        } else {
+            (slice::from_raw_parts(ids, count), slice::from_raw_parts(sequences, count))
+        };
+        let file = File::create(path).map_err(|e| Failure::from(FormatError::Io(e)))?;
+        let mut writer = FastaWriter::new(BufWriter::new(file));
+        for (&id, &sequence) in ids.iter().zip(sequences.iter()) {
+            let id = str_argument(id)?;
+            match *borrow(sequence)? {
+                ConstructorSequence::Dna(ref seq) =>
+                    writer.write(&FastaRecord::new(id, seq.clone()))?,
+                ConstructorSequence::Degenerate(ref seq) =>
+                    writer.write(&FastaRecord::new(id, seq.clone()))?,
+                ConstructorSequence::Protein(ref seq) =>
+                    writer.write(&FastaRecord::new(id, seq.clone()))?,
+            }
+        }
+        Ok(writer.flush()?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::ffi::{CStr, CString};
+    use std::fs;
+    use std::ptr;
+
+    use super::*;
+    use crate::sequence::{constructor_sequence_parse, constructor_sequence_free,
+                          constructor_sequence_length};
+
+    #[test]
+    fn round_trip() {
+        let path = env::temp_dir().join(format!("constructor-ffi-{}.fa", ::std::process::id()));
+        let path = CString::new(path.to_str().unwrap()).unwrap();
+        unsafe {
+            let text = CString::new("ATGNNK").unwrap();
+            let mut seq = ptr::null_mut();
+            let (dna, degenerate) = (ConstructorAlphabet::Dna as u32,
+                                     ConstructorAlphabet::Degenerate as u32);
+            assert_eq!(constructor_sequence_parse(text.as_ptr(), degenerate, &mut seq),
+                       ConstructorStatus::Ok);
+            let id = CString::new("lib").unwrap();
+            let ids = [id.as_ptr()];
+            let seqs = [seq as *const ConstructorSequence];
+            assert_eq!(constructor_fasta_write(path.as_ptr(), ids.as_ptr(), seqs.as_ptr(), 1),
+                       ConstructorStatus::Ok);
+            constructor_sequence_free(seq);
+
+            let mut fasta = ptr::null_mut();
+            assert_eq!(constructor_fasta_read(path.as_ptr(), dna, &mut fasta),
+                       ConstructorStatus::InvalidSequence);
+            assert_eq!(constructor_fasta_read(path.as_ptr(), 3, &mut fasta),
+                       ConstructorStatus::InvalidArgument);
+            assert_eq!(constructor_fasta_read(path.as_ptr(), degenerate, &mut fasta),
+                       ConstructorStatus::Ok);
+            let mut count = 0;
+            assert_eq!(constructor_fasta_count(fasta, &mut count), ConstructorStatus::Ok);
+            assert_eq!(count, 1);
+            let mut id = ptr::null();
+            assert_eq!(constructor_fasta_id(fasta, 0, &mut id), ConstructorStatus::Ok);
+            assert_eq!(CStr::from_ptr(id).to_str().unwrap(), "lib");
+            let mut seq = ptr::null_mut();
+            assert_eq!(constructor_fasta_sequence(fasta, 0, &mut seq), ConstructorStatus::Ok);
+            let mut length = 0;
+            assert_eq!(constructor_sequence_length(seq, &mut length), ConstructorStatus::Ok);
+            assert_eq!(length, 6);
+            assert_eq!(constructor_fasta_sequence(fasta, 1, &mut seq),
+                       ConstructorStatus::IndexOutOfRange);
+            constructor_sequence_free(seq);
+            constructor_fasta_free(fasta);
+
+            fs::remove_file(path.to_str().unwrap()).unwrap();
+            assert_eq!(constructor_fasta_read(path.as_ptr(), dna, &mut fasta),
+                       ConstructorStatus::Io);
+        }
+    }
+}
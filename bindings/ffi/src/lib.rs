@@ -0,0 +1,34 @@
+//! A C ABI over sequence parsing, reverse complement, translation and
+//! FASTA files, for linking from C and C++. The header `include/constructor.h`
+//! is generated from this crate by cbindgen. The build writes a fresh copy
+//! to `OUT_DIR` and a test fails if the checked-in one differs; after
+//! changing the API, regenerate it from this directory with cbindgen 0.29:
+//!
+//! ```sh
+//! cbindgen --config cbindgen.toml --output include/constructor.h
+//! ```
+//!
+//! Objects are opaque handles created and released by the library. Every
+//! fallible call returns a `ConstructorStatus` and passes results through
+//! out-parameters, which are left untouched on failure; the message for the
+//! last failure on a thread is available from `constructor_last_error`.
+//! Panics never cross the boundary.
+
+mod error;
+mod sequence;
+mod fasta;
+
+pub use crate::error::*;
+pub use crate::sequence::*;
+pub use crate::fasta::*;
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn header_is_current() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/constructor.h"));
+        let checked_in = include_str!("../include/constructor.h");
+        assert!(generated == checked_in,
+                "include/constructor.h is out of date; regenerate it with cbindgen");
+    }
+}
@@ -0,0 +1,266 @@
+use std::os::raw::c_char;
+
+use constructor::sequence::sequence::Sequence;
+use constructor::sequence::nucleotide::Nucleotide;
+use constructor::sequence::degenerate_nucleotide::DegenerateNucleotide;
+use constructor::sequence::amino_acid::AminoAcid;
+use constructor::sequence::genetic_code::GeneticCode;
+
+use crate::error::{ConstructorStatus, Failure, guard, str_argument, borrow, write_out,
+                   write_handle, write_string};
+
+/// The alphabet a sequence is written in.
+#[repr(C)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ConstructorAlphabet {
+    /// A, C, G and T only.
+    Dna = 0,
+    /// DNA with IUPAC ambiguity codes.
+    Degenerate = 1,
+    /// One-letter amino-acid codes, with `*` for stops.
+    Protein = 2,
+}
+
+impl ConstructorAlphabet {
+    /// The alphabet numbered `alphabet`. Functions take alphabets as plain
+    /// integers, since C may pass any value where an enum is expected.
+    pub(crate) fn from_raw(alphabet: u32) -> Result<ConstructorAlphabet, Failure> {
+        match alphabet {
+            0 => Ok(ConstructorAlphabet::Dna),
+            1 => Ok(ConstructorAlphabet::Degenerate),
+            2 => Ok(ConstructorAlphabet::Protein),
+            other => Err(Failure::new(ConstructorStatus::InvalidArgument,
+                                      &format!("unknown alphabet {}", other))),
+        }
+    }
+}
+
+/// An opaque handle to a sequence of any alphabet.
+pub enum ConstructorSequence {
+    Dna(Sequence<Nucleotide>),
+    Degenerate(Sequence<DegenerateNucleotide>),
+    Protein(Sequence<AminoAcid>),
+}
+
+impl ConstructorSequence {
+    pub(crate) fn parse(text: &str, alphabet: ConstructorAlphabet)
+                 -> Result<ConstructorSequence, Failure> {
+        Ok(match alphabet {
+            ConstructorAlphabet::Dna => ConstructorSequence::Dna(Sequence::from_str(text)?),
+            ConstructorAlphabet::Degenerate =>
+                ConstructorSequence::Degenerate(Sequence::from_str(text)?),
+            ConstructorAlphabet::Protein => ConstructorSequence::Protein(Sequence::from_str(text)?),
+        })
+    }
+
+    pub(crate) fn alphabet(&self) -> ConstructorAlphabet {
+        match *self {
+            ConstructorSequence::Dna(_) => ConstructorAlphabet::Dna,
+            ConstructorSequence::Degenerate(_) => ConstructorAlphabet::Degenerate,
+            ConstructorSequence::Protein(_) => ConstructorAlphabet::Protein,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match *self {
+            ConstructorSequence::Dna(ref seq) => seq.len(),
+            ConstructorSequence::Degenerate(ref seq) => seq.len(),
+            ConstructorSequence::Protein(ref seq) => seq.len(),
+        }
+    }
+
+    pub(crate) fn to_text(&self) -> String {
+        match *self {
+            ConstructorSequence::Dna(ref seq) => seq.to_string(),
+            ConstructorSequence::Degenerate(ref seq) => seq.to_string(),
+            ConstructorSequence::Protein(ref seq) => seq.to_string(),
+        }
+    }
+}
+
+/// Parse a NUL-terminated string as a sequence of `alphabet`, one of
+/// `ConstructorAlphabet`, ignoring case. On success `*out` receives a handle
+/// to release with `constructor_sequence_free`.
+///
+/// # Safety
+///
+/// `text` must be a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn constructor_sequence_parse(text: *const c_char,
+                                                    alphabet: u32,
+                                                    out: *mut *mut ConstructorSequence)
+                                                    -> ConstructorStatus {
+    guard(|| {
+        let alphabet = ConstructorAlphabet::from_raw(alphabet)?;
+        let sequence = ConstructorSequence::parse(str_argument(text)?, alphabet)?;
+        write_handle(out, sequence)
+    })
+}
+
+/// Release a sequence handle; null is ignored.
+///
+/// # Safety
+///
+/// `sequence` must be null or a handle from this library not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn constructor_sequence_free(sequence: *mut ConstructorSequence) {
+    if !sequence.is_null() {
+        drop(Box::from_raw(sequence));
+    }
+}
+
+/// # Safety
+///
+/// `sequence` must be a live handle and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn constructor_sequence_length(sequence: *const ConstructorSequence,
+                                                     out: *mut usize) -> ConstructorStatus {
+    guard(|| write_out(out, borrow(sequence)?.len()))
+}
+
+/// # Safety
+///
+/// `sequence` must be a live handle and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn constructor_sequence_alphabet(sequence: *const ConstructorSequence,
+                                                       out: *mut ConstructorAlphabet)
+                                                       -> ConstructorStatus {
+    guard(|| write_out(out, borrow(sequence)?.alphabet()))
+}
+
+/// The sequence as upper-case text. On success `*out` receives a string to
+/// release with `constructor_string_free`.
+///
+/// # Safety
+///
+/// `sequence` must be a live handle and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn constructor_sequence_to_string(sequence: *const ConstructorSequence,
+                                                        out: *mut *mut c_char)
+                                                        -> ConstructorStatus {
+    guard(|| write_string(out, &borrow(sequence)?.to_text()))
+}
+
+/// The reverse complement of a DNA or degenerate sequence, as a new handle
+/// of the same alphabet.
+///
+/// # Safety
+///
+/// `sequence` must be a live handle and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn constructor_reverse_complement(sequence: *const ConstructorSequence,
+                                                        out: *mut *mut ConstructorSequence)
+                                                        -> ConstructorStatus {
+    guard(|| {
+        let reverse = match *borrow(sequence)? {
+            ConstructorSequence::Dna(ref seq) => ConstructorSequence::Dna(seq.reverse_complement()),
+            ConstructorSequence::Degenerate(ref seq) =>
+                ConstructorSequence::Degenerate(seq.reverse_complement()),
+            ConstructorSequence::Protein(_) => return Err(Failure::new(
+                ConstructorStatus::WrongAlphabet, "a protein has no reverse complement")),
+        };
+        write_handle(out, reverse)
+    })
+}
+
+/// Translate the complete codons of a DNA sequence with NCBI translation
+/// table `table` (1 for the standard code) into a new protein handle.
+/// Trailing bases that do not fill a codon are ignored.
+///
+/// # Safety
+///
+/// `sequence` must be a live handle and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn constructor_translate(sequence: *const ConstructorSequence, table: u8,
+                                               out: *mut *mut ConstructorSequence)
+                                               -> ConstructorStatus {
+    guard(|| {
+        let code = GeneticCode::from_id(table).ok_or_else(|| Failure::new(
+            ConstructorStatus::UnknownTable, &format!("No translation table {}", table)))?;
+        let protein = match *borrow(sequence)? {
+            ConstructorSequence::Dna(ref seq) => seq.translate_with(code),
+            _ => return Err(Failure::new(ConstructorStatus::WrongAlphabet,
+                                         "only DNA sequences can be translated")),
+        };
+        write_handle(out, ConstructorSequence::Protein(protein))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+
+    use super::*;
+    use crate::error::{constructor_last_error, constructor_string_free};
+
+    unsafe fn text(sequence: *const ConstructorSequence) -> String {
+        let mut out = ptr::null_mut();
+        assert_eq!(constructor_sequence_to_string(sequence, &mut out), ConstructorStatus::Ok);
+        let text = CStr::from_ptr(out).to_str().unwrap().to_owned();
+        constructor_string_free(out);
+        text
+    }
+
+    #[test]
+    fn parse_and_transform() {
+        unsafe {
+            let input = CString::new("atggcctaa").unwrap();
+            let mut dna = ptr::null_mut();
+            assert_eq!(constructor_sequence_parse(input.as_ptr(), ConstructorAlphabet::Dna as u32,
+                                                  &mut dna), ConstructorStatus::Ok);
+            assert!(constructor_last_error().is_null());
+            let mut length = 0;
+            assert_eq!(constructor_sequence_length(dna, &mut length), ConstructorStatus::Ok);
+            assert_eq!(length, 9);
+
+            let mut reverse = ptr::null_mut();
+            assert_eq!(constructor_reverse_complement(dna, &mut reverse), ConstructorStatus::Ok);
+            assert_eq!(text(reverse), "TTAGGCCAT");
+            let mut protein = ptr::null_mut();
+            assert_eq!(constructor_translate(dna, 1, &mut protein), ConstructorStatus::Ok);
+            assert_eq!(text(protein), "MA*");
+            let mut alphabet = ConstructorAlphabet::Dna;
+            assert_eq!(constructor_sequence_alphabet(protein, &mut alphabet),
+                       ConstructorStatus::Ok);
+            assert_eq!(alphabet, ConstructorAlphabet::Protein);
+
+            let mut unused = ptr::null_mut();
+            assert_eq!(constructor_translate(protein, 1, &mut unused),
+                       ConstructorStatus::WrongAlphabet);
+            assert_eq!(constructor_translate(dna, 99, &mut unused), ConstructorStatus::UnknownTable);
+            assert!(unused.is_null());
+            for handle in [dna, reverse, protein].iter() {
+                constructor_sequence_free(*handle);
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        unsafe {
+            let input = CString::new("ACGN").unwrap();
+            let mut dna = ptr::null_mut();
+            let dna_alphabet = ConstructorAlphabet::Dna as u32;
+            assert_eq!(constructor_sequence_parse(input.as_ptr(), dna_alphabet, &mut dna),
+                       ConstructorStatus::InvalidSequence);
+            assert!(dna.is_null());
+            let message = CStr::from_ptr(constructor_last_error()).to_str().unwrap();
+            assert_eq!(message, "Invalid symbol 'N' at position 4");
+            assert_eq!(constructor_sequence_parse(ptr::null(), dna_alphabet, &mut dna),
+                       ConstructorStatus::NullArgument);
+            assert_eq!(constructor_sequence_parse(input.as_ptr(),
+                                                  ConstructorAlphabet::Degenerate as u32,
+                                                  ptr::null_mut()),
+                       ConstructorStatus::NullArgument);
+            assert_eq!(constructor_sequence_parse(input.as_ptr(), 7, &mut dna),
+                       ConstructorStatus::InvalidArgument);
+            let message = CStr::from_ptr(constructor_last_error()).to_str().unwrap();
+            assert_eq!(message, "unknown alphabet 7");
+            assert!(dna.is_null());
+            let mut length = 0;
+            assert_eq!(constructor_sequence_length(ptr::null(), &mut length),
+                       ConstructorStatus::NullArgument);
+        }
+    }
+}
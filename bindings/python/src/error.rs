@@ -0,0 +1,59 @@
+use pyo3::create_exception;
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+
+use constructor_rs::sequence::error::SequenceError as RustSequenceError;
+use constructor_rs::io::error::FormatError as RustFormatError;
+
+create_exception!(constructor, SequenceError, PyValueError,
+                  "A symbol, index or codon that the sequence cannot accept.");
+create_exception!(constructor, FormatError, PyValueError,
+                  "A file that does not follow the structure of its format.");
+
+/// Set `name` on an exception being raised; failures are ignored, as the
+/// message already carries the same information.
+fn annotate<'py, V>(py: Python<'py>, err: &PyErr, name: &str, value: V)
+    where V: IntoPyObject<'py> {
+    let _ = err.value(py).setattr(name, value);
+}
+
+/// A `SequenceError` exception carrying the error's fields as attributes,
+/// with positions zero-based as in Rust.
+pub fn sequence_error(py: Python<'_>, error: RustSequenceError) -> PyErr {
+    let err = SequenceError::new_err(error.to_string());
+    match error {
+        RustSequenceError::InvalidSymbol { symbol, offset } => {
+            annotate(py, &err, "symbol", symbol);
+            annotate(py, &err, "offset", offset);
+        },
+        RustSequenceError::InvalidThreeLetterCode { code } => annotate(py, &err, "code", code),
+        RustSequenceError::IndexOutOfBounds { index, length } => {
+            annotate(py, &err, "index", index);
+            annotate(py, &err, "length", length);
+        },
+        RustSequenceError::IncompleteCodon { index, available } => {
+            annotate(py, &err, "index", index);
+            annotate(py, &err, "available", available);
+        },
+        RustSequenceError::UntranslatableCodon { codon, offset } => {
+            annotate(py, &err, "codon", codon);
+            annotate(py, &err, "offset", offset);
+        },
+    }
+    err
+}
+
+/// I/O failures become `OSError`, sequence failures `SequenceError` (with
+/// the one-based `line` for text formats), and the rest `FormatError`.
+pub fn format_error(py: Python<'_>, error: RustFormatError) -> PyErr {
+    match error {
+        RustFormatError::Io(e) => PyErr::from(e),
+        RustFormatError::InvalidSequence { line, error } => {
+            let err = sequence_error(py, error);
+            annotate(py, &err, "line", line);
+            err
+        },
+        RustFormatError::Sequence(e) => sequence_error(py, e),
+        other => FormatError::new_err(other.to_string()),
+    }
+}
@@ -0,0 +1,168 @@
+use std::fs::File;
+use std::io::BufReader;
+
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+use pyo3::pyclass::PyClass;
+
+use constructor_rs::sequence::sequence::Sequence;
+use constructor_rs::sequence::nucleotide::Nucleotide;
+use constructor_rs::sequence::degenerate_nucleotide::DegenerateNucleotide;
+use constructor_rs::sequence::amino_acid::AminoAcid;
+use constructor_rs::sequence::string_io::StringIO;
+use constructor_rs::sequence::storage::Stored;
+use constructor_rs::sequence::feature::{self, Topology};
+use constructor_rs::sequence::strand::Strand;
+use constructor_rs::io::fasta::FastaReader;
+use constructor_rs::io::fastq::{FastqReader, QualityEncoding};
+use constructor_rs::io::genbank::GenbankReader;
+
+use crate::error::format_error;
+use crate::sequence::{DnaSequence, DegenerateDnaSequence, ProteinSequence};
+
+#[pyclass(module = "constructor", get_all, frozen, skip_from_py_object)]
+pub struct FastaRecord {
+    id: String,
+    description: Option<String>,
+    sequence: Py<PyAny>,
+}
+
+#[pyclass(module = "constructor", get_all, frozen, skip_from_py_object)]
+pub struct FastqRecord {
+    id: String,
+    description: Option<String>,
+    sequence: Py<PyAny>,
+    /// Phred scores, one per base, as `bytes`.
+    quality: Vec<u8>,
+}
+
+/// A feature with zero-based, half-open coordinates; `strand` is `"forward"`
+/// or `"reverse"` and `kind` the GenBank feature key.
+#[pyclass(module = "constructor", get_all, frozen, skip_from_py_object)]
+pub struct Feature {
+    kind: String,
+    label: String,
+    start: usize,
+    end: usize,
+    strand: String,
+    qualifiers: Vec<(String, String)>,
+}
+
+/// A GenBank record; `topology` is `"linear"` or `"circular"`.
+#[pyclass(module = "constructor", get_all, frozen, skip_from_py_object)]
+pub struct GenbankRecord {
+    name: String,
+    sequence: Py<DnaSequence>,
+    topology: String,
+    features: Vec<Py<Feature>>,
+}
+
+/// Which sequence class records are read into.
+enum Alphabet {
+    Dna,
+    Degenerate,
+    Protein,
+}
+
+impl Alphabet {
+    fn from_name(name: &str) -> PyResult<Alphabet> {
+        match name {
+            "dna" => Ok(Alphabet::Dna),
+            "degenerate" => Ok(Alphabet::Degenerate),
+            "protein" => Ok(Alphabet::Protein),
+            other => Err(PyValueError::new_err(format!(
+                "unknown alphabet {}: expected dna, degenerate or protein", other))),
+        }
+    }
+}
+
+fn open(py: Python<'_>, path: &str) -> PyResult<BufReader<File>> {
+    File::open(path).map(BufReader::new).map_err(|e| format_error(py, e.into()))
+}
+
+fn wrap<N, T>(py: Python<'_>, sequence: Sequence<N>) -> PyResult<Py<PyAny>>
+    where N: Stored, T: PyClass + From<Sequence<N>>, PyClassInitializer<T>: From<T> {
+    Ok(Py::new(py, T::from(sequence))?.into_any())
+}
+
+fn fasta<N, T>(py: Python<'_>, path: &str) -> PyResult<Vec<FastaRecord>>
+    where N: StringIO<N=N> + Clone, T: PyClass + From<Sequence<N>>,
+          PyClassInitializer<T>: From<T> {
+    FastaReader::<_, N>::new(open(py, path)?).map(|record| {
+        let record = record.map_err(|e| format_error(py, e))?;
+        Ok(FastaRecord { id: record.id, description: record.description,
+                         sequence: wrap::<N, T>(py, record.sequence)? })
+    }).collect()
+}
+
+fn fastq<N, T>(py: Python<'_>, path: &str, encoding: QualityEncoding)
+               -> PyResult<Vec<FastqRecord>>
+    where N: StringIO<N=N> + Clone, T: PyClass + From<Sequence<N>>,
+          PyClassInitializer<T>: From<T> {
+    FastqReader::<_, N>::new(open(py, path)?, encoding).map(|record| {
+        let record = record.map_err(|e| format_error(py, e))?;
+        Ok(FastqRecord { id: record.id, description: record.description,
+                         sequence: wrap::<N, T>(py, record.sequence)?,
+                         quality: record.quality })
+    }).collect()
+}
+
+/// Every record of a FASTA file, with sequences read as `alphabet`: `"dna"`,
+/// `"degenerate"` or `"protein"`.
+#[pyfunction]
+#[pyo3(signature = (path, alphabet="dna"))]
+pub fn read_fasta(py: Python<'_>, path: &str, alphabet: &str) -> PyResult<Vec<FastaRecord>> {
+    match Alphabet::from_name(alphabet)? {
+        Alphabet::Dna => fasta::<Nucleotide, DnaSequence>(py, path),
+        Alphabet::Degenerate => fasta::<DegenerateNucleotide, DegenerateDnaSequence>(py, path),
+        Alphabet::Protein => fasta::<AminoAcid, ProteinSequence>(py, path),
+    }
+}
+
+/// Every read of a FASTQ file. `alphabet` is `"dna"` or `"degenerate"`, the
+/// latter keeping `N` calls; `phred64` selects the old Illumina encoding.
+#[pyfunction]
+#[pyo3(signature = (path, alphabet="dna", phred64=false))]
+pub fn read_fastq(py: Python<'_>, path: &str, alphabet: &str, phred64: bool)
+                  -> PyResult<Vec<FastqRecord>> {
+    let encoding = if phred64 { QualityEncoding::Phred64 } else { QualityEncoding::Phred33 };
+    match Alphabet::from_name(alphabet)? {
+        Alphabet::Dna => fastq::<Nucleotide, DnaSequence>(py, path, encoding),
+        Alphabet::Degenerate =>
+            fastq::<DegenerateNucleotide, DegenerateDnaSequence>(py, path, encoding),
+        Alphabet::Protein => Err(PyValueError::new_err("FASTQ reads are nucleotide sequences")),
+    }
+}
+
+fn feature(f: feature::Feature) -> Feature {
+    Feature {
+        kind: String::from(f.kind.to_key()),
+        label: f.label,
+        start: f.start,
+        end: f.end,
+        strand: String::from(match f.strand {
+            Strand::Forward => "forward",
+            Strand::Reverse => "reverse",
+        }),
+        qualifiers: f.qualifiers,
+    }
+}
+
+/// Every record of a GenBank file.
+#[pyfunction]
+pub fn read_genbank(py: Python<'_>, path: &str) -> PyResult<Vec<GenbankRecord>> {
+    GenbankReader::new(open(py, path)?).map(|record| {
+        let record = record.map_err(|e| format_error(py, e))?;
+        let features = record.features.into_iter()
+            .map(|f| Py::new(py, feature(f))).collect::<PyResult<_>>()?;
+        Ok(GenbankRecord {
+            name: record.name,
+            sequence: Py::new(py, DnaSequence::from(record.sequence))?,
+            topology: String::from(match record.topology {
+                Topology::Linear => "linear",
+                Topology::Circular => "circular",
+            }),
+            features,
+        })
+    }).collect()
+}
@@ -0,0 +1,28 @@
+//! Python bindings, built as the `constructor` extension module with
+//! maturin. Sequences are immutable Python objects wrapping the Rust types;
+//! errors are raised as `SequenceError` and `FormatError`, both subclasses
+//! of `ValueError`, or as `OSError` for I/O failures.
+
+mod error;
+mod sequence;
+mod io;
+
+use pyo3::prelude::*;
+
+#[pymodule]
+fn constructor(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("SequenceError", py.get_type::<error::SequenceError>())?;
+    m.add("FormatError", py.get_type::<error::FormatError>())?;
+    m.add_class::<sequence::DnaSequence>()?;
+    m.add_class::<sequence::DegenerateDnaSequence>()?;
+    m.add_class::<sequence::ProteinSequence>()?;
+    m.add_class::<io::FastaRecord>()?;
+    m.add_class::<io::FastqRecord>()?;
+    m.add_class::<io::GenbankRecord>()?;
+    m.add_class::<io::Feature>()?;
+    m.add_function(wrap_pyfunction!(io::read_fasta, m)?)?;
+    m.add_function(wrap_pyfunction!(io::read_fastq, m)?)?;
+    m.add_function(wrap_pyfunction!(io::read_genbank, m)?)?;
+    Ok(())
+}
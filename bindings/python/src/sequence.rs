@@ -0,0 +1,174 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use pyo3::prelude::*;
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::types::{PySlice, PyString};
+
+use constructor_rs::sequence::sequence::Sequence;
+use constructor_rs::sequence::nucleotide::Nucleotide;
+use constructor_rs::sequence::degenerate_nucleotide::DegenerateNucleotide;
+use constructor_rs::sequence::amino_acid::AminoAcid;
+use constructor_rs::sequence::string_io::StringIO;
+use constructor_rs::sequence::genetic_code::GeneticCode;
+
+use crate::error::sequence_error;
+
+/// The methods every sequence class shares: parsing from a string, the
+/// string and container protocols, equality and hashing. Sequences are
+/// immutable from Python, so they can be dictionary keys. Methods specific
+/// to one class follow in braces, as pyo3 takes one method block per class.
+macro_rules! sequence_class {
+    ($class:ident, $symbol:ty, { $($methods:tt)* }) => {
+        impl From<Sequence<$symbol>> for $class {
+            fn from(inner: Sequence<$symbol>) -> $class {
+                $class { inner }
+            }
+        }
+
+        #[pymethods]
+        impl $class {
+            #[new]
+            fn new(py: Python<'_>, sequence: &str) -> PyResult<$class> {
+                Sequence::from_str(sequence).map($class::from).map_err(|e| sequence_error(py, e))
+            }
+
+            fn __len__(&self) -> usize {
+                self.inner.len()
+            }
+
+            /// One symbol as a string for an integer index, or a sequence of
+            /// the same kind for a slice.
+            fn __getitem__(&self, index: &Bound<'_, PyAny>) -> PyResult<Py<PyAny>> {
+                let py = index.py();
+                let length = self.inner.len();
+                if let Ok(slice) = index.cast::<PySlice>() {
+                    let range = slice.indices(length as isize)?;
+                    let count = range.slicelength as usize;
+                    let step = range.step.unsigned_abs();
+                    let start = range.start.max(0) as usize;
+                    let symbols: Box<dyn Iterator<Item = &$symbol>> = if range.step > 0 {
+                        Box::new(self.inner.iter().skip(start))
+                    } else {
+                        Box::new(self.inner.iter().rev().skip(length.saturating_sub(start + 1)))
+                    };
+                    let mut subsequence = Sequence::<$symbol>::new();
+                    for symbol in symbols.step_by(step).take(count) {
+                        subsequence.push(symbol.clone());
+                    }
+                    return Ok(Py::new(py, $class::from(subsequence))?.into_any());
+                }
+                let i: isize = index.extract()?;
+                let position = if i < 0 { i + length as isize } else { i };
+                if position < 0 || position >= length as isize {
+                    return Err(PyIndexError::new_err("sequence index out of range"));
+                }
+                let symbol = self.inner.get(position as usize).map_err(|e| sequence_error(py, e))?;
+                Ok(PyString::new(py, &symbol.to_char().to_string()).into_any().unbind())
+            }
+
+            /// The symbols in order, as one-character strings.
+            fn __iter__(&self) -> SequenceIterator {
+                SequenceIterator::new(self.inner.iter().map(|symbol| symbol.to_char()).collect())
+            }
+
+            fn __str__(&self) -> String {
+                self.inner.to_string()
+            }
+
+            fn __repr__(&self) -> String {
+                format!("{}('{}')", stringify!($class), self.inner.to_string())
+            }
+
+            fn __eq__(&self, other: PyRef<'_, $class>) -> bool {
+                self.inner == other.inner
+            }
+
+            fn __hash__(&self) -> u64 {
+                let mut hasher = DefaultHasher::new();
+                self.inner.to_string().hash(&mut hasher);
+                hasher.finish()
+            }
+
+            $($methods)*
+        }
+    };
+}
+
+/// Iterates over the symbols of a sequence, yielding each as a
+/// one-character string.
+#[pyclass(module = "constructor")]
+pub struct SequenceIterator {
+    symbols: std::vec::IntoIter<char>,
+}
+
+impl SequenceIterator {
+    fn new(symbols: Vec<char>) -> SequenceIterator {
+        SequenceIterator { symbols: symbols.into_iter() }
+    }
+}
+
+#[pymethods]
+impl SequenceIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<String> {
+        self.symbols.next().map(String::from)
+    }
+}
+
+/// An unambiguous DNA sequence.
+#[pyclass(module = "constructor", frozen, skip_from_py_object)]
+pub struct DnaSequence {
+    pub inner: Sequence<Nucleotide>,
+}
+
+/// A DNA sequence that may contain IUPAC ambiguity codes.
+#[pyclass(module = "constructor", frozen, skip_from_py_object)]
+pub struct DegenerateDnaSequence {
+    pub inner: Sequence<DegenerateNucleotide>,
+}
+
+/// A protein sequence, with `*` for stops.
+#[pyclass(module = "constructor", frozen, skip_from_py_object)]
+pub struct ProteinSequence {
+    pub inner: Sequence<AminoAcid>,
+}
+
+sequence_class!(DnaSequence, Nucleotide, {
+    fn reverse_complement(&self) -> DnaSequence {
+        DnaSequence::from(self.inner.reverse_complement())
+    }
+
+    /// Translate complete codons with the NCBI translation table `table`;
+    /// trailing bases that do not fill a codon are ignored.
+    #[pyo3(signature = (table=1))]
+    fn translate(&self, table: u8) -> PyResult<ProteinSequence> {
+        let code = GeneticCode::from_id(table)
+            .ok_or_else(|| PyValueError::new_err(format!("no translation table {}", table)))?;
+        Ok(ProteinSequence::from(self.inner.translate_with(code)))
+    }
+
+    /// The complete codons in frame from the first base, as strings.
+    fn codons(&self) -> Vec<String> {
+        self.inner.codons().map(|codon| codon.to_string()).collect()
+    }
+
+    fn gc_content(&self) -> f64 {
+        self.inner.gc_content()
+    }
+});
+
+sequence_class!(DegenerateDnaSequence, DegenerateNucleotide, {
+    fn reverse_complement(&self) -> DegenerateDnaSequence {
+        DegenerateDnaSequence::from(self.inner.reverse_complement())
+    }
+
+    fn codons(&self) -> Vec<String> {
+        self.inner.codons().map(|codon| codon.to_string()).collect()
+    }
+});
+
+sequence_class!(ProteinSequence, AminoAcid, {});
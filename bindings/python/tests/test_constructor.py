@@ -0,0 +1,108 @@
+"""Tests for the Python bindings.
+
+Build and install the extension into the active environment, then run
+pytest from this directory:
+
+    maturin develop --extras test
+    pytest
+"""
+
+import pytest
+
+import constructor
+from constructor import (DnaSequence, DegenerateDnaSequence, ProteinSequence,
+                         SequenceError, FormatError)
+
+
+GENBANK = """\
+LOCUS       pTiny                     40 bp    DNA     circular SYN 01-JAN-2020
+FEATURES             Location/Qualifiers
+     CDS             complement(4..12)
+                     /gene="tinA"
+     misc_feature    join(36..40,1..3)
+                     /label=wrap
+ORIGIN
+        1 atgcatgcat gcatgcatgc atgcatgcat gcatgcatgc
+//
+"""
+
+
+def test_protocols():
+    seq = DnaSequence("ATGAAATAG")
+    assert len(seq) == 9
+    assert str(seq) == "ATGAAATAG"
+    assert repr(seq) == "DnaSequence('ATGAAATAG')"
+    assert seq[0] == "A" and seq[-1] == "G"
+    assert seq[3:6] == DnaSequence("AAA")
+    assert str(seq[::-1]) == "GATAAAGTA"
+    assert str(seq[7:1:-2]) == "AAA"
+    assert list(seq[:3]) == ["A", "T", "G"]
+    assert "".join(seq) == "ATGAAATAG"
+    assert {seq: 1}[DnaSequence("atgaaatag")] == 1
+    with pytest.raises(IndexError):
+        seq[9]
+
+
+def test_operations():
+    seq = DnaSequence("ATGGCCTAA")
+    assert str(seq.reverse_complement()) == "TTAGGCCAT"
+    assert seq.translate() == ProteinSequence("MA*")
+    # Table 2, vertebrate mitochondrial, reads AGA as a stop.
+    assert str(DnaSequence("AGA").translate(2)) == "*"
+    assert seq.codons() == ["ATG", "GCC", "TAA"]
+    assert seq.gc_content() == pytest.approx(4 / 9)
+    degenerate = DegenerateDnaSequence("NNKGAS")
+    assert str(degenerate.reverse_complement()) == "STCMNN"
+    assert degenerate.codons() == ["NNK", "GAS"]
+    with pytest.raises(ValueError):
+        seq.translate(99)
+
+
+def test_errors():
+    with pytest.raises(SequenceError) as caught:
+        DnaSequence("ACGN")
+    assert (caught.value.symbol, caught.value.offset) == ("N", 3)
+    assert "position 4" in str(caught.value)
+    assert issubclass(SequenceError, ValueError)
+    assert issubclass(FormatError, ValueError)
+    with pytest.raises(SequenceError):
+        ProteinSequence("MK1")
+
+
+def test_fasta(tmp_path):
+    path = tmp_path / "reads.fa"
+    path.write_text(">one first\nATGC\nATGC\n>two\nNNKN\n")
+    with pytest.raises(SequenceError) as caught:
+        constructor.read_fasta(str(path))
+    assert caught.value.line == 5
+    records = constructor.read_fasta(str(path), alphabet="degenerate")
+    assert [r.id for r in records] == ["one", "two"]
+    assert records[0].description == "first"
+    assert str(records[0].sequence) == "ATGCATGC"
+    assert isinstance(records[1].sequence, DegenerateDnaSequence)
+    path.write_text("ATGC\n")
+    with pytest.raises(FormatError):
+        constructor.read_fasta(str(path))
+    with pytest.raises(OSError):
+        constructor.read_fasta(str(tmp_path / "missing.fa"))
+
+
+def test_fastq(tmp_path):
+    path = tmp_path / "reads.fq"
+    path.write_text("@r1\nACGT\n+\nII#!\n")
+    read = constructor.read_fastq(str(path))[0]
+    assert (read.id, str(read.sequence)) == ("r1", "ACGT")
+    assert list(read.quality) == [40, 40, 2, 0]
+
+
+def test_genbank(tmp_path):
+    path = tmp_path / "tiny.gb"
+    path.write_text(GENBANK)
+    record = constructor.read_genbank(str(path))[0]
+    assert (record.name, record.topology) == ("pTiny", "circular")
+    assert len(record.sequence) == 40
+    cds, wrap = record.features
+    assert (cds.kind, cds.label, cds.strand) == ("CDS", "tinA", "reverse")
+    assert (cds.start, cds.end) == (3, 12)
+    assert ("gene", "tinA") in cds.qualifiers
+    assert (wrap.start, wrap.end) == (35, 43)
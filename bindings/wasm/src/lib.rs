@@ -0,0 +1,126 @@
+//! WebAssembly bindings for the sequence core, for use from JavaScript and
+//! TypeScript through wasm-bindgen. Build with
+//! `wasm-pack build --target web` (or `--target nodejs`); the generated
+//! `.d.ts` types the API below. Failures are thrown as `Error`s carrying
+//! the Rust error's message.
+
+#![allow(clippy::inherent_to_string)]
+
+use wasm_bindgen::prelude::*;
+
+use constructor::sequence::sequence::Sequence;
+use constructor::sequence::nucleotide::Nucleotide;
+use constructor::sequence::degenerate_nucleotide::DegenerateNucleotide;
+use constructor::sequence::amino_acid::AminoAcid;
+use constructor::sequence::genetic_code::GeneticCode;
+use constructor::sequence::feature::Topology;
+use constructor::sequence::strand::Strand;
+use constructor::search;
+
+/// An unambiguous DNA sequence.
+#[wasm_bindgen]
+pub struct DnaSequence {
+    inner: Sequence<Nucleotide>,
+}
+
+/// A protein sequence, with `*` for stops.
+#[wasm_bindgen]
+pub struct ProteinSequence {
+    inner: Sequence<AminoAcid>,
+}
+
+/// An occurrence of a search pattern, zero-based and half-open on the
+/// forward strand. On a circular sequence a match spanning the origin has an
+/// `end` past the sequence length.
+#[wasm_bindgen]
+pub struct SearchMatch {
+    pub start: usize,
+    pub end: usize,
+    reverse: bool,
+}
+
+#[wasm_bindgen]
+impl DnaSequence {
+    #[wasm_bindgen(constructor)]
+    pub fn new(sequence: &str) -> Result<DnaSequence, JsError> {
+        Ok(DnaSequence { inner: Sequence::from_str(sequence)? })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_string(&self) -> String {
+        self.inner.to_string()
+    }
+
+    #[wasm_bindgen(js_name = reverseComplement)]
+    pub fn reverse_complement(&self) -> DnaSequence {
+        DnaSequence { inner: self.inner.reverse_complement() }
+    }
+
+    /// Translate complete codons with the NCBI translation table `table`,
+    /// the standard code if omitted; trailing bases that do not fill a codon
+    /// are ignored.
+    pub fn translate(&self, table: Option<u8>) -> Result<ProteinSequence, JsError> {
+        let table = table.unwrap_or(1);
+        let code = GeneticCode::from_id(table)
+            .ok_or_else(|| JsError::new(&format!("No translation table {}", table)))?;
+        Ok(ProteinSequence { inner: self.inner.translate_with(code) })
+    }
+
+    /// The complete codons in frame from the first base.
+    pub fn codons(&self) -> Vec<String> {
+        self.inner.codons().map(|codon| codon.to_string()).collect()
+    }
+
+    #[wasm_bindgen(js_name = gcContent)]
+    pub fn gc_content(&self) -> f64 {
+        self.inner.gc_content()
+    }
+
+    /// Every occurrence of a pattern, which may use IUPAC ambiguity codes,
+    /// on both strands in order of position.
+    pub fn search(&self, pattern: &str, circular: bool) -> Result<Vec<SearchMatch>, JsError> {
+        let pattern = Sequence::<DegenerateNucleotide>::from_str(pattern)?;
+        let topology = if circular { Topology::Circular } else { Topology::Linear };
+        Ok(search::search(&self.inner, &pattern, topology).into_iter()
+           .map(|m| SearchMatch { start: m.start, end: m.end, reverse: m.strand == Strand::Reverse })
+           .collect())
+    }
+}
+
+#[wasm_bindgen]
+impl ProteinSequence {
+    #[wasm_bindgen(constructor)]
+    pub fn new(sequence: &str) -> Result<ProteinSequence, JsError> {
+        Ok(ProteinSequence { inner: Sequence::from_str(sequence)? })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_string(&self) -> String {
+        self.inner.to_string()
+    }
+}
+
+#[wasm_bindgen]
+impl SearchMatch {
+    /// `"forward"` or `"reverse"`.
+    #[wasm_bindgen(getter)]
+    pub fn strand(&self) -> String {
+        String::from(if self.reverse { "reverse" } else { "forward" })
+    }
+}
+
+/// Reverse complement of a DNA string, which may use IUPAC ambiguity codes.
+#[wasm_bindgen(js_name = reverseComplement)]
+pub fn reverse_complement(sequence: &str) -> Result<String, JsError> {
+    Ok(Sequence::<DegenerateNucleotide>::from_str(sequence)?.reverse_complement().to_string())
+}
@@ -0,0 +1,40 @@
+//! Run headless under Node with
+//! `wasm-pack test --node`, or `cargo test --target wasm32-unknown-unknown`
+//! from this crate's directory with `wasm-bindgen-test-runner` installed.
+
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+use constructor_wasm::{reverse_complement, DnaSequence, ProteinSequence};
+
+#[wasm_bindgen_test]
+fn sequence() {
+    let seq = DnaSequence::new("atggcctaa").unwrap();
+    assert_eq!(seq.length(), 9);
+    assert_eq!(seq.to_string(), "ATGGCCTAA");
+    assert_eq!(seq.reverse_complement().to_string(), "TTAGGCCAT");
+    assert_eq!(seq.codons(), vec!["ATG", "GCC", "TAA"]);
+    assert_eq!(reverse_complement("NNK").unwrap(), "MNN");
+    assert!(DnaSequence::new("ACGN").is_err());
+}
+
+#[wasm_bindgen_test]
+fn translate() {
+    let seq = DnaSequence::new("ATGAGATAA").unwrap();
+    assert_eq!(seq.translate(None).unwrap().to_string(), "MR*");
+    assert_eq!(seq.translate(Some(2)).unwrap().to_string(), "M**");
+    assert!(seq.translate(Some(99)).is_err());
+    assert_eq!(ProteinSequence::new("MR*").unwrap().length(), 3);
+}
+
+#[wasm_bindgen_test]
+fn search() {
+    let seq = DnaSequence::new("GAATTCAAGGTCTCAAGAGACCTT").unwrap();
+    let hits = seq.search("GGTCTC", false).unwrap();
+    assert_eq!(hits.len(), 2);
+    assert_eq!((hits[0].start, hits[0].end, hits[0].strand()), (8, 14, String::from("forward")));
+    assert_eq!((hits[1].start, hits[1].strand()), (16, String::from("reverse")));
+    let wrapped = DnaSequence::new("TCAAAAGAAT").unwrap();
+    assert_eq!(wrapped.search("GAATTC", true).unwrap()[0].end, 12);
+}
@@ -0,0 +1,288 @@
+use sequence::sequence::Sequence;
+use sequence::string_io::StringIO;
+
+const NEG: i32 = i32::MIN / 4;
+
+/// One column of a pairwise alignment, read from the query's point of view.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum AlignmentOp {
+    Match,
+    Mismatch,
+    /// A query base with no counterpart in the target.
+    Insertion,
+    /// A target base with no counterpart in the query.
+    Deletion,
+}
+
+/// Affine gap scores: a gap of length `n` costs `gap_open + n * gap_extend`.
+#[derive(Clone, Debug)]
+pub struct Scoring {
+    pub match_score: i32,
+    pub mismatch: i32,
+    pub gap_open: i32,
+    pub gap_extend: i32,
+}
+
+impl Default for Scoring {
+    fn default() -> Scoring {
+        Scoring { match_score: 2, mismatch: -4, gap_open: -4, gap_extend: -2 }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alignment {
+    pub score: i32,
+    pub query_start: usize,
+    pub query_end: usize,
+    pub target_start: usize,
+    pub target_end: usize,
+    pub ops: Vec<AlignmentOp>,
+}
+
+impl Alignment {
+    /// Fraction of alignment columns that are matches.
+    pub fn identity(&self) -> f64 {
+        if self.ops.is_empty() {
+            return 0.0;
+        }
+        let matches = self.ops.iter().filter(|&&op| op == AlignmentOp::Match).count();
+        matches as f64 / self.ops.len() as f64
+    }
+    /// Render the operations as a CIGAR string using `=`, `X`, `I` and `D`.
+    pub fn cigar(&self) -> String {
+        let mut cigar = String::new();
+        let mut run: Option<(AlignmentOp, usize)> = None;
+        for &op in self.ops.iter() {
+            run = match run {
+                Some((last, n)) if last == op => Some((last, n + 1)),
+                Some((last, n)) => {
+                    cigar.push_str(&format!("{}{}", n, cigar_char(last)));
+                    Some((op, 1))
+                },
+                None => Some((op, 1)),
+            };
+        }
+        if let Some((last, n)) = run {
+            cigar.push_str(&format!("{}{}", n, cigar_char(last)));
+        }
+        cigar
+    }
+}
+
+fn cigar_char(op: AlignmentOp) -> char {
+    match op {
+        AlignmentOp::Match => '=',
+        AlignmentOp::Mismatch => 'X',
+        AlignmentOp::Insertion => 'I',
+        AlignmentOp::Deletion => 'D',
+    }
+}
+
+/// Pairwise aligner with affine gaps.
+///
+/// Symbols are compared by their character codes, so sequences of different
+/// alphabets can be aligned against each other; an ambiguity code only
+/// matches the same code. A band, when set, limits the search to cells
+/// within that distance of the main diagonal, which keeps aligning long,
+/// nearly identical sequences affordable.
+pub struct Aligner {
+    pub scoring: Scoring,
+    pub band: Option<usize>,
+}
+
+impl Aligner {
+    pub fn new(scoring: Scoring) -> Aligner {
+        Aligner { scoring, band: None }
+    }
+
+    pub fn with_band(mut self, band: usize) -> Aligner {
+        self.band = Some(band);
+        self
+    }
+
+    /// Align both sequences end to end.
+    pub fn global<A, B>(&self, query: &Sequence<A>, target: &Sequence<B>) -> Alignment
+        where A: StringIO<N=A> + Clone, B: StringIO<N=B> + Clone {
+        self.align(&chars(query), &chars(target), false)
+    }
+
+    /// Align the whole query against any stretch of the target, leaving the
+    /// target's ends unpenalised, as when placing a read on a reference.
+    pub fn semiglobal<A, B>(&self, query: &Sequence<A>, target: &Sequence<B>) -> Alignment
+        where A: StringIO<N=A> + Clone, B: StringIO<N=B> + Clone {
+        self.align(&chars(query), &chars(target), true)
+    }
+
+    fn columns(&self, i: usize, n: usize, m: usize) -> (usize, usize) {
+        match self.band {
+            None => (0, m),
+            Some(band) => {
+                let centre = (i * m).checked_div(n).unwrap_or(0);
+                (centre.saturating_sub(band), (centre + band).min(m))
+            },
+        }
+    }
+
+    pub(crate) fn align(&self, query: &[char], target: &[char], free_target_ends: bool)
+                        -> Alignment {
+        let (n, m) = (query.len(), target.len());
+        let s = &self.scoring;
+        let open = s.gap_open + s.gap_extend;
+        // Rolling score rows for the three Gotoh states, indexed by MATCH,
+        // INSERTION and DELETION.
+        let mut prev = vec![[NEG; 3]; m+1];
+        let mut cur = vec![[NEG; 3]; m+1];
+        // Traceback, stored per row for the columns inside the band. Bits 0-1
+        // hold the state a match came from; bit 2 is set when an insertion
+        // extended another insertion, bit 3 likewise for deletions.
+        let mut trace: Vec<Vec<u8>> = Vec::with_capacity(n+1);
+        let mut bounds: Vec<(usize, usize)> = Vec::with_capacity(n+1);
+
+        let (lo, hi) = self.columns(0, n, m);
+        let mut row = vec![0u8; hi+1-lo];
+        for j in lo..hi+1 {
+            if j == 0 || free_target_ends {
+                prev[j][MATCH] = 0;
+            } else {
+                prev[j][DELETION] = open + (j as i32 - 1) * s.gap_extend;
+                row[j-lo] = if j > 1 { EXTENDED_DELETION } else { 0 };
+            }
+        }
+        trace.push(row);
+        bounds.push((lo, hi));
+
+        for i in 1..n+1 {
+            let (lo, hi) = self.columns(i, n, m);
+            for cell in cur.iter_mut() {
+                *cell = [NEG; 3];
+            }
+            let mut row = vec![0u8; hi+1-lo];
+            for j in lo..hi+1 {
+                let mut bits = 0u8;
+                if j == 0 {
+                    cur[0][INSERTION] = open + (i as i32 - 1) * s.gap_extend;
+                    row[0] = if i > 1 { EXTENDED_INSERTION } else { 0 };
+                    continue;
+                }
+                let diagonal = prev[j-1];
+                let from = best_state(&diagonal);
+                if diagonal[from] > NEG {
+                    let pair = if query[i-1] == target[j-1] { s.match_score } else { s.mismatch };
+                    cur[j][MATCH] = diagonal[from] + pair;
+                    bits |= from as u8;
+                }
+                let (opened, extended) = (prev[j][MATCH] + open, prev[j][INSERTION] + s.gap_extend);
+                cur[j][INSERTION] = if extended > opened { bits |= EXTENDED_INSERTION; extended }
+                                    else { opened };
+                let (opened, extended) = (cur[j-1][MATCH] + open, cur[j-1][DELETION] + s.gap_extend);
+                cur[j][DELETION] = if extended > opened { bits |= EXTENDED_DELETION; extended }
+                                   else { opened };
+                row[j-lo] = bits;
+            }
+            trace.push(row);
+            bounds.push((lo, hi));
+            ::std::mem::swap(&mut prev, &mut cur);
+        }
+
+        // End in the last column for a global alignment, or in the best
+        // column of the last row when the target's end is free.
+        let (lo, hi) = bounds[n];
+        let end_j = if free_target_ends {
+            (lo..hi+1).max_by_key(|&j| (prev[j][best_state(&prev[j])], ::std::cmp::Reverse(j)))
+                .unwrap()
+        } else {
+            m
+        };
+        let mut state = best_state(&prev[end_j]);
+        let score = prev[end_j][state];
+
+        let (mut i, mut j) = (n, end_j);
+        let mut ops = Vec::new();
+        while i > 0 || (j > 0 && !free_target_ends) {
+            let bits = trace[i][j - bounds[i].0];
+            match state {
+                MATCH => {
+                    ops.push(if query[i-1] == target[j-1] { AlignmentOp::Match }
+                             else { AlignmentOp::Mismatch });
+                    state = (bits & 0b11) as usize;
+                    i -= 1;
+                    j -= 1;
+                },
+                INSERTION => {
+                    ops.push(AlignmentOp::Insertion);
+                    state = if bits & EXTENDED_INSERTION != 0 { INSERTION } else { MATCH };
+                    i -= 1;
+                },
+                _ => {
+                    ops.push(AlignmentOp::Deletion);
+                    state = if bits & EXTENDED_DELETION != 0 { DELETION } else { MATCH };
+                    j -= 1;
+                },
+            }
+        }
+        ops.reverse();
+        Alignment { score, query_start: 0, query_end: n, target_start: j, target_end: end_j, ops }
+    }
+}
+
+const MATCH: usize = 0;
+const INSERTION: usize = 1;
+const DELETION: usize = 2;
+const EXTENDED_INSERTION: u8 = 0b0100;
+const EXTENDED_DELETION: u8 = 0b1000;
+
+/// The highest-scoring state of a cell, preferring a match on ties.
+fn best_state(cell: &[i32; 3]) -> usize {
+    (0..3).max_by_key(|&k| (cell[k], ::std::cmp::Reverse(k))).unwrap()
+}
+
+pub(crate) fn chars<N>(seq: &Sequence<N>) -> Vec<char> where N: StringIO<N=N> + Clone {
+    seq.iter().map(|nt| nt.to_char()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Aligner, AlignmentOp, Scoring};
+    use sequence::sequence::Sequence;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::degenerate_nucleotide::DegenerateNucleotide;
+
+    fn nts(s: &str) -> Sequence<Nucleotide> {
+        Sequence::<Nucleotide>::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn global() {
+        let aligner = Aligner::new(Scoring::default());
+        let aln = aligner.global(&nts("GATTACA"), &nts("GATTACA"));
+        assert_eq!(aln.cigar(), "7=");
+        assert_eq!(aln.score, 14);
+        let aln = aligner.global(&nts("GATTTACA"), &nts("GATTACA"));
+        assert_eq!(aln.ops.iter().filter(|&&op| op == AlignmentOp::Insertion).count(), 1);
+        assert_eq!(aln.target_end, 7);
+        let aln = aligner.global(&nts("GATCA"), &nts("GATTACA"));
+        assert_eq!(aln.cigar(), "3=2D2=");
+    }
+
+    #[test]
+    fn semiglobal() {
+        let aligner = Aligner::new(Scoring::default());
+        let read = Sequence::<DegenerateNucleotide>::from_str("TTACNGG").unwrap();
+        let aln = aligner.semiglobal(&read, &nts("CCCCGATTACAGGCCCC"));
+        assert_eq!((aln.target_start, aln.target_end), (6, 13));
+        assert_eq!(aln.cigar(), "4=1X2=");
+        let aln = aligner.semiglobal(&nts("GATTACAGATTACA"), &nts("CCGATTACAGGATTACACC"));
+        assert_eq!(aln.cigar(), "7=1D7=");
+        assert_eq!(aln.target_start, 2);
+    }
+
+    #[test]
+    fn banded() {
+        let a = nts(&"GATTACAGGCCTTAACG".repeat(20));
+        let mut b = a.clone();
+        b.push(Nucleotide::T);
+        let aln = Aligner::new(Scoring::default()).with_band(10).global(&a, &b);
+        assert_eq!(aln.ops.len(), b.len());
+        assert_eq!(aln.ops.iter().filter(|&&op| op == AlignmentOp::Deletion).count(), 1);
+    }
+}
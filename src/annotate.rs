@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+
+use sequence::sequence::Sequence;
+use sequence::nucleotide::Nucleotide;
+use sequence::strand::Strand;
+use sequence::feature::{AnnotatedSequence, Feature, FeatureKind, Topology};
+use align::{chars, Aligner, Alignment, Scoring};
+use variants::reverse_complement_chars;
+
+/// Whether a library feature is stored as bases or as the protein it
+/// encodes.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Molecule {
+    Dna,
+    Protein,
+}
+
+/// A feature common in plasmids, with the GenBank key it is annotated
+/// under. DNA features are found by their bases on either strand and
+/// protein features by their translation, so synonymous changes in a
+/// coding sequence do not hide it.
+#[derive(Debug)]
+pub struct CommonFeature {
+    pub name: &'static str,
+    pub key: &'static str,
+    pub molecule: Molecule,
+    pub sequence: &'static str,
+}
+
+static FEATURES: [CommonFeature; 24] = [
+    CommonFeature { name: "ori", key: "rep_origin", molecule: Molecule::Dna,
+                    sequence: "TTGAGATCCTTTTTTTCTGCGCGTAATCTGCTGCTTGCAAACAAAAAAACCACCGCTACCAG\
+                               CGGTGGTTTGTTTGCCGGATCAAGAGCTACCAACTCTTTTTCCGAAGGTAACTGGCTTCAGCA\
+                               GAGCGCAGATACCAAATACTGTTCTTCTAGTGTAGCCGTAGTTAGGCCACCACTTCAAGAACT\
+                               CTGTAGCACCGCCTACATACCTCGCTCTGCTAATCCTGTTACCAGTGGCTGCTGCCAGTGGCG\
+                               ATAAGTCGTGTCTTACCGGGTTGGACTCAAGACGATAGTTACCGGATAAGGCGCAGCGGTCGG\
+                               GCTGAACGGGGGGTTCGTGCACACAGCCCAGCTTGGAGCGAACGACCTACACCGAACTGAGAT\
+                               ACCTACAGCGTGAGCTATGAGAAAGCGCCACGCTTCCCGAAGGGAGAAAGGCGGACAGGTATC\
+                               CGGTAAGCGGCAGGGTCGGAACAGGAGAGCGCACGAGGGAGCTTCCAGGGGGAAACGCCTGGT\
+                               ATCTTTATAGTCCTGTCGGGTTTCGCCACCTCTGACTTGAGCGTCGATTTTTGTGATGCTCGT\
+                               CAGGGGGGCGGAGCCTATGGAAAAACGCCAGCAACGCGGCC" },
+    CommonFeature { name: "AmpR promoter", key: "promoter", molecule: Molecule::Dna,
+                    sequence: "CGCGGAACCCCTATTTGTTTATTTTTCTAAATACATTCAAATATGTATCCGCTCATGAGACA\
+                               ATAACCCTGATAAATGCTTCAATAATATTGAAAAAGGAAGAGT" },
+    CommonFeature { name: "lac promoter", key: "promoter", molecule: Molecule::Dna,
+                    sequence: "TTTACACTTTATGCTTCCGGCTCGTATGTTG" },
+    CommonFeature { name: "tac promoter", key: "promoter", molecule: Molecule::Dna,
+                    sequence: "TTGACAATTAATCATCGGCTCGTATAATG" },
+    CommonFeature { name: "T7 promoter", key: "promoter", molecule: Molecule::Dna,
+                    sequence: "TAATACGACTCACTATAGG" },
+    CommonFeature { name: "T3 promoter", key: "promoter", molecule: Molecule::Dna,
+                    sequence: "AATTAACCCTCACTAAAGG" },
+    CommonFeature { name: "SP6 promoter", key: "promoter", molecule: Molecule::Dna,
+                    sequence: "ATTTAGGTGACACTATAG" },
+    CommonFeature { name: "lac operator", key: "protein_bind", molecule: Molecule::Dna,
+                    sequence: "TTGTGAGCGGATAACAA" },
+    CommonFeature { name: "rrnB T1 terminator", key: "terminator", molecule: Molecule::Dna,
+                    sequence: "CCAGGCATCAAATAAAACGAAAGGCTCAGTCGAAAGACTGGGCCTTTCGTTTTATCTGTTGT\
+                               TTGTCGGTGAACGCTCTC" },
+    CommonFeature { name: "T7Te terminator", key: "terminator", molecule: Molecule::Dna,
+                    sequence: "TCACACTGGCTCACCTTCGGGTGAGCCTTTCTGCGTTTATA" },
+    CommonFeature { name: "T7 terminator", key: "terminator", molecule: Molecule::Dna,
+                    sequence: "CTAGCATAACCCCTTGGGGCCTCTAAACGGGTCTTGAGGGGTTTTTTG" },
+    CommonFeature { name: "AmpR", key: "CDS", molecule: Molecule::Protein,
+                    sequence: "MSIQHFRVALIPFFAAFCLPVFAHPETLVKVKDAEDQLGARVGYIELDLNSGKILESFRPEE\
+                               RFPMMSTFKVLLCGAVLSRIDAGQEQLGRRIHYSQNDLVEYSPVTEKHLTDGMTVRELCSAAI\
+                               TMSDNTAANLLLTTIGGPKELTAFLHNMGDHVTRLDRWEPELNEAIPNDERDTTMPVAMATTL\
+                               RKLLTGELLTLASRQQLIDWMEADKVAGPLLRSALPAGWFIADKSGAGERGSRGIIAALGPDG\
+                               KPSRIVVIYTTGSQATMDERNRQIAEIGASLIKHW" },
+    CommonFeature { name: "KanR", key: "CDS", molecule: Molecule::Protein,
+                    sequence: "MSHIQRETSCSRPRLNSNMDADLYGYKWARDNVGQSGATIYRLYGKPDAPELFLKHGKGSVA\
+                               NDVTDEMVRLNWLTEFMPLPTIKHFIRTPDDAWLLTTAIPGKTAFQVLEEYPDSGENIVDALA\
+                               VFLRRLHSIPVCNCPFNSDRVFRLAQAQSRMNNGLVDASDFDDERNGWPVEQVWKEMHKLLPF\
+                               SPDSVVTHGDFSLDNLIFDEGKLIGCIDVGRVGIADRYQDLAILWNCLGEFSPSLQKRLFQKY\
+                               GIDNPDMNKLQFHLMLDEFF" },
+    CommonFeature { name: "NeoR/KanR", key: "CDS", molecule: Molecule::Protein,
+                    sequence: "MIEQDGLHAGSPAAWVERLFGYDWAQQTIGCSDAAVFRLSAQGRPVLFVKTDLSGALNELQDE\
+                               AARLSWLATTGVPCAAVLDVVTEAGRDWLLLGEVPGQDLLSSHLAPAEKVSIMADAMRRLHTL\
+                               DPATCPFDHQAKHRIERARTRMEAGLVDQDDLDEEHQGLAPAELFARLKARMPDGEDLVVTHG\
+                               DACLPNIMVENGRFSGFIDCGRLGVADRYQDIALATRDIAEELGGEWADRFLVLYGIAAPDSQ\
+                               RIAFYRLLDEFF" },
+    CommonFeature { name: "CmR", key: "CDS", molecule: Molecule::Protein,
+                    sequence: "MEKKITGYTTVDISQWHRKEHFEAFQSVAQCTYNQTVQLDITAFLKTVKKNKHKFYPAFIHIL\
+                               ARLMNAHPEFRMAMKDGELVIWDSVHPCYTVFHEQTETFSSLWSEYHDDFRQFLHIYSQDVAC\
+                               YGENLAYFPKGFIENMFFVSANPWVSFTSFDLNVANMDNFFAPVFTMGKYYTQGDKVLMPLAI\
+                               QVHHAVCDGFHVGRLLNELQQYCDEWQGGA" },
+    CommonFeature { name: "lacI", key: "CDS", molecule: Molecule::Protein,
+                    sequence: "MKPVTLYDVAEYAGVSYQTVSRVVNQASHVSAKTREKVEAAMAELNYIPNRVAQQLAGKQSLL\
+                               IGVATSSLALHAPSQIVAAIKSRADQLGASVVVSMVERSGVEACKAAVHNLLAQRVSGLIINY\
+                               PLDDQDAIAVEAACTNVPALFLDVSDQTPINSIIFSHEDGTRLGVEHLVALGHQQIAMLAGPL\
+                               SSVSARLRLAGWHKYLTRNQIQPIAEREGDWSAMSGFQQTMQMLNEGIVPTAMLVANDQMALG\
+                               AMRAITESGLRVGADISVVGYDDTEDSSCYIPPLTTIKQDFRLLGQTSVDRLLQLSQGQAVKG\
+                               NQLLPVSLVKRKTTLAPNTQTASPRALADSLMQLARQVSRLESGQ" },
+    CommonFeature { name: "TetR", key: "CDS", molecule: Molecule::Protein,
+                    sequence: "MSRLDKSKVINSALELLNEVGIEGLTTRKLAQKLGVEQPTLYWHVKNKRALLDALAIEMLDRH\
+                               HTHFCPLEGESWQDFLRNNAKSFRCALLSHRDGAKVHLGTRPTEKQYETLENQLAFLCQQGFS\
+                               LENALYALSAVGHFTLGCVLEDQEHQVAKEERETPTTDSMPPLLRQAIELFDHQGAEPAFLFG\
+                               LELIICGLEKQLKCESGS" },
+    CommonFeature { name: "EGFP", key: "CDS", molecule: Molecule::Protein,
+                    sequence: "MVSKGEELFTGVVPILVELDGDVNGHKFSVSGEGEGDATYGKLTLKFICTTGKLPVPWPTLVT\
+                               TLTYGVQCFSRYPDHMKQHDFFKSAMPEGYVQERTIFFKDDGNYKTRAEVKFEGDTLVNRIEL\
+                               KGIDFKEDGNILGHKLEYNYNSHNVYIMADKQKNGIKVNFKIRHNIEDGSVQLADHYQQNTPI\
+                               GDGPVLLPDNHYLSTQSALSKDPNEKRDHMVLLEFVTAAGITLGMDELYK" },
+    CommonFeature { name: "6xHis", key: "CDS", molecule: Molecule::Protein,
+                    sequence: "HHHHHH" },
+    CommonFeature { name: "FLAG", key: "CDS", molecule: Molecule::Protein,
+                    sequence: "DYKDDDDK" },
+    CommonFeature { name: "HA", key: "CDS", molecule: Molecule::Protein,
+                    sequence: "YPYDVPDYA" },
+    CommonFeature { name: "Myc", key: "CDS", molecule: Molecule::Protein,
+                    sequence: "EQKLISEEDL" },
+    CommonFeature { name: "V5", key: "CDS", molecule: Molecule::Protein,
+                    sequence: "GKPIPNPLLGLDST" },
+    CommonFeature { name: "Strep-tag II", key: "CDS", molecule: Molecule::Protein,
+                    sequence: "WSHPQFEK" },
+];
+
+impl CommonFeature {
+    /// Look up a bundled feature by name, ignoring case.
+    pub fn from_name(name: &str) -> Option<&'static CommonFeature> {
+        FEATURES.iter().find(|f| f.name.eq_ignore_ascii_case(name))
+    }
+    pub fn all() -> &'static [CommonFeature] {
+        &FEATURES
+    }
+}
+
+/// A library feature found in a sequence. Coordinates are on the forward
+/// strand; on a circular sequence a match spanning the origin has an `end`
+/// past the sequence length, as features do.
+#[derive(Debug)]
+pub struct Annotation {
+    pub feature: &'static CommonFeature,
+    pub start: usize,
+    pub end: usize,
+    pub strand: Strand,
+    /// Fraction of alignment columns, in bases or residues, that match the
+    /// library sequence.
+    pub identity: f64,
+}
+
+impl Annotation {
+    /// The match as a feature labelled with the library name, with its
+    /// percent identity in a note.
+    pub fn to_feature(&self) -> Feature {
+        let mut feature = Feature::new(FeatureKind::from_key(self.feature.key), self.feature.name,
+                                       self.start, self.end, self.strand);
+        feature.qualifiers.push((String::from("note"),
+                                 format!("{:.1}% identity to common feature {}",
+                                         self.identity * 100.0, self.feature.name)));
+        feature
+    }
+}
+
+/// Seed length for DNA and protein searches, shortened for features shorter
+/// than this.
+const DNA_SEED: usize = 12;
+const PROTEIN_SEED: usize = 5;
+/// Candidate placements tried per feature and searched sequence, best
+/// supported first.
+const CANDIDATES: usize = 20;
+
+/// One sequence searched for library features: a strand, or a reading
+/// frame of one, with an index of its k-mers.
+struct Target {
+    symbols: Vec<char>,
+    index: HashMap<Vec<char>, Vec<usize>>,
+    k: usize,
+}
+
+impl Target {
+    fn new(symbols: Vec<char>, k: usize) -> Target {
+        let mut index: HashMap<Vec<char>, Vec<usize>> = HashMap::new();
+        for (i, window) in symbols.windows(k).enumerate() {
+            index.entry(window.to_vec()).or_default().push(i);
+        }
+        Target { symbols, index, k }
+    }
+
+    /// Alignments of the whole query to stretches of the target with at
+    /// least `min_identity`, not overlapping one another.
+    fn find(&self, aligner: &Aligner, query: &[char], min_identity: f64) -> Vec<Alignment> {
+        let k = self.k.min(query.len());
+        let mut votes: HashMap<isize, usize> = HashMap::new();
+        for (offset, window) in query.windows(k).enumerate() {
+            let hits = if k == self.k {
+                self.index.get(window).cloned().unwrap_or_default()
+            } else {
+                self.symbols.windows(k).enumerate()
+                    .filter(|&(_, w)| w == window).map(|(i, _)| i).collect()
+            };
+            for hit in hits {
+                *votes.entry(hit as isize - offset as isize).or_insert(0) += 1;
+            }
+        }
+        let mut diagonals: Vec<(isize, usize)> = votes.into_iter().collect();
+        diagonals.sort_by_key(|&(diagonal, count)| (usize::MAX - count, diagonal));
+        let band = 8 + query.len() / 10;
+        let mut found: Vec<Alignment> = Vec::new();
+        let mut tried: Vec<isize> = Vec::new();
+        for (diagonal, _) in diagonals.into_iter().take(CANDIDATES) {
+            // Seeds either side of an indel vote for nearby diagonals; one
+            // alignment covers them all.
+            if tried.iter().any(|&other| (other - diagonal).unsigned_abs() <= band) {
+                continue;
+            }
+            tried.push(diagonal);
+            let start = (diagonal - band as isize).max(0) as usize;
+            let end = ((diagonal + (query.len() + band) as isize).max(0) as usize)
+                .min(self.symbols.len());
+            if start >= end {
+                continue;
+            }
+            let mut alignment = aligner.align(query, &self.symbols[start..end], true);
+            if alignment.identity() < min_identity {
+                continue;
+            }
+            alignment.target_start += start;
+            alignment.target_end += start;
+            if !found.iter().any(|other| alignment.target_start < other.target_end
+                                 && other.target_start < alignment.target_end) {
+                found.push(alignment);
+            }
+        }
+        found
+    }
+}
+
+/// Search a sequence for every bundled feature, DNA features on both
+/// strands and protein features in all six reading frames, reporting
+/// matches with at least `min_identity`, between 0 and 1. Results are
+/// ordered by position.
+///
+/// Matching is meant for features that are present nearly intact: each
+/// library sequence must align end to end, and candidate placements are
+/// found from exact seeds of 12 bases or 5 residues.
+pub fn find_features(sequence: &Sequence<Nucleotide>, topology: Topology, min_identity: f64)
+                     -> Vec<Annotation> {
+    let length = sequence.len();
+    let mut annotations = Vec::new();
+    if length == 0 {
+        return annotations;
+    }
+    let circular = topology == Topology::Circular;
+    let forward = chars(sequence);
+    let reverse = reverse_complement_chars(&forward);
+    // Each searched sequence with its strand and, for a reading frame, the
+    // offset of its first codon; protein positions are codon numbers.
+    let mut targets: Vec<(Strand, Option<usize>, Target)> = Vec::new();
+    for &(strand, bases) in [(Strand::Forward, &forward), (Strand::Reverse, &reverse)].iter() {
+        let mut bases = bases.to_vec();
+        if circular {
+            // Search twice round so matches spanning the origin are whole.
+            bases.extend_from_within(..);
+        }
+        targets.push((strand, None, Target::new(bases.clone(), DNA_SEED)));
+        let dna = Sequence::<Nucleotide>::from_str(&bases.iter().collect::<String>()).unwrap();
+        for frame in 0..3.min(bases.len()) {
+            let protein = dna.subsequence(frame, dna.len()).unwrap().translate().unwrap();
+            targets.push((strand, Some(frame), Target::new(chars(&protein), PROTEIN_SEED)));
+        }
+    }
+
+    let aligner = Aligner::new(Scoring::default());
+    for feature in FEATURES.iter() {
+        let query: Vec<char> = feature.sequence.chars().collect();
+        for &(strand, frame, ref target) in targets.iter() {
+            if frame.is_some() != (feature.molecule == Molecule::Protein) {
+                continue;
+            }
+            for alignment in target.find(&aligner, &query, min_identity) {
+                // Bases on the searched strand.
+                let (from, to) = match frame {
+                    Some(frame) => (frame + 3 * alignment.target_start,
+                                    frame + 3 * alignment.target_end),
+                    None => (alignment.target_start, alignment.target_end),
+                };
+                // Matches starting in the second copy round a circle repeat
+                // ones in the first.
+                if from >= length {
+                    continue;
+                }
+                let (start, end) = match strand {
+                    Strand::Forward => (from, to),
+                    Strand::Reverse if circular => {
+                        let start = (length as isize - to as isize).rem_euclid(length as isize);
+                        (start as usize, start as usize + to - from)
+                    },
+                    Strand::Reverse => (length - to, length - from),
+                };
+                annotations.push(Annotation { feature, start, end, strand,
+                                              identity: alignment.identity() });
+            }
+        }
+    }
+    annotations.sort_by_key(|a| (a.start, a.end, a.feature.name));
+    annotations
+}
+
+/// Add the bundled features found in a record as annotations, skipping any
+/// already annotated with the same label, location and strand. Returns the
+/// features added.
+pub fn annotate(record: &mut AnnotatedSequence, min_identity: f64) -> Vec<Feature> {
+    let mut added = Vec::new();
+    for annotation in find_features(&record.sequence, record.topology, min_identity) {
+        let feature = annotation.to_feature();
+        if !record.features.iter().any(|f| f.label == feature.label && f.start == feature.start
+                                       && f.end == feature.end && f.strand == feature.strand) {
+            record.features.push(feature.clone());
+            added.push(feature);
+        }
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{annotate, find_features, CommonFeature};
+    use sequence::sequence::Sequence;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::strand::Strand;
+    use sequence::feature::{AnnotatedSequence, Topology};
+
+    fn reverse_complement(seq: &str) -> String {
+        Sequence::<Nucleotide>::from_str(seq).unwrap().reverse_complement().to_string()
+    }
+
+    #[test]
+    fn find() {
+        // A vector with the T7 promoter, a FLAG tag encoded with codons the
+        // library knows nothing about, the T7 terminator reversed, and the
+        // lac operator with one base changed spanning the origin.
+        let t7 = CommonFeature::from_name("t7 promoter").unwrap().sequence;
+        let terminator = CommonFeature::from_name("T7 terminator").unwrap().sequence;
+        let flag = "GACTACAAAGACGATGACGACAAG";
+        let operator = "TTGTGAGCGGATTACAA";
+        let seq = format!("{}GCAT{}CCTTAAGGCATGCCAGTCCAATTTCGGACGAT{}GTCA{}",
+                          &operator[8..], t7, flag, reverse_complement(terminator));
+        let seq = format!("{}{}", seq, &operator[..8]);
+        let length = seq.len();
+        let seq = Sequence::<Nucleotide>::from_str(&seq).unwrap();
+
+        let found = find_features(&seq, Topology::Circular, 0.9);
+        let summary: Vec<(&str, usize, usize, Strand)> = found.iter()
+            .map(|a| (a.feature.name, a.start, a.end, a.strand)).collect();
+        assert_eq!(summary, vec![("T7 promoter", 13, 32, Strand::Forward),
+                                 ("FLAG", 64, 88, Strand::Forward),
+                                 ("T7 terminator", 92, 140, Strand::Reverse),
+                                 ("lac operator", length - 8, length + 9, Strand::Forward)]);
+        assert_eq!(found[0].identity, 1.0);
+        assert!((found[3].identity - 16.0 / 17.0).abs() < 1e-9);
+        // Without wrapping round, the operator is cut in two and not found.
+        assert_eq!(find_features(&seq, Topology::Linear, 0.9).len(), 3);
+        // It is not close enough for a stricter search.
+        assert_eq!(find_features(&seq, Topology::Circular, 0.95).len(), 3);
+
+        let mut record = AnnotatedSequence::new("pTest", seq, Topology::Circular);
+        let added = annotate(&mut record, 0.9);
+        assert_eq!(added.len(), 4);
+        assert_eq!(added[3].qualifiers[0].1, "94.1% identity to common feature lac operator");
+        assert!(annotate(&mut record, 0.9).is_empty());
+        assert_eq!(record.features.len(), 4);
+    }
+
+    #[test]
+    fn protein() {
+        // AmpR back-translated with one codon per residue, on the reverse
+        // strand, with two residues changed.
+        let ampr = CommonFeature::from_name("AmpR").unwrap().sequence;
+        let codon = |aa: char| match aa {
+            'A' => "GCT", 'C' => "TGC", 'D' => "GAT", 'E' => "GAA", 'F' => "TTC", 'G' => "GGT",
+            'H' => "CAT", 'I' => "ATC", 'K' => "AAA", 'L' => "CTG", 'M' => "ATG", 'N' => "AAC",
+            'P' => "CCG", 'Q' => "CAG", 'R' => "CGT", 'S' => "TCT", 'T' => "ACC", 'V' => "GTT",
+            'W' => "TGG", 'Y' => "TAC", _ => unreachable!(),
+        };
+        let mut gene: String = ampr.chars().map(codon).collect();
+        gene.replace_range(30..33, "TGG");
+        gene.replace_range(600..603, "TGG");
+        let seq = format!("ACGTACGTAC{}TAAGCGGCCGC", gene);
+        let seq = Sequence::<Nucleotide>::from_str(&reverse_complement(&seq)).unwrap();
+        let found = find_features(&seq, Topology::Linear, 0.9);
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].feature.name, found[0].start, found[0].end, found[0].strand),
+                   ("AmpR", 11, 11 + 3 * 286, Strand::Reverse));
+        assert!((found[0].identity - 284.0 / 286.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,604 @@
+use std::fs::File;
+use std::io::{self, stdout, stderr, BufWriter, Write};
+
+use argparse::{ArgumentParser, Store, StoreTrue, List, Collect};
+
+use constructor::{AnnotatedSequence, Feature, FeatureKind, Nucleotide, Sequence, Strand, Topology};
+use constructor::sequence::degenerate_nucleotide::DegenerateNucleotide;
+use constructor::sequence::genetic_code::GeneticCode;
+use constructor::io::fasta::{FastaRecord, FastaWriter};
+use constructor::io::genbank::GenbankWriter;
+use constructor::io::sbol::{SbolDocument, SbolWriter, RdfSyntax};
+use constructor::orf::find_orfs;
+use constructor::digest::{digest as cut, Enzyme};
+use constructor::search::search as find;
+use constructor::registry::{Entry, Registry};
+use constructor::annotate::{annotate as add_features, find_features};
+
+use cli::CliError;
+use cli::input::{for_each_record, InputFormat, Record};
+use cli::output::{Format, Output, Value};
+
+/// Options shared by every command.
+struct Common {
+    format: Format,
+    input_format: InputFormat,
+    output: String,
+    files: Vec<String>,
+}
+
+impl Common {
+    fn new() -> Common {
+        Common { format: Format::Text, input_format: InputFormat::Auto, output: String::new(),
+                 files: Vec::new() }
+    }
+
+    fn register<'a>(&'a mut self, ap: &mut ArgumentParser<'a>, with_format: bool) {
+        if with_format {
+            ap.refer(&mut self.format)
+                .add_option(&["-f", "--format"], Store, "Output format: text, json or tsv");
+        }
+        ap.refer(&mut self.input_format)
+            .add_option(&["-i", "--input-format"], Store, "Input format: auto, fasta, genbank or sbol");
+        ap.refer(&mut self.output)
+            .add_option(&["-o", "--output"], Store, "Write to this file instead of standard output");
+        ap.refer(&mut self.files)
+            .add_argument("files", List, "FASTA, GenBank or SBOL files; standard input if none or -");
+    }
+
+    fn writer(&self) -> Result<Box<dyn Write>, CliError> {
+        if self.output.is_empty() || self.output == "-" {
+            Ok(Box::new(BufWriter::new(stdout())))
+        } else {
+            let file = File::create(&self.output).map_err(|e| CliError::Output(io::Error::new(
+                e.kind(), format!("{}: {}", self.output, e))))?;
+            Ok(Box::new(BufWriter::new(file)))
+        }
+    }
+
+    fn each_record<F>(&self, handle: F) -> Result<(), CliError>
+        where F: FnMut(Record) -> Result<(), CliError> {
+        for_each_record(&self.files, self.input_format, handle)
+    }
+}
+
+fn parse(ap: &ArgumentParser, args: Vec<String>) -> Result<(), CliError> {
+    ap.parse(args, &mut stdout(), &mut stderr()).map_err(CliError::Parse)
+}
+
+fn genetic_code(table: u8) -> Result<&'static GeneticCode, CliError> {
+    GeneticCode::from_id(table).ok_or_else(|| {
+        let known: Vec<String> = GeneticCode::all().iter().map(|c| c.id.to_string()).collect();
+        CliError::Usage(format!("unknown genetic code {}; choose one of {}", table,
+                                known.join(", ")))
+    })
+}
+
+/// A FASTA entry as text, wrapped at 60 characters.
+fn fasta(id: &str, description: Option<&str>, sequence: &str) -> String {
+    let mut text = match description {
+        Some(description) => format!(">{} {}\n", id, description),
+        None => format!(">{}\n", id),
+    };
+    for chunk in sequence.as_bytes().chunks(60) {
+        text.push_str(&String::from_utf8_lossy(chunk));
+        text.push('\n');
+    }
+    text
+}
+
+fn strand_symbol(strand: Strand) -> &'static str {
+    match strand {
+        Strand::Forward => "+",
+        Strand::Reverse => "-",
+    }
+}
+
+fn id(record: &Record) -> Value {
+    Value::from(&record.sequence.name[..])
+}
+
+/// A record's sequence as unambiguous bases, for commands that cannot work
+/// with IUPAC codes.
+fn strict(record: &AnnotatedSequence<DegenerateNucleotide>)
+          -> Result<AnnotatedSequence, CliError> {
+    let sequence = Sequence::<Nucleotide>::from_str(&record.sequence.to_string())
+        .map_err(|e| CliError::Ambiguous(record.name.clone(), e))?;
+    let mut strict = AnnotatedSequence::new(&record.name, sequence, record.topology);
+    strict.features = record.features.clone();
+    Ok(strict)
+}
+
+pub fn revcomp(args: Vec<String>) -> Result<(), CliError> {
+    let mut common = Common::new();
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Reverse-complement each sequence.");
+        common.register(&mut ap, true);
+        parse(&ap, args)?;
+    }
+    let mut out = Output::new(common.writer()?, common.format);
+    common.each_record(|record| {
+        let rc = record.sequence.sequence.reverse_complement().to_string();
+        let text = fasta(&record.sequence.name, record.description.as_deref(), &rc);
+        out.record(&[("id", id(&record)), ("sequence", Value::from(rc))], &text)?;
+        Ok(())
+    })?;
+    out.finish()?;
+    Ok(())
+}
+
+pub fn translate(args: Vec<String>) -> Result<(), CliError> {
+    let mut common = Common::new();
+    let mut frame = 1i32;
+    let mut table = 1u8;
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Translate each sequence into protein.");
+        ap.refer(&mut frame)
+            .add_option(&["--frame"], Store,
+                        "Reading frame: 1, 2 or 3 on the forward strand, -1, -2 or -3 on the \
+                         reverse (default 1)");
+        ap.refer(&mut table)
+            .add_option(&["--table"], Store, "NCBI genetic code number (default 1)");
+        common.register(&mut ap, true);
+        parse(&ap, args)?;
+    }
+    if frame == 0 || frame.abs() > 3 {
+        return Err(CliError::Usage(format!("frame must be 1, 2, 3, -1, -2 or -3, not {}", frame)));
+    }
+    let code = genetic_code(table)?;
+    let mut out = Output::new(common.writer()?, common.format);
+    common.each_record(|record| {
+        let seq = strict(&record.sequence)?.sequence;
+        let strand = if frame > 0 { seq } else { seq.reverse_complement() };
+        let offset = (frame.unsigned_abs() as usize - 1).min(strand.len());
+        let protein = strand.subsequence(offset, strand.len())?.translate_with(code).to_string();
+        let text = fasta(&record.sequence.name, record.description.as_deref(), &protein);
+        out.record(&[("id", id(&record)), ("frame", Value::Int(frame as i64)),
+                     ("protein", Value::from(protein))], &text)?;
+        Ok(())
+    })?;
+    out.finish()?;
+    Ok(())
+}
+
+pub fn orfs(args: Vec<String>) -> Result<(), CliError> {
+    let mut common = Common::new();
+    let mut min_codons = 30usize;
+    let mut table = 1u8;
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("List open reading frames on both strands. Positions are one-based \
+                            and inclusive; on circular sequences an ORF crossing the origin \
+                            ends past the sequence length.");
+        ap.refer(&mut min_codons)
+            .add_option(&["--min-codons"], Store,
+                        "Shortest ORF to report, in codons excluding the stop (default 30)");
+        ap.refer(&mut table)
+            .add_option(&["--table"], Store, "NCBI genetic code number (default 1)");
+        common.register(&mut ap, true);
+        parse(&ap, args)?;
+    }
+    let code = genetic_code(table)?;
+    let mut out = Output::new(common.writer()?, common.format);
+    common.each_record(|record| {
+        let seq = strict(&record.sequence)?;
+        for orf in find_orfs(&seq.sequence, seq.topology, code, min_codons).iter() {
+            let protein = orf.protein.to_string();
+            let text = format!("{}\t{}..{}\t{}\t{} aa\t{}\n", seq.name, orf.start + 1, orf.end,
+                               strand_symbol(orf.strand), orf.protein.len(), protein);
+            out.record(&[("id", id(&record)), ("start", Value::from(orf.start + 1)),
+                         ("end", Value::from(orf.end)),
+                         ("strand", Value::from(strand_symbol(orf.strand))),
+                         ("length", Value::from(orf.len())), ("protein", Value::from(protein))],
+                       &text)?;
+        }
+        Ok(())
+    })?;
+    out.finish()?;
+    Ok(())
+}
+
+/// The enzymes named in a comma-separated list.
+fn enzymes(names: &str) -> Result<Vec<&'static Enzyme>, CliError> {
+    let mut enzymes = Vec::new();
+    for name in names.split(',').map(|n| n.trim()).filter(|n| !n.is_empty()) {
+        match Enzyme::from_name(name) {
+            Some(enzyme) => enzymes.push(enzyme),
+            None => {
+                let known: Vec<&str> = Enzyme::all().iter().map(|e| e.name).collect();
+                return Err(CliError::Usage(format!("unknown enzyme {}; known enzymes are {}",
+                                                   name, known.join(", "))));
+            },
+        }
+    }
+    Ok(enzymes)
+}
+
+pub fn digest(args: Vec<String>) -> Result<(), CliError> {
+    let mut common = Common::new();
+    let mut names = String::new();
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Cut each sequence with restriction enzymes and list the fragments. \
+                            Positions are one-based and inclusive.");
+        ap.refer(&mut names).required()
+            .add_option(&["-e", "--enzymes"], Store, "Comma-separated enzyme names, e.g. EcoRI,BamHI");
+        common.register(&mut ap, true);
+        parse(&ap, args)?;
+    }
+    let enzymes = enzymes(&names)?;
+    let mut out = Output::new(common.writer()?, common.format);
+    common.each_record(|record| {
+        let seq = strict(&record.sequence)?;
+        for fragment in cut(&seq.sequence, seq.topology, &enzymes).iter() {
+            let (left, right) = (fragment.left.unwrap_or(""), fragment.right.unwrap_or(""));
+            let text = format!("{}\t{}..{}\t{} bp\t{}\t{}\n", seq.name, fragment.start + 1,
+                               fragment.end, fragment.len(),
+                               if left.is_empty() { "end" } else { left },
+                               if right.is_empty() { "end" } else { right });
+            out.record(&[("id", id(&record)), ("start", Value::from(fragment.start + 1)),
+                         ("end", Value::from(fragment.end)), ("length", Value::from(fragment.len())),
+                         ("left", Value::from(left)), ("right", Value::from(right))], &text)?;
+        }
+        Ok(())
+    })?;
+    out.finish()?;
+    Ok(())
+}
+
+pub fn stats(args: Vec<String>) -> Result<(), CliError> {
+    let mut common = Common::new();
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Summarise each sequence: length, topology, composition and features.");
+        common.register(&mut ap, true);
+        parse(&ap, args)?;
+    }
+    let mut out = Output::new(common.writer()?, common.format);
+    common.each_record(|record| {
+        let seq = &record.sequence;
+        let text = seq.sequence.to_string();
+        let count = |base: char| text.chars().filter(|&ch| ch == base).count();
+        let (a, c, g, t) = (count('A'), count('C'), count('G'), count('T'));
+        let gc = seq.sequence.gc_content().mean;
+        let topology = match seq.topology {
+            Topology::Linear => "linear",
+            Topology::Circular => "circular",
+        };
+        let summary = format!("{}\t{} bp\t{}\tGC {:.1}%\t{} features\n", seq.name, text.len(),
+                              topology, gc * 100.0, seq.features.len());
+        out.record(&[("id", id(&record)), ("length", Value::from(text.len())),
+                     ("topology", Value::from(topology)), ("gc", Value::from(gc)),
+                     ("a", Value::from(a)), ("c", Value::from(c)), ("g", Value::from(g)),
+                     ("t", Value::from(t)), ("features", Value::from(seq.features.len()))],
+                   &summary)?;
+        Ok(())
+    })?;
+    out.finish()?;
+    Ok(())
+}
+
+pub fn search(args: Vec<String>) -> Result<(), CliError> {
+    let mut common = Common::new();
+    let mut pattern = String::new();
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Find a pattern, which may use IUPAC ambiguity codes, on both \
+                            strands. Positions are one-based and inclusive.");
+        ap.refer(&mut pattern).required()
+            .add_argument("pattern", Store, "Sequence to look for");
+        common.register(&mut ap, true);
+        parse(&ap, args)?;
+    }
+    let pattern = Sequence::<DegenerateNucleotide>::from_str(&pattern)
+        .map_err(|e| CliError::Usage(format!("invalid pattern: {}", e)))?;
+    let mut out = Output::new(common.writer()?, common.format);
+    common.each_record(|record| {
+        let seq = strict(&record.sequence)?;
+        let text: Vec<char> = seq.sequence.to_string().chars().collect();
+        for m in find(&seq.sequence, &pattern, seq.topology).iter() {
+            let matched: String = (m.start..m.end).map(|i| text[i % text.len()]).collect();
+            let line = format!("{}\t{}..{}\t{}\t{}\n", seq.name, m.start + 1, m.end,
+                               strand_symbol(m.strand), matched);
+            out.record(&[("id", id(&record)), ("start", Value::from(m.start + 1)),
+                         ("end", Value::from(m.end)), ("strand", Value::from(strand_symbol(m.strand))),
+                         ("match", Value::from(matched))], &line)?;
+        }
+        Ok(())
+    })?;
+    out.finish()?;
+    Ok(())
+}
+
+pub fn convert(args: Vec<String>) -> Result<(), CliError> {
+    let mut common = Common::new();
+    let mut to = String::from("fasta");
+    let mut namespace = String::from("https://example.org/constructor");
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Convert between FASTA, GenBank and SBOL3. SBOL3 is written as \
+                            Turtle, or as RDF/XML with -t sbol-xml, with every record in one \
+                            document.");
+        ap.refer(&mut to)
+            .add_option(&["-t", "--to"], Store, "Output format: fasta, genbank, sbol or sbol-xml");
+        ap.refer(&mut namespace)
+            .add_option(&["--namespace"], Store, "IRI prefix of SBOL3 objects");
+        common.register(&mut ap, false);
+        parse(&ap, args)?;
+    }
+    if to != "fasta" && to != "genbank" && to != "sbol" && to != "sbol-xml" {
+        return Err(CliError::Usage(format!(
+            "cannot convert to {}; choose fasta, genbank, sbol or sbol-xml", to)));
+    }
+    let writer = common.writer()?;
+    if to == "sbol" || to == "sbol-xml" {
+        let mut records = Vec::new();
+        common.each_record(|record| {
+            records.push(record.sequence);
+            Ok(())
+        })?;
+        let syntax = if to == "sbol" { RdfSyntax::Turtle } else { RdfSyntax::RdfXml };
+        let mut sbol = SbolWriter::new(writer).with_syntax(syntax);
+        sbol.write(&SbolDocument::from_records(&namespace, &records))?;
+        sbol.flush()?;
+    } else if to == "genbank" {
+        let mut genbank = GenbankWriter::new(writer);
+        common.each_record(|record| Ok(genbank.write(&record.sequence)?))?;
+        genbank.flush()?;
+    } else {
+        let mut fasta = FastaWriter::new(writer);
+        common.each_record(|record| {
+            let mut entry = FastaRecord::new(&record.sequence.name, record.sequence.sequence);
+            entry.description = record.description;
+            Ok(fasta.write(&entry)?)
+        })?;
+        fasta.flush()?;
+    }
+    Ok(())
+}
+
+/// A record name made safe for use as a part name.
+fn part_name(name: &str) -> String {
+    let name: String = name.chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() || "-_.".contains(ch) { ch } else { '_' })
+        .collect();
+    if name.is_empty() || name.starts_with('.') { format!("_{}", name) } else { name }
+}
+
+/// The fields listed for a part by `list` and `search`.
+fn part_fields(entry: &Entry) -> Vec<(&'static str, Value)> {
+    vec![("name", Value::from(&entry.name[..])), ("version", Value::from(entry.version as usize)),
+         ("type", Value::from(entry.kind.to_key())), ("length", Value::from(entry.sequence.len())),
+         ("tags", Value::from(entry.tags.join(",")))]
+}
+
+pub fn parts(args: Vec<String>) -> Result<(), CliError> {
+    let mut common = Common::new();
+    let mut action = String::new();
+    let mut root = String::from("parts");
+    let mut kind = String::new();
+    let mut tags: Vec<String> = Vec::new();
+    let mut to = String::from("fasta");
+    let mut name = String::new();
+    let mut tag = String::new();
+    let mut query = String::new();
+    let mut k = 12usize;
+    let mut min_score = 0.5f64;
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Manage a directory of named, versioned parts. `import FILES` adds \
+                            each record as a new version of the part it names, `export NAMES` \
+                            writes parts out, taking the latest version unless a name ends in \
+                            @VERSION, `list` shows the latest version of every part and \
+                            `search` finds parts by name, tag or sequence similarity.");
+        ap.refer(&mut action).required()
+            .add_argument("action", Store, "import, export, list or search");
+        ap.refer(&mut common.files)
+            .add_argument("arguments", List, "Files to import or parts to export");
+        ap.refer(&mut root)
+            .add_option(&["-r", "--registry"], Store, "Registry directory (default parts)");
+        ap.refer(&mut kind)
+            .add_option(&["--type"], Store,
+                        "GenBank feature key of imported parts; by default the key of a \
+                         feature spanning the whole record, or misc_feature");
+        ap.refer(&mut tags).add_option(&["--tag"], Collect, "Tag imported parts; repeatable");
+        ap.refer(&mut to)
+            .add_option(&["-t", "--to"], Store, "Export format: fasta or genbank");
+        ap.refer(&mut name)
+            .add_option(&["--name"], Store, "Search for names containing this text");
+        ap.refer(&mut tag).add_option(&["--with-tag"], Store, "Search for parts with this tag");
+        ap.refer(&mut query)
+            .add_option(&["--sequence"], Store, "Search for parts resembling this sequence");
+        ap.refer(&mut k)
+            .add_option(&["-k"], Store, "k-mer length for sequence search, 1 to 32 (default 12)");
+        ap.refer(&mut min_score)
+            .add_option(&["--min-score"], Store,
+                        "Fraction of the query's k-mers a part must share (default 0.5)");
+        ap.refer(&mut common.format)
+            .add_option(&["-f", "--format"], Store, "Output format: text, json or tsv");
+        ap.refer(&mut common.input_format)
+            .add_option(&["-i", "--input-format"], Store,
+                        "Input format: auto, fasta, genbank or sbol");
+        ap.refer(&mut common.output)
+            .add_option(&["-o", "--output"], Store, "Write to this file instead of standard output");
+        parse(&ap, args)?;
+    }
+    let registry = Registry::open(&root)?;
+    match &action[..] {
+        "import" => {
+            let mut out = Output::new(common.writer()?, common.format);
+            common.each_record(|record| {
+                let seq = strict(&record.sequence)?;
+                let whole = seq.features.iter()
+                    .find(|f| f.start == 0 && f.end == seq.sequence.len()).cloned();
+                let kind = if !kind.is_empty() {
+                    FeatureKind::from_key(&kind)
+                } else {
+                    whole.as_ref().map_or(FeatureKind::MiscFeature, |f| f.kind.clone())
+                };
+                let mut entry = Entry::new(&part_name(&seq.name), kind, seq.sequence);
+                // The qualifiers `export` writes on the spanning feature come back
+                // as tags and metadata, less the note of the exported version.
+                for (key, value) in whole.map_or(Vec::new(), |f| f.qualifiers) {
+                    match &key[..] {
+                        "tag" => entry.tags.push(value),
+                        "note" if value.strip_prefix("version ")
+                            .is_some_and(|v| v.parse::<u32>().is_ok()) => (),
+                        _ => entry.metadata.push((key, value)),
+                    }
+                }
+                for tag in tags.iter() {
+                    if !entry.has_tag(tag) {
+                        entry.tags.push(tag.clone());
+                    }
+                }
+                if let Some(description) = record.description {
+                    if entry.metadata("description").is_none() {
+                        entry.metadata.push((String::from("description"), description));
+                    }
+                }
+                let version = registry.add(&entry)?;
+                out.record(&[("name", Value::from(&entry.name[..])),
+                             ("version", Value::from(version as usize))],
+                           &format!("{}\tversion {}\n", entry.name, version))?;
+                Ok(())
+            })?;
+            out.finish()?;
+        },
+        "export" => {
+            if to != "fasta" && to != "genbank" {
+                return Err(CliError::Usage(format!("cannot export to {}; choose fasta or genbank",
+                                                   to)));
+            }
+            let mut entries = Vec::new();
+            for argument in common.files.iter() {
+                let (name, version) = match argument.rfind('@') {
+                    Some(at) => {
+                        let version = argument[at + 1..].parse().map_err(|_| CliError::Usage(
+                            format!("invalid version in {}", argument)))?;
+                        (&argument[..at], Some(version))
+                    },
+                    None => (&argument[..], None),
+                };
+                entries.push(registry.get(name, version)?);
+            }
+            let writer = common.writer()?;
+            if to == "genbank" {
+                let mut genbank = GenbankWriter::new(writer);
+                for entry in entries {
+                    let length = entry.sequence.len();
+                    let mut record = AnnotatedSequence::new(&entry.name, entry.sequence,
+                                                            Topology::Linear);
+                    let mut feature = Feature::new(entry.kind, &entry.name, 0, length,
+                                                   Strand::Forward);
+                    feature.qualifiers.push((String::from("note"),
+                                             format!("version {}", entry.version)));
+                    for tag in entry.tags {
+                        feature.qualifiers.push((String::from("tag"), tag));
+                    }
+                    feature.qualifiers.extend(entry.metadata);
+                    record.features.push(feature);
+                    genbank.write(&record)?;
+                }
+                genbank.flush()?;
+            } else {
+                let mut fasta = FastaWriter::new(writer);
+                for entry in entries {
+                    let mut record = FastaRecord::new(&entry.name, entry.sequence);
+                    record.description = Some(format!("version={} type={}", entry.version,
+                                                      entry.kind.to_key()));
+                    fasta.write(&record)?;
+                }
+                fasta.flush()?;
+            }
+        },
+        "list" | "search" => {
+            let mut found: Vec<(Entry, Option<f64>)> = if query.is_empty() {
+                registry.latest()?.into_iter().map(|entry| (entry, None)).collect()
+            } else {
+                if k == 0 || k > 32 {
+                    return Err(CliError::Usage(format!("k must be from 1 to 32, not {}", k)));
+                }
+                let query = Sequence::<Nucleotide>::from_str(&query)
+                    .map_err(|e| CliError::Usage(format!("invalid sequence: {}", e)))?;
+                registry.search_sequence(&query, k, min_score)?.into_iter()
+                    .map(|hit| (hit.entry, Some(hit.score))).collect()
+            };
+            let name = name.to_lowercase();
+            found.retain(|(entry, _)| entry.name.to_lowercase().contains(&name)
+                         && (tag.is_empty() || entry.has_tag(&tag)));
+            let mut out = Output::new(common.writer()?, common.format);
+            for (entry, score) in found {
+                let mut fields = part_fields(&entry);
+                let mut text = format!("{}\tversion {}\t{}\t{} bp", entry.name, entry.version,
+                                       entry.kind.to_key(), entry.sequence.len());
+                if let Some(score) = score {
+                    fields.push(("score", Value::from(score)));
+                    text.push_str(&format!("\t{:.2}", score));
+                }
+                text.push('\n');
+                out.record(&fields, &text)?;
+            }
+            out.finish()?;
+        },
+        other => return Err(CliError::Usage(format!(
+            "unknown parts action {}; choose import, export, list or search", other))),
+    }
+    Ok(())
+}
+
+pub fn annotate(args: Vec<String>) -> Result<(), CliError> {
+    let mut common = Common::new();
+    let mut min_identity = 0.9f64;
+    let mut report = false;
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Find common plasmid features, such as origins, resistance genes, \
+                            promoters, tags and terminators, and write each record as GenBank \
+                            with them annotated. DNA features are matched on both strands and \
+                            proteins in all six frames. Positions in the report are one-based \
+                            and inclusive.");
+        ap.refer(&mut min_identity)
+            .add_option(&["--min-identity"], Store,
+                        "Fraction of a feature that must match, 0 to 1 (default 0.9)");
+        ap.refer(&mut report)
+            .add_option(&["--report"], StoreTrue, "List the features found instead of the records");
+        common.register(&mut ap, false);
+        parse(&ap, args)?;
+    }
+    if !(0.0..=1.0).contains(&min_identity) {
+        return Err(CliError::Usage(format!("minimum identity must be between 0 and 1, not {}",
+                                           min_identity)));
+    }
+    let writer = common.writer()?;
+    if report {
+        let mut out = Output::new(writer, common.format);
+        common.each_record(|record| {
+            let seq = strict(&record.sequence)?;
+            for found in find_features(&seq.sequence, seq.topology, min_identity) {
+                let text = format!("{}\t{}\t{}..{}\t{}\t{:.1}%\n", seq.name, found.feature.name,
+                                   found.start + 1, found.end, strand_symbol(found.strand),
+                                   found.identity * 100.0);
+                out.record(&[("id", id(&record)), ("feature", Value::from(found.feature.name)),
+                             ("type", Value::from(found.feature.key)),
+                             ("start", Value::from(found.start + 1)),
+                             ("end", Value::from(found.end)),
+                             ("strand", Value::from(strand_symbol(found.strand))),
+                             ("identity", Value::from(found.identity))], &text)?;
+            }
+            Ok(())
+        })?;
+        out.finish()?;
+    } else {
+        let mut genbank = GenbankWriter::new(writer);
+        common.each_record(|record| {
+            let mut seq = strict(&record.sequence)?;
+            add_features(&mut seq, min_identity);
+            Ok(genbank.write(&seq)?)
+        })?;
+        genbank.flush()?;
+    }
+    Ok(())
+}
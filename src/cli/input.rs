@@ -0,0 +1,170 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::str::FromStr;
+
+use constructor::{AnnotatedSequence, Topology};
+use constructor::sequence::degenerate_nucleotide::DegenerateNucleotide;
+use constructor::io::error::FormatError;
+use constructor::io::fasta::FastaReader;
+use constructor::io::genbank::GenbankReader;
+use constructor::io::sbol::SbolReader;
+
+use cli::CliError;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum InputFormat {
+    /// Decide from the first character of each input.
+    Auto,
+    Fasta,
+    Genbank,
+    /// SBOL3 as Turtle or N-Triples; each DNA component with a sequence is
+    /// a record.
+    Sbol,
+}
+
+impl FromStr for InputFormat {
+    type Err = ();
+    fn from_str(input: &str) -> Result<InputFormat, ()> {
+        match input {
+            "auto" => Ok(InputFormat::Auto),
+            "fasta" => Ok(InputFormat::Fasta),
+            "genbank" | "gb" => Ok(InputFormat::Genbank),
+            "sbol" => Ok(InputFormat::Sbol),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A sequence read from any input format. FASTA records carry their header
+/// description and come in as linear sequences without features. Bases may
+/// be any IUPAC code, so commands that need unambiguous bases must check.
+pub struct Record {
+    pub sequence: AnnotatedSequence<DegenerateNucleotide>,
+    pub description: Option<String>,
+}
+
+/// Pass every record from the named files to `handle` in order, with no
+/// names or `-` meaning standard input. Records are parsed one at a time
+/// and dropped once handled, so memory use does not grow with the input.
+pub fn for_each_record<F>(files: &[String], format: InputFormat, mut handle: F)
+                          -> Result<(), CliError> where F: FnMut(Record) -> Result<(), CliError> {
+    let stdin = [String::from("-")];
+    let files = if files.is_empty() { &stdin[..] } else { files };
+    for name in files.iter() {
+        let reader: Box<dyn BufRead> = if name == "-" {
+            Box::new(BufReader::new(io::stdin()))
+        } else {
+            let file = File::open(name).map_err(|e| FormatError::Io(
+                io::Error::new(e.kind(), format!("{}: {}", name, e))))?;
+            Box::new(BufReader::new(file))
+        };
+        read(reader, format, &mut handle)?;
+    }
+    Ok(())
+}
+
+fn read<F>(mut reader: Box<dyn BufRead>, format: InputFormat, handle: &mut F)
+           -> Result<(), CliError> where F: FnMut(Record) -> Result<(), CliError> {
+    let format = match format {
+        InputFormat::Auto => detect(&mut reader)?,
+        other => other,
+    };
+    match format {
+        InputFormat::Genbank => for record in GenbankReader::<_, DegenerateNucleotide>::new(reader) {
+            handle(Record { sequence: record?, description: None })?;
+        },
+        // RDF has no record boundaries, so the whole document is read first.
+        InputFormat::Sbol => for record in SbolReader::new(reader).read()?.records() {
+            handle(Record { sequence: record, description: None })?;
+        },
+        _ => for record in FastaReader::<_, DegenerateNucleotide>::new(reader) {
+            let record = record?;
+            handle(Record {
+                sequence: AnnotatedSequence::new(&record.id, record.sequence, Topology::Linear),
+                description: record.description,
+            })?;
+        },
+    }
+    Ok(())
+}
+
+/// Guess the format from the first non-blank character without consuming
+/// it: GenBank records open with `LOCUS`, SBOL documents with a Turtle
+/// `@prefix`, an N-Triples IRI or an XML tag, and anything else is read as
+/// FASTA.
+fn detect(reader: &mut Box<dyn BufRead>) -> Result<InputFormat, FormatError> {
+    loop {
+        let (skip, found) = {
+            let buffer = reader.fill_buf()?;
+            if buffer.is_empty() {
+                return Ok(InputFormat::Fasta);
+            }
+            match buffer.iter().position(|b| !b.is_ascii_whitespace()) {
+                Some(i) => (i, Some(buffer[i])),
+                None => (buffer.len(), None),
+            }
+        };
+        reader.consume(skip);
+        if let Some(first) = found {
+            return Ok(match first {
+                b'L' => InputFormat::Genbank,
+                b'@' | b'<' => InputFormat::Sbol,
+                _ => InputFormat::Fasta,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, Cursor};
+
+    use constructor::Topology;
+
+    use super::{read, InputFormat, Record};
+
+    fn names(input: &'static str) -> Vec<(String, Topology)> {
+        let mut seen = Vec::new();
+        let reader: Box<dyn BufRead> = Box::new(Cursor::new(input));
+        read(reader, InputFormat::Auto, &mut |record: Record| {
+            seen.push((record.sequence.name.clone(), record.sequence.topology));
+            Ok(())
+        }).unwrap();
+        seen
+    }
+
+    #[test]
+    fn detects_format() {
+        assert_eq!(names("\n>a\nACGT\n>b\nGG\n"),
+                   vec![(String::from("a"), Topology::Linear), (String::from("b"), Topology::Linear)]);
+        let genbank = "LOCUS       p   4 bp    DNA     circular\nORIGIN\n        1 acgt\n//\n";
+        assert_eq!(names(genbank), vec![(String::from("p"), Topology::Circular)]);
+        let sbol = "@prefix sbol: <http://sbols.org/v3#> .\n\
+                    <urn:p> a sbol:Component ; sbol:type <https://identifiers.org/SBO:0000251> ;\n\
+                    sbol:hasSequence <urn:s> .\n<urn:s> sbol:elements \"acgt\" .\n";
+        assert_eq!(names(sbol), vec![(String::from("p"), Topology::Linear)]);
+        let xml = "<?xml version=\"1.0\"?>\n<rdf:RDF \
+                   xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\" \
+                   xmlns:sbol=\"http://sbols.org/v3#\">\n<sbol:Component rdf:about=\"urn:p\">\
+                   <sbol:type rdf:resource=\"https://identifiers.org/SBO:0000251\"/>\
+                   <sbol:hasSequence><sbol:Sequence rdf:about=\"urn:s\">\
+                   <sbol:elements>acgt</sbol:elements></sbol:Sequence></sbol:hasSequence>\
+                   </sbol:Component></rdf:RDF>\n";
+        assert_eq!(names(xml), vec![(String::from("p"), Topology::Linear)]);
+        assert!(names("").is_empty());
+    }
+
+    #[test]
+    fn keeps_ambiguity_codes() {
+        let mut seen = Vec::new();
+        let genbank = "LOCUS       p   4 bp    DNA     linear\nORIGIN\n        1 acnt\n//\n";
+        for input in [">a\nACGTNRY\n", genbank].iter() {
+            let reader: Box<dyn BufRead> = Box::new(Cursor::new(*input));
+            read(reader, InputFormat::Auto, &mut |record: Record| {
+                seen.push(record.sequence.sequence.to_string());
+                Ok(())
+            }).unwrap();
+        }
+        assert_eq!(seen, vec![String::from("ACGTNRY"), String::from("ACNT")]);
+    }
+}
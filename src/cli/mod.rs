@@ -0,0 +1,77 @@
+use std::fmt;
+use std::io;
+
+use constructor::SequenceError;
+use constructor::io::error::FormatError;
+
+pub mod input;
+pub mod output;
+pub mod commands;
+
+/// Why a command failed, which decides the process's exit code.
+#[derive(Debug)]
+pub enum CliError {
+    /// The argument parser already reported the problem, or printed help,
+    /// and asked for this exit code.
+    Parse(i32),
+    /// The arguments parsed but do not make sense together.
+    Usage(String),
+    /// Input could not be read or processed.
+    Input(FormatError),
+    /// The named record holds an ambiguity code where the command needs
+    /// unambiguous bases.
+    Ambiguous(String, SequenceError),
+    /// Output could not be written.
+    Output(io::Error),
+}
+
+impl CliError {
+    /// Whether the reader of our output went away, as when piping into
+    /// `head`; that ends the run early but is not a failure.
+    pub fn is_broken_pipe(&self) -> bool {
+        match *self {
+            CliError::Output(ref e) | CliError::Input(FormatError::Io(ref e)) =>
+                e.kind() == io::ErrorKind::BrokenPipe,
+            _ => false,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match *self {
+            CliError::Parse(code) => code,
+            CliError::Usage(_) | CliError::Input(FormatError::InvalidName(_)) => 2,
+            CliError::Input(_) | CliError::Ambiguous(..) | CliError::Output(_) => 1,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CliError::Parse(_) => Ok(()),
+            CliError::Usage(ref message) => write!(f, "{}", message),
+            CliError::Input(ref e) => write!(f, "{}", e),
+            CliError::Ambiguous(ref name, ref e) =>
+                write!(f, "{}: {}; this command needs A, C, G or T", name, e),
+            CliError::Output(ref e) => write!(f, "cannot write output: {}", e),
+        }
+    }
+}
+
+impl From<FormatError> for CliError {
+    fn from(e: FormatError) -> CliError {
+        CliError::Input(e)
+    }
+}
+
+impl From<SequenceError> for CliError {
+    fn from(e: SequenceError) -> CliError {
+        CliError::Input(FormatError::Sequence(e))
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(e: io::Error) -> CliError {
+        CliError::Output(e)
+    }
+}
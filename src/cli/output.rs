@@ -0,0 +1,157 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Format {
+    Text,
+    Json,
+    Tsv,
+}
+
+impl FromStr for Format {
+    type Err = ();
+    fn from_str(input: &str) -> Result<Format, ()> {
+        match input {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "tsv" => Ok(Format::Tsv),
+            _ => Err(()),
+        }
+    }
+}
+
+pub enum Value {
+    Str(String),
+    Int(i64),
+    Float(f64),
+}
+
+impl Value {
+    fn tsv(&self) -> String {
+        match *self {
+            Value::Str(ref s) => s.replace(['\t', '\n'], " "),
+            Value::Int(i) => i.to_string(),
+            Value::Float(x) => format!("{:.4}", x),
+        }
+    }
+    fn json(&self) -> String {
+        match *self {
+            Value::Str(ref s) => json_string(s),
+            Value::Int(i) => i.to_string(),
+            Value::Float(x) if x.is_finite() => format!("{:.4}", x),
+            Value::Float(_) => String::from("null"),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(s: &'a str) -> Value {
+        Value::Str(String::from(s))
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::Str(s)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(i: usize) -> Value {
+        Value::Int(i as i64)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Value {
+        Value::Float(x)
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Writes command results as free text, a JSON array of objects, or TSV
+/// with a header row.
+pub struct Output<W: Write> {
+    writer: W,
+    format: Format,
+    count: usize,
+}
+
+impl<W: Write> Output<W> {
+    pub fn new(writer: W, format: Format) -> Output<W> {
+        Output { writer, format, count: 0 }
+    }
+
+    /// Write one result, as `text` in text mode and as `fields` otherwise.
+    pub fn record(&mut self, fields: &[(&str, Value)], text: &str) -> io::Result<()> {
+        match self.format {
+            Format::Text => self.writer.write_all(text.as_bytes())?,
+            Format::Tsv => {
+                if self.count == 0 {
+                    let header: Vec<&str> = fields.iter().map(|f| f.0).collect();
+                    writeln!(self.writer, "{}", header.join("\t"))?;
+                }
+                let values: Vec<String> = fields.iter().map(|f| f.1.tsv()).collect();
+                writeln!(self.writer, "{}", values.join("\t"))?;
+            },
+            Format::Json => {
+                self.writer.write_all(if self.count == 0 { b"[\n  " } else { b",\n  " })?;
+                let members: Vec<String> = fields.iter()
+                    .map(|f| format!("{}: {}", json_string(f.0), f.1.json())).collect();
+                write!(self.writer, "{{{}}}", members.join(", "))?;
+            },
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Close any open structure and flush.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.format == Format::Json {
+            self.writer.write_all(if self.count == 0 { b"[]\n" } else { b"\n]\n" })?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Format, Output, Value};
+
+    fn render(format: Format) -> String {
+        let mut out = Output::new(Vec::new(), format);
+        out.record(&[("id", Value::from("a\"b")), ("length", Value::from(4usize))], "a: 4\n")
+            .unwrap();
+        out.record(&[("id", Value::from("c")), ("length", Value::from(2usize))], "c: 2\n")
+            .unwrap();
+        String::from_utf8(out.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(render(Format::Text), "a: 4\nc: 2\n");
+        assert_eq!(render(Format::Tsv), "id\tlength\na\"b\t4\nc\t2\n");
+        assert_eq!(render(Format::Json),
+                   "[\n  {\"id\": \"a\\\"b\", \"length\": 4},\n  {\"id\": \"c\", \"length\": 2}\n]\n");
+        let empty = Output::new(Vec::new(), Format::Json).finish().unwrap();
+        assert_eq!(String::from_utf8(empty).unwrap(), "[]\n");
+    }
+}
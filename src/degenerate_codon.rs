@@ -0,0 +1,264 @@
+use std::cmp::Ordering;
+
+use sequence::nucleotide::Nucleotide;
+use sequence::degenerate_nucleotide::DegenerateNucleotide;
+use sequence::codon::Codon;
+use sequence::amino_acid::AminoAcid;
+use sequence::string_io::StringIO;
+use sequence::genetic_code::CodonTable;
+
+const SYMBOLS: &str = "ACGTRYSWKMBDHVN";
+
+/// A set of the 64 concrete codons, bit `16a + 4b + c` for bases indexed
+/// A, C, G, T.
+type CodonSet = u64;
+
+fn base_index(nt: &Nucleotide) -> usize {
+    match *nt {
+        Nucleotide::A => 0,
+        Nucleotide::C => 1,
+        Nucleotide::G => 2,
+        Nucleotide::T => 3,
+    }
+}
+
+fn concrete(codon: &Codon<DegenerateNucleotide>) -> CodonSet {
+    let symbols: Vec<DegenerateNucleotide> = codon.to_string().chars()
+        .map(|ch| DegenerateNucleotide::from_char(ch).unwrap()).collect();
+    let mut set = 0;
+    for first in symbols[0].expand() {
+        for second in symbols[1].expand() {
+            for third in symbols[2].expand() {
+                set |= 1 << (16 * base_index(first) + 4 * base_index(second) + base_index(third));
+            }
+        }
+    }
+    set
+}
+
+/// Every one of the 15³ degenerate codons with its concrete codons.
+fn candidates() -> Vec<(Codon<DegenerateNucleotide>, CodonSet)> {
+    let mut all = Vec::with_capacity(15 * 15 * 15);
+    for a in SYMBOLS.chars() {
+        for b in SYMBOLS.chars() {
+            for c in SYMBOLS.chars() {
+                let codon = Codon::<DegenerateNucleotide>::from_chars(a, b, c).unwrap();
+                let set = concrete(&codon);
+                all.push((codon, set));
+            }
+        }
+    }
+    all
+}
+
+/// What each of the 64 concrete codons encodes under `code`.
+fn translations<C: CodonTable + ?Sized>(code: &C) -> Vec<AminoAcid> {
+    let bases = [Nucleotide::A, Nucleotide::C, Nucleotide::G, Nucleotide::T];
+    (0..64).map(|i| code.translate(&Codon::from_slice(&[bases[i / 16], bases[i / 4 % 4],
+                                                        bases[i % 4]]))).collect()
+}
+
+/// Degenerate codons to order for one library position, with what they
+/// encode when every concrete codon is equally represented.
+#[derive(Debug)]
+pub struct CodonDesign {
+    /// Each degenerate codon with its share of the oligo mixture, in
+    /// proportion to how many concrete codons it stands for.
+    pub codons: Vec<(Codon<DegenerateNucleotide>, f64)>,
+    /// Fraction of concrete codons encoding each amino acid or stop, most
+    /// frequent first.
+    pub encoded: Vec<(AminoAcid, f64)>,
+    /// Target residues the design does not encode.
+    pub missing: Vec<AminoAcid>,
+    /// Fraction of concrete codons that encode a target residue.
+    pub on_target: f64,
+    /// Fraction of concrete codons that are stops.
+    pub stop: f64,
+    /// Half the summed absolute difference between how targets are
+    /// represented and the requested weights: zero is a perfect match.
+    pub bias: f64,
+}
+
+impl CodonDesign {
+    fn new(codons: Vec<(Codon<DegenerateNucleotide>, CodonSet)>, table: &[AminoAcid],
+           targets: &[(AminoAcid, f64)]) -> CodonDesign {
+        let set = codons.iter().fold(0, |set, &(_, s)| set | s);
+        let total = set.count_ones() as f64;
+        let mut encoded: Vec<(AminoAcid, f64)> = Vec::new();
+        for (_, amino_acid) in table.iter().enumerate().filter(|&(i, _)| set >> i & 1 == 1) {
+            match encoded.iter_mut().find(|e| e.0 == *amino_acid) {
+                Some(entry) => entry.1 += 1.0 / total,
+                None => encoded.push((amino_acid.clone(), 1.0 / total)),
+            }
+        }
+        encoded.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+        let share = |amino_acid: &AminoAcid| encoded.iter().find(|e| e.0 == *amino_acid)
+            .map_or(0.0, |e| e.1);
+        let missing = targets.iter().filter(|t| share(&t.0) == 0.0).map(|t| t.0.clone()).collect();
+        let on_target: f64 = targets.iter().map(|t| share(&t.0)).sum();
+        let weight: f64 = targets.iter().map(|t| t.1).sum();
+        let bias = if on_target > 0.0 {
+            targets.iter().map(|t| (share(&t.0) / on_target - t.1 / weight).abs()).sum::<f64>() / 2.0
+        } else {
+            1.0
+        };
+        let codons = codons.into_iter()
+            .map(|(codon, s)| (codon, s.count_ones() as f64 / total)).collect();
+        CodonDesign { codons, stop: share(&AminoAcid::STOP), encoded, missing, on_target, bias }
+    }
+
+    /// Lower is better: missing targets first, then wasted and biased
+    /// codons, then stops, then the simpler design.
+    fn cost(&self) -> (usize, f64, f64, usize) {
+        (self.missing.len(), (1.0 - self.on_target) + self.bias, self.stop, self.codons.len())
+    }
+}
+
+fn compare(a: &CodonDesign, b: &CodonDesign) -> Ordering {
+    a.cost().partial_cmp(&b.cost()).unwrap_or(Ordering::Equal)
+}
+
+/// Targets with repeats merged into one entry, their weights added.
+fn merge(targets: &[(AminoAcid, f64)]) -> Vec<(AminoAcid, f64)> {
+    let mut merged: Vec<(AminoAcid, f64)> = Vec::new();
+    for &(ref amino_acid, weight) in targets.iter() {
+        match merged.iter_mut().find(|t| t.0 == *amino_acid) {
+            Some(target) => target.1 += weight,
+            None => merged.push((amino_acid.clone(), weight)),
+        }
+    }
+    merged
+}
+
+/// The single degenerate codon that best encodes the targets, given as
+/// amino acids with relative weights; a repeated amino acid counts once
+/// with the sum of its weights. Designs that miss a target always
+/// lose; among the rest, the cost is the fraction of codons that are
+/// off-target or stops plus how far the targets' representation strays
+/// from their weights.
+pub fn best_codon<C>(targets: &[(AminoAcid, f64)], code: &C) -> CodonDesign
+    where C: CodonTable + ?Sized {
+    let table = translations(code);
+    let targets = &merge(targets);
+    candidates().into_iter()
+        .map(|candidate| CodonDesign::new(vec![candidate], &table, targets))
+        .min_by(compare)
+        .unwrap()
+}
+
+/// Partial mixtures kept at each step of the search for `best_mixture`.
+const BEAM_WIDTH: usize = 64;
+
+/// A mixture of at most `max_codons` degenerate codons in the style of the
+/// 22c trick (Kille et al., 2013), where NDT, VHG and TGG mixed 12:9:1 give
+/// all twenty amino acids without stops from 22 codons.
+///
+/// Only codons that encode nothing but targets are considered, and no two
+/// chosen codons share a concrete codon. Mixtures are grown a codon at a
+/// time, keeping those with the most targets covered net of redundant
+/// codons, and the best by the same cost as `best_codon` is returned, or
+/// the best single codon if no mixture beats it.
+pub fn best_mixture<C>(targets: &[(AminoAcid, f64)], code: &C, max_codons: usize)
+                       -> CodonDesign where C: CodonTable + ?Sized {
+    let table = translations(code);
+    let targets = &merge(targets);
+    // Targets the code can encode, whose positions index the coverage
+    // bits; there are at most 21 of them.
+    let encodable: Vec<&AminoAcid> = targets.iter().map(|t| &t.0)
+        .filter(|&amino_acid| table.contains(amino_acid)).collect();
+    let residue = |i: usize| encodable.iter().position(|&amino_acid| *amino_acid == table[i]);
+    let wanted: CodonSet = (0..64).filter(|&i| residue(i).is_some()).fold(0, |set, i| set | 1 << i);
+    // Each usable codon with its concrete codons and the targets it covers.
+    let clean: Vec<(Codon<DegenerateNucleotide>, CodonSet, u64)> = candidates().into_iter()
+        .filter(|&(_, set)| set & !wanted == 0)
+        .map(|(codon, set)| {
+            let covered = (0..64).filter(|&i| set >> i & 1 == 1)
+                .fold(0u64, |mask, i| mask | 1 << residue(i).unwrap());
+            (codon, set, covered)
+        }).collect();
+    let net = |used: CodonSet, covered: u64| {
+        2 * covered.count_ones() as i64 - used.count_ones() as i64
+    };
+
+    let mut best = best_codon(targets, code);
+    // Each state: indices into `clean`, codons used and targets covered.
+    let mut beam: Vec<(Vec<usize>, CodonSet, u64)> = vec![(Vec::new(), 0, 0)];
+    for _ in 0..max_codons {
+        let mut next = Vec::new();
+        for &(ref chosen, used, covered) in beam.iter() {
+            for (index, &(_, set, more)) in clean.iter().enumerate() {
+                if set & used == 0 && more & !covered != 0 {
+                    let mut grown = chosen.clone();
+                    grown.push(index);
+                    next.push((grown, used | set, covered | more));
+                }
+            }
+        }
+        // Best net coverage first, then most targets covered; the same codons
+        // reached in a different order are one state.
+        next.sort_by_key(|&(_, used, covered)| (-net(used, covered), -(covered.count_ones() as i64),
+                                                used));
+        next.dedup_by_key(|state| state.1);
+        next.truncate(BEAM_WIDTH);
+        for (chosen, _, _) in next.iter() {
+            let codons = chosen.iter().map(|&i| (clean[i].0.clone(), clean[i].1)).collect();
+            let design = CodonDesign::new(codons, &table, targets);
+            if compare(&design, &best) == Ordering::Less {
+                best = design;
+            }
+        }
+        beam = next;
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{best_codon, best_mixture};
+    use sequence::amino_acid::AminoAcid;
+    use sequence::string_io::StringIO;
+    use sequence::genetic_code::GeneticCode;
+
+    fn targets(residues: &str) -> Vec<(AminoAcid, f64)> {
+        residues.chars().map(|ch| (AminoAcid::from_char(ch).unwrap(), 1.0)).collect()
+    }
+
+    #[test]
+    fn single_codon() {
+        let code = GeneticCode::standard();
+        let design = best_codon(&targets("DE"), code);
+        assert_eq!(design.codons[0].0.to_string(), "GAS");
+        assert!(design.missing.is_empty() && design.on_target == 1.0 && design.bias == 0.0);
+
+        // No single codon gives all twenty amino acids without a stop.
+        let all = best_codon(&targets("ACDEFGHIKLMNPQRSTVWY"), code);
+        assert!(all.missing.is_empty());
+        assert!(all.stop > 0.0 && all.stop <= 1.0 / 32.0);
+    }
+
+    #[test]
+    fn repeated_targets() {
+        let code = GeneticCode::standard();
+        // Repeats merge, so D weighs twice E whether listed once or twice.
+        let twice = best_codon(&targets("DDE"), code);
+        let weighted = best_codon(&[(AminoAcid::D, 2.0), (AminoAcid::E, 1.0)], code);
+        assert_eq!(twice.codons[0].0.to_string(), weighted.codons[0].0.to_string());
+        assert_eq!(twice.bias, weighted.bias);
+        let many = targets(&"DE".repeat(40));
+        assert!(best_mixture(&many, code, 2).missing.is_empty());
+    }
+
+    #[test]
+    fn twenty_two_codon_trick() {
+        let design = best_mixture(&targets("ACDEFGHIKLMNPQRSTVWY"), GeneticCode::standard(), 3);
+        // NDT, VHG and TGG, or an equally good alternative.
+        let shares: Vec<f64> = design.codons.iter().map(|&(_, share)| share * 22.0).collect();
+        assert_eq!(shares.len(), 3);
+        assert!(shares.iter().all(|share| (share - share.round()).abs() < 1e-9));
+        assert!(design.missing.is_empty() && design.stop == 0.0);
+        // A fourth codon allows one codon per amino acid.
+        let design = best_mixture(&targets("ACDEFGHIKLMNPQRSTVWY"), GeneticCode::standard(), 4);
+        assert_eq!(design.codons.len(), 4);
+        assert!(design.bias < 1e-9 && design.stop == 0.0);
+    }
+}
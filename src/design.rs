@@ -0,0 +1,443 @@
+use std::fmt;
+
+use sequence::sequence::Sequence;
+use sequence::nucleotide::Nucleotide;
+use sequence::codon::Codon;
+use sequence::amino_acid::AminoAcid;
+use sequence::strand::Strand;
+use sequence::feature::{AnnotatedSequence, Feature, FeatureKind, Topology};
+use sequence::genetic_code::CodonTable;
+
+/// Codons accepted as the start of a coding sequence: ATG and the GTG and
+/// TTG starts common in bacteria.
+const START_CODONS: [&str; 3] = ["ATG", "GTG", "TTG"];
+
+/// The role a part plays in a construct.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum PartKind {
+    Promoter,
+    Rbs,
+    Cds,
+    Terminator,
+    Origin,
+    /// A selectable marker, such as an antibiotic resistance gene. Markers
+    /// are coding sequences and are checked as such.
+    Marker,
+}
+
+impl PartKind {
+    pub fn from_name(name: &str) -> Option<PartKind> {
+        match &name.to_lowercase()[..] {
+            "promoter" => Some(PartKind::Promoter),
+            "rbs" => Some(PartKind::Rbs),
+            "cds" => Some(PartKind::Cds),
+            "terminator" => Some(PartKind::Terminator),
+            "origin" => Some(PartKind::Origin),
+            "marker" => Some(PartKind::Marker),
+            _ => None,
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match *self {
+            PartKind::Promoter => "promoter",
+            PartKind::Rbs => "rbs",
+            PartKind::Cds => "cds",
+            PartKind::Terminator => "terminator",
+            PartKind::Origin => "origin",
+            PartKind::Marker => "marker",
+        }
+    }
+    /// The feature a part of this kind is annotated as.
+    pub fn feature_kind(&self) -> FeatureKind {
+        match *self {
+            PartKind::Promoter => FeatureKind::Promoter,
+            PartKind::Rbs => FeatureKind::Rbs,
+            PartKind::Cds | PartKind::Marker => FeatureKind::Cds,
+            PartKind::Terminator => FeatureKind::Terminator,
+            PartKind::Origin => FeatureKind::Origin,
+        }
+    }
+    pub fn is_coding(&self) -> bool {
+        matches!(*self, PartKind::Cds | PartKind::Marker)
+    }
+}
+
+/// A named piece of DNA with a role, written 5' to 3' in the direction it
+/// acts.
+#[derive(Clone, Debug)]
+pub struct Part {
+    pub name: String,
+    pub kind: PartKind,
+    pub sequence: Sequence<Nucleotide>,
+}
+
+impl Part {
+    pub fn new(name: &str, kind: PartKind, sequence: Sequence<Nucleotide>) -> Part {
+        Part { name: String::from(name), kind, sequence }
+    }
+}
+
+/// Parts in the order they are transcribed, typically a promoter, then an
+/// RBS before each coding sequence, then a terminator. Adjacent coding
+/// parts are read as one fused open reading frame.
+#[derive(Clone, Debug)]
+pub struct TranscriptionUnit {
+    pub name: String,
+    pub parts: Vec<Part>,
+}
+
+/// Something placed on a plasmid: a lone part or a transcription unit.
+#[derive(Clone, Debug)]
+pub enum Element {
+    Part(Part),
+    Unit(TranscriptionUnit),
+}
+
+/// A circular construct made of elements in order round the plasmid, each
+/// on either strand.
+#[derive(Clone, Debug)]
+pub struct Plasmid {
+    pub name: String,
+    pub elements: Vec<(Element, Strand)>,
+}
+
+/// Something wrong with a design.
+#[derive(PartialEq, Clone, Debug)]
+pub enum Problem {
+    /// A part with no sequence.
+    Empty,
+    /// A coding sequence that does not begin with ATG, GTG or TTG.
+    MissingStart,
+    /// A stop codon before the end of a coding sequence, at the given codon
+    /// of the part, counted from zero.
+    InternalStop(usize),
+    /// A coding sequence that does not end with a stop codon.
+    MissingStop,
+    /// A coding part whose length is not a whole number of codons, so that
+    /// whatever is fused after it, or its own stop, is out of frame.
+    OutOfFrame,
+    /// A coding sequence not directly preceded by an RBS.
+    MissingRbs,
+    MissingPromoter,
+    MissingTerminator,
+    /// A promoter after the first coding sequence, or a terminator before
+    /// the last.
+    Misordered,
+    MissingOrigin,
+}
+
+/// A problem and the part, unit or plasmid it was found in.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Issue {
+    pub problem: Problem,
+    pub location: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let problem = match self.problem {
+            Problem::Empty => String::from("empty part"),
+            Problem::MissingStart => String::from("no start codon"),
+            Problem::InternalStop(codon) => format!("internal stop at codon {}", codon + 1),
+            Problem::MissingStop => String::from("no stop codon"),
+            Problem::OutOfFrame => String::from("not a whole number of codons"),
+            Problem::MissingRbs => String::from("no RBS before coding sequence"),
+            Problem::MissingPromoter => String::from("no promoter"),
+            Problem::MissingTerminator => String::from("no terminator"),
+            Problem::Misordered => String::from("parts out of order"),
+            Problem::MissingOrigin => String::from("no origin of replication"),
+        };
+        write!(f, "{}: {}", self.location, problem)
+    }
+}
+
+fn issue(problem: Problem, location: &str) -> Issue {
+    Issue { problem, location: String::from(location) }
+}
+
+/// Check one open reading frame made of coding parts fused in order.
+fn check_orf<C>(parts: &[&Part], code: &C, issues: &mut Vec<Issue>) where C: CodonTable + ?Sized {
+    let mut bases: Vec<Nucleotide> = Vec::new();
+    // Where each part starts in the fused frame.
+    let mut offsets = Vec::new();
+    for part in parts.iter() {
+        offsets.push(bases.len());
+        bases.extend(part.sequence.iter().cloned());
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    let codon_at = |i: usize| Codon::from_slice(&bases[i..i + 3]);
+    if bases.len() < 3 || !START_CODONS.contains(&&codon_at(0).to_string()[..]) {
+        issues.push(issue(Problem::MissingStart, &first.name));
+    }
+    for (part, &offset) in parts[..parts.len() - 1].iter().zip(offsets.iter()) {
+        if !(offset + part.sequence.len()).is_multiple_of(3) {
+            issues.push(issue(Problem::OutOfFrame, &part.name));
+        }
+    }
+    let complete = bases.len() / 3;
+    for codon in 0..complete.saturating_sub(1) {
+        if code.translate(&codon_at(3 * codon)) == AminoAcid::STOP {
+            let index = offsets.iter().rposition(|&offset| offset <= 3 * codon).unwrap();
+            let within = (3 * codon - offsets[index]) / 3;
+            issues.push(issue(Problem::InternalStop(within), &parts[index].name));
+        }
+    }
+    if !bases.len().is_multiple_of(3) {
+        issues.push(issue(Problem::OutOfFrame, &last.name));
+    } else if complete == 0 || code.translate(&codon_at(bases.len() - 3)) != AminoAcid::STOP {
+        issues.push(issue(Problem::MissingStop, &last.name));
+    }
+}
+
+/// Check every run of adjacent coding parts, and that no part is empty.
+fn check_parts<C>(parts: &[&Part], code: &C, issues: &mut Vec<Issue>) where C: CodonTable + ?Sized {
+    for part in parts.iter().filter(|part| part.sequence.is_empty()) {
+        issues.push(issue(Problem::Empty, &part.name));
+    }
+    let mut run: Vec<&Part> = Vec::new();
+    for part in parts.iter() {
+        if part.kind.is_coding() {
+            run.push(part);
+        } else if !run.is_empty() {
+            check_orf(&run, code, issues);
+            run.clear();
+        }
+    }
+    if !run.is_empty() {
+        check_orf(&run, code, issues);
+    }
+}
+
+impl TranscriptionUnit {
+    pub fn new(name: &str) -> TranscriptionUnit {
+        TranscriptionUnit { name: String::from(name), parts: Vec::new() }
+    }
+
+    /// Append a part in transcription order.
+    pub fn part(mut self, part: Part) -> TranscriptionUnit {
+        self.parts.push(part);
+        self
+    }
+
+    pub fn sequence(&self) -> Sequence<Nucleotide> {
+        let mut seq = Sequence::<Nucleotide>::new();
+        for part in self.parts.iter() {
+            for &nt in part.sequence.iter() {
+                seq.push(nt);
+            }
+        }
+        seq
+    }
+
+    /// The unit as a linear sequence with a feature for each part.
+    pub fn assemble(&self) -> AnnotatedSequence {
+        let mut record = AnnotatedSequence::new(&self.name, self.sequence(), Topology::Linear);
+        annotate(&self.parts, 0, self.sequence().len(), Strand::Forward, &mut record.features);
+        record
+    }
+
+    /// Check the unit's structure and each open reading frame, translated
+    /// with `code`.
+    pub fn validate<C: CodonTable + ?Sized>(&self, code: &C) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        let kinds: Vec<PartKind> = self.parts.iter().map(|part| part.kind).collect();
+        let position = |kind: PartKind| kinds.iter().position(|&k| k == kind);
+        let last_position = |kind: PartKind| kinds.iter().rposition(|&k| k == kind);
+        let first_coding = kinds.iter().position(|k| k.is_coding());
+        let last_coding = kinds.iter().rposition(|k| k.is_coding());
+        match position(PartKind::Promoter) {
+            None => issues.push(issue(Problem::MissingPromoter, &self.name)),
+            Some(promoter) => if first_coding.is_some_and(|coding| promoter > coding) {
+                issues.push(issue(Problem::Misordered, &self.name));
+            },
+        }
+        match last_position(PartKind::Terminator) {
+            None => issues.push(issue(Problem::MissingTerminator, &self.name)),
+            Some(terminator) => if last_coding.is_some_and(|coding| terminator < coding) {
+                issues.push(issue(Problem::Misordered, &self.name));
+            },
+        }
+        for (i, part) in self.parts.iter().enumerate() {
+            let starts_run = part.kind.is_coding() && (i == 0 || !kinds[i - 1].is_coding());
+            if starts_run && (i == 0 || kinds[i - 1] != PartKind::Rbs) {
+                issues.push(issue(Problem::MissingRbs, &part.name));
+            }
+        }
+        let parts: Vec<&Part> = self.parts.iter().collect();
+        check_parts(&parts, code, &mut issues);
+        issues
+    }
+}
+
+/// Add a feature for each part of an element placed at `offset` with
+/// length `length`. On the reverse strand the element is reverse
+/// complemented, so its parts run backwards from its end.
+fn annotate(parts: &[Part], offset: usize, length: usize, strand: Strand,
+            features: &mut Vec<Feature>) {
+    let mut start = 0;
+    for part in parts.iter() {
+        let end = start + part.sequence.len();
+        let (from, to) = match strand {
+            Strand::Forward => (offset + start, offset + end),
+            Strand::Reverse => (offset + length - end, offset + length - start),
+        };
+        features.push(Feature::new(part.kind.feature_kind(), &part.name, from, to, strand));
+        start = end;
+    }
+}
+
+impl Element {
+    fn name(&self) -> &str {
+        match *self {
+            Element::Part(ref part) => &part.name,
+            Element::Unit(ref unit) => &unit.name,
+        }
+    }
+
+    fn parts(&self) -> &[Part] {
+        match *self {
+            Element::Part(ref part) => ::std::slice::from_ref(part),
+            Element::Unit(ref unit) => &unit.parts,
+        }
+    }
+}
+
+impl Plasmid {
+    pub fn new(name: &str) -> Plasmid {
+        Plasmid { name: String::from(name), elements: Vec::new() }
+    }
+
+    /// Append a lone part on the given strand.
+    pub fn part(mut self, part: Part, strand: Strand) -> Plasmid {
+        self.elements.push((Element::Part(part), strand));
+        self
+    }
+
+    /// Append a transcription unit on the given strand.
+    pub fn unit(mut self, unit: TranscriptionUnit, strand: Strand) -> Plasmid {
+        self.elements.push((Element::Unit(unit), strand));
+        self
+    }
+
+    /// The plasmid as a circular sequence starting at its first element, with
+    /// a feature for each part on the strand it is placed.
+    pub fn assemble(&self) -> AnnotatedSequence {
+        let mut seq = Sequence::<Nucleotide>::new();
+        let mut features = Vec::new();
+        for &(ref element, strand) in self.elements.iter() {
+            let mut piece = Sequence::<Nucleotide>::new();
+            for part in element.parts().iter() {
+                for &nt in part.sequence.iter() {
+                    piece.push(nt);
+                }
+            }
+            if strand == Strand::Reverse {
+                piece = piece.reverse_complement();
+            }
+            annotate(element.parts(), seq.len(), piece.len(), strand, &mut features);
+            for &nt in piece.iter() {
+                seq.push(nt);
+            }
+        }
+        let mut record = AnnotatedSequence::new(&self.name, seq, Topology::Circular);
+        record.features = features;
+        record
+    }
+
+    /// Check every unit and lone coding part, and that the plasmid has an
+    /// origin of replication.
+    pub fn validate<C: CodonTable + ?Sized>(&self, code: &C) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        let has_origin = self.elements.iter()
+            .any(|(element, _)| element.parts().iter().any(|part| part.kind == PartKind::Origin));
+        if !has_origin {
+            issues.push(issue(Problem::MissingOrigin, &self.name));
+        }
+        for (element, _) in self.elements.iter() {
+            match *element {
+                Element::Unit(ref unit) => issues.extend(unit.validate(code)),
+                Element::Part(ref part) => check_parts(&[part], code, &mut issues),
+            }
+        }
+        issues
+    }
+
+    /// Names of the elements, in order.
+    pub fn element_names(&self) -> Vec<&str> {
+        self.elements.iter().map(|(element, _)| element.name()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Part, PartKind, TranscriptionUnit, Plasmid, Problem};
+    use sequence::sequence::Sequence;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::strand::Strand;
+    use sequence::feature::{FeatureKind, Topology};
+    use sequence::genetic_code::GeneticCode;
+
+    fn part(name: &str, kind: PartKind, seq: &str) -> Part {
+        Part::new(name, kind, Sequence::<Nucleotide>::from_str(seq).unwrap())
+    }
+
+    fn unit() -> TranscriptionUnit {
+        TranscriptionUnit::new("tu")
+            .part(part("pTac", PartKind::Promoter, "TTGACAATTAATCATCGGCTCGTATAATG"))
+            .part(part("rbs", PartKind::Rbs, "AGGAGG"))
+            .part(part("his", PartKind::Cds, "ATGCACCACCACCACCACCAC"))
+            .part(part("gfp", PartKind::Cds, "GTGAGCAAGGGCTAA"))
+            .part(part("term", PartKind::Terminator, "CCAGGCATCAAATAAAACGAAAGGCTCAGTCG"))
+    }
+
+    #[test]
+    fn assemble() {
+        let tu = unit();
+        let linear = tu.assemble();
+        assert_eq!(linear.topology, Topology::Linear);
+        assert_eq!(linear.features.len(), 5);
+        assert_eq!((linear.features[2].start, linear.features[2].end), (35, 56));
+        assert_eq!(linear.extract(&linear.features[3]).to_string(), "GTGAGCAAGGGCTAA");
+
+        let plasmid = Plasmid::new("pDemo")
+            .part(part("ori", PartKind::Origin, "TTGAGATCCTTTTTTTCTGCGCGTAATC"), Strand::Forward)
+            .unit(tu, Strand::Reverse);
+        let circular = plasmid.assemble();
+        assert_eq!(circular.topology, Topology::Circular);
+        assert_eq!(circular.sequence.len(), 28 + 103);
+        let gfp = circular.features.iter().find(|f| f.label == "gfp").unwrap();
+        assert_eq!((gfp.kind.clone(), gfp.strand), (FeatureKind::Cds, Strand::Reverse));
+        assert_eq!(circular.extract(gfp).to_string(), "GTGAGCAAGGGCTAA");
+        let promoter = circular.features.iter().find(|f| f.label == "pTac").unwrap();
+        assert_eq!(promoter.end, circular.sequence.len());
+        assert_eq!(plasmid.element_names(), vec!["ori", "tu"]);
+    }
+
+    #[test]
+    fn validate() {
+        let code = GeneticCode::standard();
+        assert_eq!(unit().validate(code), vec![]);
+
+        // A tag one base short shifts the fusion, exposing a TGA in the GFP.
+        let mut shifted = unit();
+        shifted.parts[2] = part("his", PartKind::Cds, "ATGCACCACCACCACCACCA");
+        let problems: Vec<Problem> = shifted.validate(code).into_iter().map(|i| i.problem).collect();
+        assert_eq!(problems, vec![Problem::OutOfFrame, Problem::InternalStop(0), Problem::OutOfFrame]);
+
+        let mut broken = unit();
+        broken.parts[2] = part("his", PartKind::Cds, "CTGCACTAGCACCACCACCAC");
+        broken.parts.swap(0, 1);
+        broken.parts.remove(4);
+        let issues = broken.validate(code);
+        let problems: Vec<Problem> = issues.iter().map(|i| i.problem.clone()).collect();
+        assert_eq!(problems, vec![Problem::MissingTerminator, Problem::MissingRbs,
+                                  Problem::MissingStart, Problem::InternalStop(2)]);
+        assert_eq!(issues[3].to_string(), "his: internal stop at codon 3");
+
+        let plasmid = Plasmid::new("pDemo").unit(unit(), Strand::Forward)
+            .part(part("kanR", PartKind::Marker, "ATGAGCCATTAA"), Strand::Reverse);
+        let issues = plasmid.validate(code);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].to_string(), "pDemo: no origin of replication");
+    }
+}
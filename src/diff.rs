@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+
+use sequence::sequence::Sequence;
+use sequence::string_io::StringIO;
+use sequence::strand::Strand;
+use sequence::feature::{AnnotatedSequence, Topology};
+use align::{Aligner, AlignmentOp, Scoring, chars};
+use variants::{Variant, VariantKind, Effect, annotate, kmer_codes, kmer_index,
+               reverse_complement_chars};
+
+/// Settings for comparing a consensus against its designed construct.
+#[derive(Clone, Debug)]
+pub struct DiffParams {
+    /// Seed length used to find the consensus's strand and rotation.
+    pub kmer: usize,
+    /// A feature is reported missing once at least this fraction of its bases
+    /// are deleted or mismatched.
+    pub missing_fraction: f64,
+    /// Accept silent changes inside coding sequences.
+    pub allow_synonymous: bool,
+    /// Accept edits that fall outside every annotated feature.
+    pub allow_outside_features: bool,
+}
+
+impl Default for DiffParams {
+    fn default() -> DiffParams {
+        DiffParams { kmer: 15, missing_fraction: 0.5, allow_synonymous: false,
+                     allow_outside_features: true }
+    }
+}
+
+/// A difference between consensus and construct. The variant's count and
+/// depth are both one, since a consensus is a single observation.
+#[derive(Clone, Debug)]
+pub struct Edit {
+    pub variant: Variant,
+    /// Labels of the features the edit falls in.
+    pub features: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ConstructDiff {
+    /// Strand of the consensus relative to the construct.
+    pub strand: Strand,
+    /// Position in the oriented consensus that lines up with the construct's
+    /// origin; always zero for linear constructs.
+    pub rotation: usize,
+    pub identity: f64,
+    pub edits: Vec<Edit>,
+    pub missing_features: Vec<String>,
+    /// Reasons the consensus does not match the design.
+    pub failures: Vec<String>,
+}
+
+impl ConstructDiff {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Compare a consensus sequence against the construct it should match.
+///
+/// The consensus is first oriented with exact k-mer seeds: the strand with
+/// more seed hits wins and, for circular constructs, the seed nearest the
+/// construct's origin fixes the rotation. The oriented consensus is then
+/// aligned end to end against the construct, and every difference becomes
+/// an edit annotated with the features it touches.
+pub fn diff<N>(construct: &AnnotatedSequence, consensus: &Sequence<N>, params: &DiffParams)
+               -> ConstructDiff where N: StringIO<N=N> + Clone {
+    let target = chars(&construct.sequence);
+    let forward = chars(consensus);
+    if target.is_empty() {
+        // Nothing to orient against or to place edits in.
+        let failures = if forward.is_empty() { Vec::new() } else {
+            vec![format!("construct is empty but the consensus has {} bases", forward.len())]
+        };
+        let identity = if forward.is_empty() { 1.0 } else { 0.0 };
+        return ConstructDiff { strand: Strand::Forward, rotation: 0, identity, edits: Vec::new(),
+                               missing_features: Vec::new(), failures };
+    }
+    let (strand, rotation) = orient(construct, &target, &forward, params.kmer);
+    let mut query = match strand {
+        Strand::Forward => forward,
+        Strand::Reverse => reverse_complement_chars(&forward),
+    };
+    query.rotate_left(rotation);
+
+    let band = (query.len() as isize - target.len() as isize).unsigned_abs() + 64;
+    let alignment = Aligner::new(Scoring::default()).with_band(band).align(&query, &target, false);
+    let (edits, unmatched) = edits(construct, &target, &query, &alignment.ops);
+
+    let length = target.len();
+    let missing_features: Vec<String> = construct.features.iter()
+        .filter(|f| !f.is_empty())
+        .filter(|f| {
+            let lost = (f.start..f.end).filter(|&i| unmatched[i % length]).count();
+            lost as f64 / f.len() as f64 >= params.missing_fraction
+        })
+        .map(|f| f.label.clone())
+        .collect();
+
+    let mut failures: Vec<String> = missing_features.iter()
+        .map(|label| format!("feature {} is missing", label)).collect();
+    for edit in edits.iter() {
+        if edit.features.is_empty() {
+            if !params.allow_outside_features {
+                failures.push(format!("{} outside annotated features", edit.variant));
+            }
+            continue;
+        }
+        let consequences = &edit.variant.consequences;
+        let silent = edit.variant.kind == VariantKind::Snv
+            && consequences.len() == edit.features.len()
+            && consequences.iter().all(|c| matches!(c.effect, Effect::Synonymous { .. }));
+        if !(silent && params.allow_synonymous) {
+            failures.push(format!("{} in {}", edit.variant, edit.features.join(", ")));
+        }
+    }
+    ConstructDiff { strand, rotation, identity: alignment.identity(), edits, missing_features,
+                    failures }
+}
+
+/// Seed hits as (consensus position, construct position).
+type Hits = Vec<(usize, usize)>;
+
+/// Pick the consensus strand and, for circular constructs, the consensus
+/// position corresponding to the construct's origin.
+fn orient(construct: &AnnotatedSequence, target: &[char], consensus: &[char], k: usize)
+          -> (Strand, usize) {
+    let length = target.len();
+    let circular = construct.topology == Topology::Circular;
+    let mut indexed = target.to_vec();
+    if circular {
+        indexed.extend_from_slice(target);
+    }
+    let index = kmer_index(&indexed, k, length);
+    let diagonal = |hit: usize, position: usize| {
+        let d = hit as isize - position as isize;
+        if circular { d.rem_euclid(length as isize) } else { d }
+    };
+
+    let reverse = reverse_complement_chars(consensus);
+    let mut best: Option<(Strand, Hits, isize, usize)> = None;
+    for &(strand, query) in [(Strand::Forward, consensus), (Strand::Reverse, &reverse[..])].iter() {
+        let mut hits = Vec::new();
+        let mut votes: HashMap<isize, usize> = HashMap::new();
+        for (end, code) in kmer_codes(query, k).into_iter().enumerate() {
+            if let Some(targets) = code.and_then(|code| index.get(&code)) {
+                for &hit in targets.iter() {
+                    hits.push((end + 1 - k, hit));
+                    *votes.entry(diagonal(hit, end + 1 - k)).or_insert(0) += 1;
+                }
+            }
+        }
+        if let Some((d, count)) = votes.into_iter().max_by_key(|&(d, count)| (count, -d)) {
+            if best.as_ref().map(|b| count > b.3).unwrap_or(true) {
+                best = Some((strand, hits, d, count));
+            }
+        }
+    }
+    let (strand, hits, main, _) = match best {
+        Some(best) => best,
+        None => return (Strand::Forward, 0),
+    };
+    if !circular {
+        return (strand, 0);
+    }
+    // Indels shift the diagonal along the molecule, so use the seed closest
+    // to the origin that still lies near the dominant diagonal.
+    let slack = (consensus.len() as isize - length as isize).abs() + 32;
+    let near = |d: isize| {
+        let distance = (d - main).rem_euclid(length as isize);
+        distance.min(length as isize - distance) <= slack
+    };
+    let rotation = hits.iter()
+        .filter(|&&(position, hit)| near(diagonal(hit, position)))
+        .min_by_key(|&&(_, hit)| hit)
+        .map(|&(position, hit)| (position as isize - hit as isize)
+             .rem_euclid(consensus.len() as isize) as usize)
+        .unwrap_or(0);
+    (strand, rotation)
+}
+
+/// Turn alignment columns into annotated edits, and flag the construct
+/// positions the consensus does not reproduce.
+fn edits(construct: &AnnotatedSequence, target: &[char], query: &[char], ops: &[AlignmentOp])
+         -> (Vec<Edit>, Vec<bool>) {
+    let length = target.len();
+    let circular = construct.topology == Topology::Circular;
+    let mut unmatched = vec![false; length];
+    let mut edits = Vec::new();
+    let (mut q, mut t, mut k) = (0, 0, 0);
+    while k < ops.len() {
+        let run = ops[k..].iter().take_while(|&&op| op == ops[k]).count();
+        match ops[k] {
+            AlignmentOp::Match => {
+                q += 1;
+                t += 1;
+                k += 1;
+                continue;
+            },
+            AlignmentOp::Mismatch => {
+                unmatched[t] = true;
+                let variant = variant(construct, t, VariantKind::Snv, target[t].to_string(),
+                                      query[q].to_string());
+                edits.push(Edit { features: labels(construct, &[t]), variant });
+                q += 1;
+                t += 1;
+                k += 1;
+                continue;
+            },
+            AlignmentOp::Insertion => {
+                let inserted: String = query[q..q+run].iter().collect();
+                let variant = if t == 0 && (!circular || length == 0) {
+                    // Nothing precedes the insertion, so anchor on the base after it.
+                    let anchor = target.first().map(|ch| ch.to_string()).unwrap_or_default();
+                    variant(construct, 0, VariantKind::Insertion, anchor.clone(),
+                            format!("{}{}", inserted, anchor))
+                } else {
+                    let anchor = (t + length - 1) % length;
+                    variant(construct, anchor, VariantKind::Insertion, target[anchor].to_string(),
+                            format!("{}{}", target[anchor], inserted))
+                };
+                let features = if t == 0 || t == length { Vec::new() }
+                               else { common_labels(construct, t - 1, t) };
+                edits.push(Edit { variant, features });
+                q += run;
+            },
+            AlignmentOp::Deletion => {
+                let deleted: String = target[t..t+run].iter().collect();
+                for flag in unmatched[t..t+run].iter_mut() {
+                    *flag = true;
+                }
+                let variant = if t == 0 && !circular {
+                    let after = target.get(run).map(|ch| ch.to_string()).unwrap_or_default();
+                    variant(construct, 0, VariantKind::Deletion, format!("{}{}", deleted, after),
+                            after)
+                } else {
+                    let anchor = (t + length - 1) % length;
+                    variant(construct, anchor, VariantKind::Deletion,
+                            format!("{}{}", target[anchor], deleted), target[anchor].to_string())
+                };
+                let positions: Vec<usize> = (t..t+run).collect();
+                edits.push(Edit { features: labels(construct, &positions), variant });
+                t += run;
+            },
+        }
+        k += run;
+    }
+    (edits, unmatched)
+}
+
+fn variant(construct: &AnnotatedSequence, position: usize, kind: VariantKind, reference: String,
+           alternate: String) -> Variant {
+    let mut variant = Variant { position, kind, reference, alternate, count: 1, depth: 1,
+                                frequency: 1.0, consequences: Vec::new() };
+    variant.consequences = annotate(construct, &variant);
+    variant
+}
+
+/// Labels of features covering any of `positions`, in annotation order.
+fn labels(construct: &AnnotatedSequence, positions: &[usize]) -> Vec<String> {
+    let length = construct.sequence.len();
+    construct.features.iter()
+        .filter(|f| positions.iter().any(|&p| f.contains(p, length)))
+        .map(|f| f.label.clone())
+        .collect()
+}
+
+/// Labels of features covering both sides of an insertion point.
+fn common_labels(construct: &AnnotatedSequence, before: usize, after: usize) -> Vec<String> {
+    let length = construct.sequence.len();
+    construct.features.iter()
+        .filter(|f| f.contains(before, length) && f.contains(after, length))
+        .map(|f| f.label.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, DiffParams};
+    use sequence::sequence::Sequence;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::degenerate_nucleotide::DegenerateNucleotide;
+    use sequence::strand::Strand;
+    use sequence::feature::{AnnotatedSequence, Feature, FeatureKind, Topology};
+    use variants::{Effect, VariantKind};
+
+    // A 120 bp construct with a forward CDS at 10..52 and a tag at 60..90.
+    const REFERENCE: &str = "GCGCTTAACGATGAAAGCGATTCTGGTGCTGTACGGTCAGCTGAAACGCTAATCGGATCCAAGCTTGCATGCCTGCAGGTCGACTCTAGAGGATCCCCGGGTACCGAGCTCGAATTCCAT";
+
+    fn construct() -> AnnotatedSequence {
+        let seq = Sequence::<Nucleotide>::from_str(REFERENCE).unwrap();
+        let mut construct = AnnotatedSequence::new("pTest", seq, Topology::Circular);
+        construct.features.push(Feature::new(FeatureKind::Cds, "geneA", 10, 52, Strand::Forward));
+        construct.features.push(Feature::new(FeatureKind::MiscFeature, "tag", 60, 90,
+                                             Strand::Forward));
+        construct
+    }
+
+    /// The sample sequence started at `rotation` and read from `strand`.
+    fn consensus(sample: &str, rotation: usize, strand: Strand) -> Sequence<DegenerateNucleotide> {
+        let rotated = format!("{}{}", &sample[rotation..], &sample[..rotation]);
+        let seq = Sequence::<DegenerateNucleotide>::from_str(&rotated).unwrap();
+        match strand {
+            Strand::Forward => seq,
+            Strand::Reverse => seq.reverse_complement(),
+        }
+    }
+
+    #[test]
+    fn rotated_reverse_complement_matches() {
+        let construct = construct();
+        let result = diff(&construct, &consensus(REFERENCE, 37, Strand::Reverse),
+                          &DiffParams::default());
+        assert_eq!(result.strand, Strand::Reverse);
+        assert!(result.edits.is_empty());
+        assert!(result.passed());
+        assert!((result.identity - 1.0).abs() < 1e-9);
+        let result = diff(&construct, &consensus(REFERENCE, 37, Strand::Forward),
+                          &DiffParams::default());
+        assert_eq!((result.strand, result.rotation), (Strand::Forward, 83));
+    }
+
+    #[test]
+    fn empty_construct() {
+        let empty = AnnotatedSequence::new("empty", Sequence::new(), Topology::Circular);
+        let result = diff(&empty, &consensus(REFERENCE, 0, Strand::Forward),
+                          &DiffParams::default());
+        assert!(!result.passed() && result.edits.is_empty());
+        assert!(diff(&empty, &consensus("", 0, Strand::Forward), &DiffParams::default()).passed());
+    }
+
+    #[test]
+    fn edits_in_and_outside_features() {
+        let construct = construct();
+        // Codon 3 of geneA GCG becomes GAG, and a backbone base changes.
+        let mut sample = String::from(REFERENCE);
+        sample.replace_range(17..18, "A");
+        sample.replace_range(100..101, "T");
+        let result = diff(&construct, &consensus(&sample, 90, Strand::Reverse),
+                          &DiffParams::default());
+        assert_eq!(result.edits.len(), 2);
+        let edit = &result.edits[0];
+        assert_eq!(edit.variant.to_string(), "18C>A");
+        assert_eq!(edit.features, vec![String::from("geneA")]);
+        assert_eq!(edit.variant.consequences[0].effect.to_string(), "p.A3E");
+        assert!(result.edits[1].features.is_empty());
+        assert!(!result.passed());
+        assert_eq!(result.failures.len(), 1);
+        let strict = DiffParams { allow_outside_features: false, ..DiffParams::default() };
+        assert_eq!(diff(&construct, &consensus(&sample, 90, Strand::Reverse), &strict)
+                   .failures.len(), 2);
+
+        // A silent change, GCG to GCA, is tolerated when asked.
+        let mut sample = String::from(REFERENCE);
+        sample.replace_range(18..19, "A");
+        let lenient = DiffParams { allow_synonymous: true, ..DiffParams::default() };
+        let result = diff(&construct, &consensus(&sample, 5, Strand::Forward), &lenient);
+        match result.edits[0].variant.consequences[0].effect {
+            Effect::Synonymous { codon: 3, .. } => (),
+            ref other => panic!("expected a synonymous change, got {:?}", other),
+        }
+        assert!(result.passed());
+    }
+
+    #[test]
+    fn indels_and_missing_feature() {
+        let construct = construct();
+        let mut sample = String::from(REFERENCE);
+        sample.replace_range(60..90, "");
+        sample.remove(24);
+        let result = diff(&construct, &consensus(&sample, 50, Strand::Forward),
+                          &DiffParams::default());
+        assert_eq!(result.missing_features, vec![String::from("tag")]);
+        let kinds: Vec<VariantKind> = result.edits.iter().map(|e| e.variant.kind.clone()).collect();
+        assert_eq!(kinds, vec![VariantKind::Deletion, VariantKind::Deletion]);
+        match result.edits[0].variant.consequences[0].effect {
+            Effect::Frameshift { codon: 5, .. } => (),
+            ref other => panic!("expected a frameshift, got {:?}", other),
+        }
+        assert_eq!(result.edits[1].variant.reference.len(), 31);
+        assert!(!result.passed());
+        assert!(result.failures[0].contains("tag"));
+    }
+}
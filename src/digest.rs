@@ -0,0 +1,195 @@
+use sequence::sequence::Sequence;
+use sequence::nucleotide::Nucleotide;
+use sequence::degenerate_nucleotide::DegenerateNucleotide;
+use sequence::strand::Strand;
+use sequence::feature::Topology;
+use search::search;
+
+/// A restriction enzyme and where it cuts relative to the start of its site,
+/// as positions between bases on the top strand of the site as written.
+/// Type IIS enzymes cut outside their site, so positions may exceed its
+/// length.
+#[derive(Debug)]
+pub struct Enzyme {
+    pub name: &'static str,
+    pub site: &'static str,
+    pub cut: isize,
+    pub complement_cut: isize,
+}
+
+static ENZYMES: [Enzyme; 30] = [
+    Enzyme { name: "AgeI", site: "ACCGGT", cut: 1, complement_cut: 5 },
+    Enzyme { name: "ApaI", site: "GGGCCC", cut: 5, complement_cut: 1 },
+    Enzyme { name: "BamHI", site: "GGATCC", cut: 1, complement_cut: 5 },
+    Enzyme { name: "BbsI", site: "GAAGAC", cut: 8, complement_cut: 12 },
+    Enzyme { name: "BglI", site: "GCCNNNNNGGC", cut: 7, complement_cut: 4 },
+    Enzyme { name: "BglII", site: "AGATCT", cut: 1, complement_cut: 5 },
+    Enzyme { name: "BsaI", site: "GGTCTC", cut: 7, complement_cut: 11 },
+    Enzyme { name: "BsmBI", site: "CGTCTC", cut: 7, complement_cut: 11 },
+    Enzyme { name: "ClaI", site: "ATCGAT", cut: 2, complement_cut: 4 },
+    Enzyme { name: "DraI", site: "TTTAAA", cut: 3, complement_cut: 3 },
+    Enzyme { name: "EcoRI", site: "GAATTC", cut: 1, complement_cut: 5 },
+    Enzyme { name: "EcoRV", site: "GATATC", cut: 3, complement_cut: 3 },
+    Enzyme { name: "HindIII", site: "AAGCTT", cut: 1, complement_cut: 5 },
+    Enzyme { name: "KpnI", site: "GGTACC", cut: 5, complement_cut: 1 },
+    Enzyme { name: "MluI", site: "ACGCGT", cut: 1, complement_cut: 5 },
+    Enzyme { name: "NcoI", site: "CCATGG", cut: 1, complement_cut: 5 },
+    Enzyme { name: "NdeI", site: "CATATG", cut: 2, complement_cut: 4 },
+    Enzyme { name: "NheI", site: "GCTAGC", cut: 1, complement_cut: 5 },
+    Enzyme { name: "NotI", site: "GCGGCCGC", cut: 2, complement_cut: 6 },
+    Enzyme { name: "PacI", site: "TTAATTAA", cut: 5, complement_cut: 3 },
+    Enzyme { name: "PaqCI", site: "CACCTGC", cut: 11, complement_cut: 15 },
+    Enzyme { name: "PstI", site: "CTGCAG", cut: 5, complement_cut: 1 },
+    Enzyme { name: "SacI", site: "GAGCTC", cut: 5, complement_cut: 1 },
+    Enzyme { name: "SalI", site: "GTCGAC", cut: 1, complement_cut: 5 },
+    Enzyme { name: "SapI", site: "GCTCTTC", cut: 8, complement_cut: 11 },
+    Enzyme { name: "SmaI", site: "CCCGGG", cut: 3, complement_cut: 3 },
+    Enzyme { name: "SpeI", site: "ACTAGT", cut: 1, complement_cut: 5 },
+    Enzyme { name: "SphI", site: "GCATGC", cut: 5, complement_cut: 1 },
+    Enzyme { name: "XbaI", site: "TCTAGA", cut: 1, complement_cut: 5 },
+    Enzyme { name: "XhoI", site: "CTCGAG", cut: 1, complement_cut: 5 },
+];
+
+impl Enzyme {
+    /// Look up a bundled enzyme by name, ignoring case.
+    pub fn from_name(name: &str) -> Option<&'static Enzyme> {
+        ENZYMES.iter().find(|e| e.name.eq_ignore_ascii_case(name))
+    }
+    pub fn all() -> &'static [Enzyme] {
+        &ENZYMES
+    }
+    pub fn site(&self) -> Sequence<DegenerateNucleotide> {
+        Sequence::<DegenerateNucleotide>::from_str(self.site).unwrap()
+    }
+
+    /// Top-strand cut positions in a sequence, ordered and without repeats.
+    /// Cuts falling off the ends of a linear sequence are dropped.
+    pub fn cuts(&self, sequence: &Sequence<Nucleotide>, topology: Topology) -> Vec<usize> {
+        let length = sequence.len() as isize;
+        let width = self.site.len() as isize;
+        let mut cuts: Vec<usize> = search(sequence, &self.site(), topology).iter()
+            .map(|m| match m.strand {
+                Strand::Forward => m.start as isize + self.cut,
+                Strand::Reverse => m.start as isize + width - self.complement_cut,
+            })
+            .filter_map(|cut| match topology {
+                Topology::Linear if cut <= 0 || cut >= length => None,
+                Topology::Linear => Some(cut as usize),
+                Topology::Circular => Some(cut.rem_euclid(length) as usize),
+            })
+            .collect();
+        cuts.sort();
+        cuts.dedup();
+        cuts
+    }
+}
+
+/// A piece of a digested sequence and the enzymes that produced its ends;
+/// an end without an enzyme is an end of the original linear sequence.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Fragment {
+    pub start: usize,
+    pub end: usize,
+    pub left: Option<&'static str>,
+    pub right: Option<&'static str>,
+}
+
+impl Fragment {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+    pub fn is_empty(&self) -> bool {
+        self.end == self.start
+    }
+}
+
+/// Cut a sequence with a set of enzymes. On a circular sequence the last
+/// fragment runs across the origin and so ends past the sequence length.
+/// An uncut circle comes back as a single fragment with no enzyme ends.
+pub fn digest(sequence: &Sequence<Nucleotide>, topology: Topology, enzymes: &[&'static Enzyme])
+              -> Vec<Fragment> {
+    let length = sequence.len();
+    let mut cuts: Vec<(usize, &'static str)> = enzymes.iter()
+        .flat_map(|e| e.cuts(sequence, topology).into_iter().map(move |cut| (cut, e.name)))
+        .collect();
+    cuts.sort();
+    cuts.dedup_by_key(|cut| cut.0);
+    let mut fragments = Vec::new();
+    match topology {
+        Topology::Linear => {
+            let mut start = (0, None);
+            for &(cut, name) in cuts.iter() {
+                fragments.push(Fragment { start: start.0, end: cut, left: start.1,
+                                          right: Some(name) });
+                start = (cut, Some(name));
+            }
+            fragments.push(Fragment { start: start.0, end: length, left: start.1, right: None });
+        },
+        Topology::Circular => {
+            if cuts.is_empty() {
+                fragments.push(Fragment { start: 0, end: length, left: None, right: None });
+            }
+            for (i, &(cut, name)) in cuts.iter().enumerate() {
+                let (next, next_name) = if i + 1 < cuts.len() { cuts[i + 1] }
+                                        else { (cuts[0].0 + length, cuts[0].1) };
+                fragments.push(Fragment { start: cut, end: next, left: Some(name),
+                                          right: Some(next_name) });
+            }
+        },
+    }
+    fragments
+}
+
+/// Every position where one of `enzymes` cuts, in order, with the names of
+/// the enzymes cutting there in the order given.
+pub fn cut_sites(sequence: &Sequence<Nucleotide>, topology: Topology,
+                 enzymes: &[&'static Enzyme]) -> Vec<(usize, Vec<&'static str>)> {
+    let mut sites: Vec<(usize, Vec<&'static str>)> = Vec::new();
+    for enzyme in enzymes.iter() {
+        for cut in enzyme.cuts(sequence, topology) {
+            match sites.iter_mut().find(|site| site.0 == cut) {
+                Some(site) => site.1.push(enzyme.name),
+                None => sites.push((cut, vec![enzyme.name])),
+            }
+        }
+    }
+    sites.sort_by_key(|site| site.0);
+    sites
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{digest, cut_sites, Enzyme};
+    use sequence::sequence::Sequence;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::feature::Topology;
+
+    #[test]
+    fn cuts() {
+        let seq = Sequence::<Nucleotide>::from_str("AAGAATTCAAAGGTCTCAAAAAAAGAGACCAA").unwrap();
+        let eco = Enzyme::from_name("ecori").unwrap();
+        assert_eq!(eco.cuts(&seq, Topology::Linear), vec![3]);
+        // BsaI cuts downstream of its site on whichever strand it lies.
+        let bsa = Enzyme::from_name("BsaI").unwrap();
+        assert_eq!(bsa.cuts(&seq, Topology::Linear), vec![18, 19]);
+        assert!(Enzyme::from_name("Nonexistent").is_none());
+        for enzyme in Enzyme::all().iter() {
+            assert_eq!(enzyme.site().len(), enzyme.site.len());
+        }
+    }
+
+    #[test]
+    fn fragments() {
+        let seq = Sequence::<Nucleotide>::from_str("AAGAATTCAAAAAGGATCCAAAA").unwrap();
+        let enzymes = [Enzyme::from_name("EcoRI").unwrap(), Enzyme::from_name("BamHI").unwrap()];
+        let linear = digest(&seq, Topology::Linear, &enzymes);
+        assert_eq!(linear.iter().map(|f| f.len()).collect::<Vec<_>>(), vec![3, 11, 9]);
+        assert_eq!((linear[1].left, linear[1].right), (Some("EcoRI"), Some("BamHI")));
+        let circular = digest(&seq, Topology::Circular, &enzymes);
+        assert_eq!(circular.len(), 2);
+        assert_eq!((circular[1].start, circular[1].end), (14, 26));
+        assert_eq!(digest(&seq, Topology::Circular, &[]).len(), 1);
+        assert_eq!(cut_sites(&seq, Topology::Linear, &[enzymes[1], enzymes[0]]),
+                   vec![(3, vec!["EcoRI"]), (14, vec!["BamHI"])]);
+    }
+}
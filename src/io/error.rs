@@ -0,0 +1,65 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use sequence::error::SequenceError;
+
+/// Failures raised while reading or writing sequence files.
+#[derive(Debug)]
+pub enum FormatError {
+    /// The underlying reader or writer failed.
+    Io(io::Error),
+    /// The file does not follow the structure of its format.
+    Malformed(String),
+    /// No record with this name exists in the file.
+    UnknownRecord(String),
+    /// A line of a text format held a symbol outside the alphabet being
+    /// read; the error's offset is relative to the start of that line.
+    InvalidSequence { line: usize, error: SequenceError },
+    /// A region string such as `chr1:1000-2000` could not be parsed.
+    InvalidRegion(String),
+    /// A name that cannot identify a record where it is to be stored, such
+    /// as a part name that is not a plain file name.
+    InvalidName(String),
+    /// A sequence-level failure, such as a range past the end of a record
+    /// or a symbol outside the requested alphabet.
+    Sequence(SequenceError),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FormatError::Io(ref e) => write!(f, "I/O error: {}", e),
+            FormatError::Malformed(ref message) => write!(f, "Malformed file: {}", message),
+            FormatError::UnknownRecord(ref name) => write!(f, "No record named {}", name),
+            FormatError::InvalidSequence { line, ref error } =>
+                write!(f, "Line {}: {}", line, error),
+            FormatError::InvalidRegion(ref region) => write!(f, "Invalid region {}", region),
+            FormatError::InvalidName(ref name) => write!(f, "Invalid name '{}'", name),
+            FormatError::Sequence(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for FormatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            FormatError::Io(ref e) => Some(e),
+            FormatError::Sequence(ref e) => Some(e),
+            FormatError::InvalidSequence { ref error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FormatError {
+    fn from(e: io::Error) -> FormatError {
+        FormatError::Io(e)
+    }
+}
+
+impl From<SequenceError> for FormatError {
+    fn from(e: SequenceError) -> FormatError {
+        FormatError::Sequence(e)
+    }
+}
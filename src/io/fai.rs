@@ -0,0 +1,363 @@
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use sequence::sequence::Sequence;
+use sequence::string_io::StringIO;
+use sequence::nucleotide::Complement;
+use sequence::strand::Strand;
+use sequence::error::SequenceError;
+use io::error::FormatError;
+
+/// One line of a samtools-compatible .fai index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FaiRecord {
+    pub name: String,
+    pub length: usize,
+    /// Byte offset of the first base of the record.
+    pub offset: u64,
+    /// Bases on each full line.
+    pub line_bases: usize,
+    /// Bytes on each full line, including the line terminator.
+    pub line_width: usize,
+}
+
+impl FaiRecord {
+    fn byte_offset(&self, index: usize) -> u64 {
+        self.offset + ((index / self.line_bases) * self.line_width
+                       + index % self.line_bases) as u64
+    }
+}
+
+/// The index of a FASTA file, as written by `samtools faidx`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FastaIndex {
+    records: Vec<FaiRecord>,
+}
+
+impl FastaIndex {
+    /// Index a FASTA file by scanning it once.
+    ///
+    /// As with samtools, every line of a record but the last must hold the
+    /// same number of bases.
+    pub fn build<R: BufRead>(mut reader: R) -> Result<FastaIndex, FormatError> {
+        let mut records: Vec<FaiRecord> = Vec::new();
+        let mut line = Vec::new();
+        let mut pos: u64 = 0;
+        let mut line_number = 0;
+        // Set once a record has a line shorter than its first one.
+        let mut ended = false;
+        loop {
+            line.clear();
+            let width = reader.read_until(b'\n', &mut line)?;
+            if width == 0 {
+                break;
+            }
+            line_number += 1;
+            let start = pos;
+            pos += width as u64;
+            let text = trim_newline(&line);
+            if text.first() == Some(&b'>') {
+                let header = String::from_utf8_lossy(&text[1..]);
+                let name = header.split_whitespace().next().unwrap_or("");
+                records.push(FaiRecord { name: String::from(name), length: 0, offset: pos,
+                                         line_bases: 0, line_width: 0 });
+                ended = false;
+                continue;
+            }
+            let record = match records.last_mut() {
+                Some(record) => record,
+                None if text.is_empty() => continue,
+                None => return Err(FormatError::Malformed(
+                    format!("line {}: sequence before the first header", line_number))),
+            };
+            if text.is_empty() {
+                ended = true;
+                continue;
+            }
+            if ended {
+                return Err(FormatError::Malformed(
+                    format!("line {}: different line length in sequence {}",
+                            line_number, record.name)));
+            }
+            if record.line_bases == 0 {
+                record.offset = start;
+                record.line_bases = text.len();
+                record.line_width = width;
+            } else if text.len() > record.line_bases {
+                return Err(FormatError::Malformed(
+                    format!("line {}: different line length in sequence {}",
+                            line_number, record.name)));
+            }
+            if text.len() < record.line_bases || width == text.len() {
+                ended = true;
+            }
+            record.length += text.len();
+        }
+        Ok(FastaIndex { records })
+    }
+
+    /// Read an existing .fai file.
+    pub fn read<R: BufRead>(reader: R) -> Result<FastaIndex, FormatError> {
+        let mut records = Vec::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            let malformed = || FormatError::Malformed(format!("line {}: bad .fai entry", i+1));
+            if fields.len() < 5 {
+                return Err(malformed());
+            }
+            let record = FaiRecord {
+                name: String::from(fields[0]),
+                length: fields[1].parse().map_err(|_| malformed())?,
+                offset: fields[2].parse().map_err(|_| malformed())?,
+                line_bases: fields[3].parse().map_err(|_| malformed())?,
+                line_width: fields[4].parse().map_err(|_| malformed())?,
+            };
+            // Only an empty record, which is never read, may have no bases
+            // per line.
+            if (record.line_bases == 0 && record.length > 0)
+                || record.line_width < record.line_bases {
+                return Err(malformed());
+            }
+            records.push(record);
+        }
+        Ok(FastaIndex { records })
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), FormatError> {
+        for r in self.records.iter() {
+            writeln!(writer, "{}\t{}\t{}\t{}\t{}",
+                     r.name, r.length, r.offset, r.line_bases, r.line_width)?;
+        }
+        Ok(())
+    }
+
+    pub fn records(&self) -> &[FaiRecord] {
+        &self.records
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FaiRecord> {
+        self.records.iter().find(|r| r.name == name)
+    }
+}
+
+fn trim_newline(line: &[u8]) -> &[u8] {
+    let mut end = line.len();
+    while end > 0 && (line[end-1] == b'\n' || line[end-1] == b'\r') {
+        end -= 1;
+    }
+    &line[..end]
+}
+
+/// A stretch of a named record, held zero-based and half-open.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Region {
+    pub name: String,
+    pub start: usize,
+    /// `None` runs to the end of the record.
+    pub end: Option<usize>,
+    pub strand: Strand,
+}
+
+impl Region {
+    pub fn new(name: &str, start: usize, end: Option<usize>) -> Region {
+        Region { name: String::from(name), start, end, strand: Strand::Forward }
+    }
+
+    /// Parse a samtools region string: `name`, `name:begin` or
+    /// `name:begin-end`, with one-based inclusive coordinates that may
+    /// contain thousands separators.
+    pub fn parse(spec: &str) -> Result<Region, FormatError> {
+        let invalid = || FormatError::InvalidRegion(String::from(spec));
+        let (name, range) = match spec.rfind(':') {
+            Some(colon) => (&spec[..colon], Some(&spec[colon+1..])),
+            None => (spec, None),
+        };
+        if name.is_empty() {
+            return Err(invalid());
+        }
+        let range = match range {
+            Some(range) => range,
+            None => return Ok(Region::new(name, 0, None)),
+        };
+        let number = |s: &str| s.replace(',', "").parse::<usize>().map_err(|_| invalid());
+        let (begin, end) = match range.find('-') {
+            Some(dash) => (number(&range[..dash])?, Some(number(&range[dash+1..])?)),
+            None => (number(range)?, None),
+        };
+        if begin == 0 || end.map(|end| end < begin).unwrap_or(false) {
+            return Err(invalid());
+        }
+        Ok(Region::new(name, begin - 1, end))
+    }
+
+    pub fn with_strand(mut self, strand: Strand) -> Region {
+        self.strand = strand;
+        self
+    }
+}
+
+/// Random access to the records of an indexed FASTA file.
+pub struct IndexedFastaReader<R> {
+    reader: R,
+    index: FastaIndex,
+}
+
+impl IndexedFastaReader<File> {
+    /// Open `path`, reading its index from `path.fai` if one exists and
+    /// building it in memory otherwise.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<IndexedFastaReader<File>, FormatError> {
+        let mut fai_path = OsString::from(path.as_ref());
+        fai_path.push(".fai");
+        let index = match File::open(&fai_path) {
+            Ok(fai) => FastaIndex::read(BufReader::new(fai))?,
+            Err(_) => FastaIndex::build(BufReader::new(File::open(path.as_ref())?))?,
+        };
+        Ok(IndexedFastaReader::new(File::open(path)?, index))
+    }
+}
+
+impl<R> IndexedFastaReader<R> where R: Read + Seek {
+    pub fn new(reader: R, index: FastaIndex) -> IndexedFastaReader<R> {
+        IndexedFastaReader { reader, index }
+    }
+
+    pub fn index(&self) -> &FastaIndex {
+        &self.index
+    }
+
+    /// Fetch the sequence of `region`, reverse complemented if it lies on
+    /// the reverse strand. An end past the record is clamped to its length,
+    /// as samtools does.
+    pub fn fetch<N>(&mut self, region: &Region) -> Result<Sequence<N>, FormatError>
+        where N: StringIO<N=N> + Complement<N=N> + Clone {
+        let record = match self.index.get(&region.name) {
+            Some(record) => record.clone(),
+            None => return Err(FormatError::UnknownRecord(region.name.clone())),
+        };
+        let end = region.end.unwrap_or(record.length).min(record.length);
+        if region.start > record.length {
+            return Err(FormatError::Sequence(SequenceError::IndexOutOfBounds {
+                index: region.start, length: record.length }));
+        }
+        if region.start >= end {
+            return Ok(Sequence::<N>::new());
+        }
+        let first = record.byte_offset(region.start);
+        let last = record.byte_offset(end - 1);
+        let mut bytes = vec![0u8; (last - first + 1) as usize];
+        self.reader.seek(SeekFrom::Start(first))?;
+        self.reader.read_exact(&mut bytes)?;
+        let mut seq = Sequence::<N>::new();
+        for &b in bytes.iter().filter(|&&b| b != b'\n' && b != b'\r') {
+            let offset = seq.len();
+            seq.push(N::from_char(b as char).map_err(|e| e.offset_by(offset))?);
+        }
+        Ok(match region.strand {
+            Strand::Forward => seq,
+            Strand::Reverse => seq.reverse_complement(),
+        })
+    }
+
+    /// Fetch a region given as a samtools region string. A string that is
+    /// itself the name of a record fetches that whole record, so names
+    /// containing colons still work.
+    pub fn fetch_str<N>(&mut self, spec: &str, strand: Strand) -> Result<Sequence<N>, FormatError>
+        where N: StringIO<N=N> + Complement<N=N> + Clone {
+        let region = if self.index.get(spec).is_some()
+            { Region::new(spec, 0, None) }
+        else
+            { Region::parse(spec)? };
+        self.fetch(&region.with_strand(strand))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::Cursor;
+
+    use super::{FastaIndex, FaiRecord, Region, IndexedFastaReader};
+    use sequence::nucleotide::Nucleotide;
+    use sequence::degenerate_nucleotide::DegenerateNucleotide;
+    use sequence::sequence::Sequence;
+    use sequence::strand::Strand;
+    use sequence::error::SequenceError;
+    use io::error::FormatError;
+
+    const FASTA: &str = ">chr1 first chromosome\nACGTACGTAC\nGGGGCCCCAA\nTTT\n>chr2\nNNACG\n>chr:3\nGATTACA\n";
+
+    #[test]
+    fn build_index() {
+        let index = FastaIndex::build(Cursor::new(FASTA)).unwrap();
+        assert_eq!(index.records()[0],
+                   FaiRecord { name: String::from("chr1"), length: 23, offset: 23,
+                               line_bases: 10, line_width: 11 });
+        assert_eq!(index.records()[1],
+                   FaiRecord { name: String::from("chr2"), length: 5, offset: 55,
+                               line_bases: 5, line_width: 6 });
+        let mut fai = Vec::new();
+        index.write(&mut fai).unwrap();
+        assert_eq!(String::from_utf8(fai.clone()).unwrap(),
+                   "chr1\t23\t23\t10\t11\nchr2\t5\t55\t5\t6\nchr:3\t7\t68\t7\t8\n");
+        assert_eq!(FastaIndex::read(Cursor::new(fai)).unwrap(), index);
+        for bad in ["a\t4\t3\t0\t1\n", "a\t4\t3\t4\t3\n"].iter() {
+            assert!(FastaIndex::read(Cursor::new(bad)).is_err());
+        }
+        assert!(FastaIndex::read(Cursor::new("empty\t0\t9\t0\t0\n")).is_ok());
+        assert!(FastaIndex::build(Cursor::new(">a\nACGT\nAC\nACGT\n")).is_err());
+        assert!(FastaIndex::build(Cursor::new(">a\r\nACGT\r\nAC\r\n")).is_ok());
+    }
+
+    #[test]
+    fn regions() {
+        assert_eq!(Region::parse("chr1").unwrap(), Region::new("chr1", 0, None));
+        assert_eq!(Region::parse("chr1:1,000").unwrap(), Region::new("chr1", 999, None));
+        assert_eq!(Region::parse("chr1:1,000-2,000").unwrap(),
+                   Region::new("chr1", 999, Some(2000)));
+        for bad in ["chr1:0-10", "chr1:20-10", ":1-2", "chr1:a-b"].iter() {
+            assert!(Region::parse(bad).is_err(), "accepted {}", bad);
+        }
+    }
+
+    #[test]
+    fn fetch() {
+        let index = FastaIndex::build(Cursor::new(FASTA)).unwrap();
+        let mut reader = IndexedFastaReader::new(Cursor::new(FASTA), index);
+        let seq: Sequence<Nucleotide> = reader.fetch_str("chr1:9-13", Strand::Forward).unwrap();
+        assert_eq!(seq.to_string(), "ACGGG");
+        let seq: Sequence<Nucleotide> = reader.fetch_str("chr1:9-13", Strand::Reverse).unwrap();
+        assert_eq!(seq.to_string(), "CCCGT");
+        let seq: Sequence<Nucleotide> = reader.fetch_str("chr1:20-100", Strand::Forward).unwrap();
+        assert_eq!(seq.to_string(), "ATTT");
+        let seq: Sequence<Nucleotide> = reader.fetch_str("chr:3", Strand::Forward).unwrap();
+        assert_eq!(seq.to_string(), "GATTACA");
+        let seq: Sequence<DegenerateNucleotide> = reader.fetch_str("chr2", Strand::Forward).unwrap();
+        assert_eq!(seq.to_string(), "NNACG");
+        match reader.fetch_str::<Nucleotide>("chr2:2-4", Strand::Forward) {
+            Err(FormatError::Sequence(e)) =>
+                assert_eq!(e, SequenceError::InvalidSymbol { symbol: 'N', offset: 0 }),
+            other => panic!("expected an invalid symbol, got {:?}", other),
+        }
+        match reader.fetch_str::<Nucleotide>("chrX:1-4", Strand::Forward) {
+            Err(FormatError::UnknownRecord(_)) => (),
+            other => panic!("expected an unknown record, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn open() {
+        let path = env::temp_dir().join(format!("constructor-fai-{}.fa", ::std::process::id()));
+        fs::write(&path, FASTA).unwrap();
+        let mut reader = IndexedFastaReader::open(&path).unwrap();
+        let seq: Sequence<Nucleotide> = reader.fetch(&Region::new("chr1", 10, Some(14))).unwrap();
+        assert_eq!(seq.to_string(), "GGGG");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+use std::io::{BufRead, Write};
+use std::marker::PhantomData;
+
+use sequence::sequence::Sequence;
+use sequence::string_io::StringIO;
+use sequence::storage::Stored;
+use io::error::FormatError;
+
+#[derive(Clone, Debug)]
+pub struct FastaRecord<N> where N: Stored {
+    pub id: String,
+    pub description: Option<String>,
+    pub sequence: Sequence<N>,
+}
+
+impl<N> FastaRecord<N> where N: Stored {
+    pub fn new(id: &str, sequence: Sequence<N>) -> FastaRecord<N> {
+        FastaRecord { id: String::from(id), description: None, sequence }
+    }
+}
+
+/// Streaming FASTA parser yielding one record at a time, so memory use is
+/// bounded by the longest record rather than the file.
+pub struct FastaReader<R, N> {
+    reader: R,
+    line: usize,
+    /// Header of the next record, read while finishing the previous one.
+    header: Option<String>,
+    phantom: PhantomData<N>,
+}
+
+impl<R, N> FastaReader<R, N> where R: BufRead, N: StringIO<N=N> + Clone {
+    pub fn new(reader: R) -> FastaReader<R, N> {
+        FastaReader { reader, line: 0, header: None, phantom: PhantomData }
+    }
+
+    fn next_line(&mut self) -> Result<Option<String>, FormatError> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        self.line += 1;
+        while line.ends_with('\n') || line.ends_with('\r') {
+            line.pop();
+        }
+        Ok(Some(line))
+    }
+
+    fn read_record(&mut self) -> Result<Option<FastaRecord<N>>, FormatError> {
+        let header = match self.header.take() {
+            Some(header) => header,
+            None => loop {
+                match self.next_line()? {
+                    None => return Ok(None),
+                    Some(ref line) if line.trim().is_empty() || line.starts_with(';') => continue,
+                    Some(ref line) if !line.starts_with('>') => return Err(FormatError::Malformed(
+                        format!("line {}: expected a header starting with >", self.line))),
+                    Some(line) => break line,
+                }
+            },
+        };
+        let header = header[1..].trim();
+        let (id, description) = match header.find(char::is_whitespace) {
+            Some(space) => (String::from(&header[..space]),
+                            Some(String::from(header[space..].trim()))),
+            None => (String::from(header), None),
+        };
+        let mut sequence = Sequence::<N>::new();
+        while let Some(line) = self.next_line()? {
+            if line.starts_with('>') {
+                self.header = Some(line);
+                break;
+            }
+            for (offset, ch) in line.chars().enumerate() {
+                if ch.is_whitespace() {
+                    continue;
+                }
+                match N::from_char(ch) {
+                    Ok(nt) => sequence.push(nt),
+                    Err(e) => return Err(FormatError::InvalidSequence { line: self.line,
+                                                                         error: e.offset_by(offset) }),
+                }
+            }
+        }
+        Ok(Some(FastaRecord { id, description, sequence }))
+    }
+}
+
+impl<R, N> Iterator for FastaReader<R, N> where R: BufRead, N: StringIO<N=N> + Clone {
+    type Item = Result<FastaRecord<N>, FormatError>;
+    fn next(&mut self) -> Option<Result<FastaRecord<N>, FormatError>> {
+        match self.read_record() {
+            Ok(Some(record)) => Some(Ok(record)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+pub struct FastaWriter<W> {
+    writer: W,
+    line_width: usize,
+}
+
+impl<W> FastaWriter<W> where W: Write {
+    /// Write records wrapped at 60 bases per line.
+    pub fn new(writer: W) -> FastaWriter<W> {
+        FastaWriter { writer, line_width: 60 }
+    }
+
+    /// Set the number of bases per line; zero writes each sequence on one line.
+    pub fn with_line_width(mut self, line_width: usize) -> FastaWriter<W> {
+        self.line_width = line_width;
+        self
+    }
+
+    pub fn write<N>(&mut self, record: &FastaRecord<N>) -> Result<(), FormatError>
+        where N: StringIO<N=N> + Clone {
+        match record.description {
+            Some(ref description) => writeln!(self.writer, ">{} {}", record.id, description)?,
+            None => writeln!(self.writer, ">{}", record.id)?,
+        }
+        let text = record.sequence.to_string();
+        if self.line_width == 0 {
+            writeln!(self.writer, "{}", text)?;
+        } else {
+            for chunk in text.as_bytes().chunks(self.line_width) {
+                self.writer.write_all(chunk)?;
+                writeln!(self.writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), FormatError> {
+        Ok(self.writer.flush()?)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{FastaReader, FastaWriter};
+    use sequence::nucleotide::Nucleotide;
+    use sequence::amino_acid::AminoAcid;
+    use sequence::error::SequenceError;
+    use io::error::FormatError;
+
+    #[test]
+    fn read_and_write() {
+        let input = ">seq1 a test\nACGT\nAC\n\n>seq2\nGGG\n";
+        let records: Vec<_> = FastaReader::<_, Nucleotide>::new(Cursor::new(input))
+            .map(|r| r.unwrap()).collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id, "seq1");
+        assert_eq!(records[0].description, Some(String::from("a test")));
+        assert_eq!(records[0].sequence.to_string(), "ACGTAC");
+        assert_eq!(records[1].sequence.to_string(), "GGG");
+        let mut writer = FastaWriter::new(Vec::new()).with_line_width(4);
+        for r in records.iter() {
+            writer.write(r).unwrap();
+        }
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(),
+                   ">seq1 a test\nACGT\nAC\n>seq2\nGGG\n");
+    }
+
+    #[test]
+    fn protein() {
+        let input = ">p\nMKV*\n";
+        let record = FastaReader::<_, AminoAcid>::new(Cursor::new(input)).next().unwrap().unwrap();
+        assert_eq!(record.sequence.to_string(), "MKV*");
+    }
+
+    #[test]
+    fn bad_input() {
+        let mut reader = FastaReader::<_, Nucleotide>::new(Cursor::new(">a\nACGT\nACXT\n"));
+        match reader.next() {
+            Some(Err(FormatError::InvalidSequence { line, error })) => {
+                assert_eq!(line, 3);
+                assert_eq!(error, SequenceError::InvalidSymbol { symbol: 'X', offset: 2 });
+            },
+            other => panic!("expected an invalid base, got {:?}", other),
+        }
+        let mut reader = FastaReader::<_, Nucleotide>::new(Cursor::new("ACGT\n"));
+        assert!(reader.next().unwrap().is_err());
+    }
+}
@@ -0,0 +1,395 @@
+use std::io::{BufRead, Write};
+use std::marker::PhantomData;
+
+use sequence::sequence::Sequence;
+use sequence::string_io::StringIO;
+use sequence::storage::Stored;
+use sequence::nucleotide::Nucleotide;
+use io::error::FormatError;
+
+/// How Phred quality scores are stored as characters.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum QualityEncoding {
+    /// Sanger and Illumina 1.8+: scores offset by 33 (`!`).
+    Phred33,
+    /// Illumina 1.3 to 1.7: scores offset by 64 (`@`).
+    Phred64,
+}
+
+impl QualityEncoding {
+    #[allow(clippy::match_ref_pats)]
+    fn offset(&self) -> u8 {
+        match self {
+            &QualityEncoding::Phred33 => 33,
+            &QualityEncoding::Phred64 => 64,
+        }
+    }
+    pub fn decode(&self, ch: u8) -> Option<u8> {
+        if ch >= self.offset() && ch <= b'~' { Some(ch - self.offset()) } else { None }
+    }
+    pub fn encode(&self, quality: u8) -> u8 {
+        quality.saturating_add(self.offset()).min(b'~')
+    }
+}
+
+/// A sequencing read with one Phred quality score per base.
+///
+/// Reads are generic over their alphabet: read as `Nucleotide`, any `N` call
+/// is an error, while `DegenerateNucleotide` keeps them.
+#[derive(Clone, Debug)]
+pub struct FastqRecord<N> where N: Stored {
+    pub id: String,
+    pub description: Option<String>,
+    pub sequence: Sequence<N>,
+    pub quality: Vec<u8>,
+}
+
+/// Summary statistics for a single read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadStats {
+    pub length: usize,
+    pub mean_quality: f64,
+    pub min_quality: u8,
+    /// Fraction of unambiguous bases that are G or C.
+    pub gc_content: f64,
+    /// Number of bases called as anything other than A, C, G or T.
+    pub ambiguous: usize,
+    /// Expected number of miscalled bases, summed from each base's Phred score.
+    pub expected_errors: f64,
+}
+
+impl<N> FastqRecord<N> where N: StringIO<N=N> + Clone {
+    /// Fail unless there is exactly one quality score per base, as the
+    /// fields are public and may have been set apart.
+    fn check_lengths(&self) -> Result<(), FormatError> {
+        if self.quality.len() == self.sequence.len() {
+            Ok(())
+        } else {
+            Err(FormatError::Malformed(format!("read {}: {} quality scores for {} bases",
+                                               self.id, self.quality.len(),
+                                               self.sequence.len())))
+        }
+    }
+
+    fn slice(&self, start: usize, end: usize) -> FastqRecord<N> {
+        FastqRecord {
+            id: self.id.clone(),
+            description: self.description.clone(),
+            sequence: self.sequence.subsequence(start, end).unwrap(),
+            quality: self.quality[start..end].to_vec(),
+        }
+    }
+
+    /// Trim low-quality ends with the BWA/cutadapt algorithm: cut the end at
+    /// the point that maximises the summed shortfall below `threshold`.
+    /// A threshold of zero leaves that end untouched. Fails if the read does
+    /// not have one quality score per base.
+    pub fn trim_quality(&self, five_prime: u8, three_prime: u8)
+                        -> Result<FastqRecord<N>, FormatError> {
+        self.check_lengths()?;
+        let score = |q: u8, threshold: u8| threshold as i32 - q as i32;
+        let mut start = 0;
+        if five_prime > 0 {
+            let (mut sum, mut best) = (0, 0);
+            for (i, &q) in self.quality.iter().enumerate() {
+                sum += score(q, five_prime);
+                if sum < 0 { break; }
+                if sum > best { best = sum; start = i + 1; }
+            }
+        }
+        let mut end = self.quality.len();
+        if three_prime > 0 {
+            let (mut sum, mut best) = (0, 0);
+            for (i, &q) in self.quality.iter().enumerate().rev() {
+                if i < start { break; }
+                sum += score(q, three_prime);
+                if sum < 0 { break; }
+                if sum > best { best = sum; end = i; }
+            }
+        }
+        Ok(self.slice(start, end.max(start)))
+    }
+
+    /// Remove the first adapter occurrence and everything after it.
+    ///
+    /// An adapter matches wherever it aligns without gaps to the read with at
+    /// most `max_error_rate` mismatches, including a partial match of at
+    /// least `min_overlap` bases running off the 3' end. Ambiguous bases in
+    /// the read count as mismatches. Like `trim_quality`, fails on a read
+    /// whose quality scores and bases differ in number.
+    pub fn trim_adapters(&self, adapters: &[Sequence<Nucleotide>], min_overlap: usize,
+                         max_error_rate: f64) -> Result<FastqRecord<N>, FormatError> {
+        self.check_lengths()?;
+        let read: Vec<char> = self.sequence.iter().map(|nt| nt.to_char()).collect();
+        let min_overlap = min_overlap.max(1);
+        for start in 0..read.len() {
+            let remaining = read.len() - start;
+            for adapter in adapters.iter() {
+                let overlap = adapter.len().min(remaining);
+                if overlap < min_overlap.min(adapter.len()) || overlap == 0 {
+                    continue;
+                }
+                let mismatches = read[start..start+overlap].iter().zip(adapter.iter())
+                    .filter(|&(&r, a)| r != a.to_char())
+                    .count();
+                if mismatches as f64 <= max_error_rate * overlap as f64 {
+                    return Ok(self.slice(0, start));
+                }
+            }
+        }
+        Ok(self.clone())
+    }
+
+    pub fn stats(&self) -> ReadStats {
+        let (mut gc, mut ambiguous) = (0, 0);
+        for nt in self.sequence.iter() {
+            match nt.to_char() {
+                'G' | 'C' => gc += 1,
+                'A' | 'T' => (),
+                _ => ambiguous += 1,
+            }
+        }
+        let length = self.sequence.len();
+        let unambiguous = length - ambiguous;
+        let total: u64 = self.quality.iter().map(|&q| q as u64).sum();
+        ReadStats {
+            length,
+            mean_quality: if length > 0 { total as f64 / length as f64 } else { 0.0 },
+            min_quality: self.quality.iter().cloned().min().unwrap_or(0),
+            gc_content: if unambiguous > 0 { gc as f64 / unambiguous as f64 } else { 0.0 },
+            ambiguous,
+            expected_errors: self.quality.iter()
+                .map(|&q| 10f64.powf(-(q as f64) / 10.0))
+                .sum(),
+        }
+    }
+}
+
+/// Streaming FASTQ parser yielding one record at a time.
+pub struct FastqReader<R, N> {
+    reader: R,
+    encoding: QualityEncoding,
+    line: usize,
+    phantom: PhantomData<N>,
+}
+
+impl<R, N> FastqReader<R, N> where R: BufRead, N: StringIO<N=N> + Clone {
+    pub fn new(reader: R, encoding: QualityEncoding) -> FastqReader<R, N> {
+        FastqReader { reader, encoding, line: 0, phantom: PhantomData }
+    }
+
+    fn next_line(&mut self) -> Result<Option<String>, FormatError> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        self.line += 1;
+        while line.ends_with('\n') || line.ends_with('\r') {
+            line.pop();
+        }
+        Ok(Some(line))
+    }
+
+    fn malformed(&self, message: &str) -> FormatError {
+        FormatError::Malformed(format!("line {}: {}", self.line, message))
+    }
+
+    fn read_record(&mut self) -> Result<Option<FastqRecord<N>>, FormatError> {
+        let header = loop {
+            match self.next_line()? {
+                None => return Ok(None),
+                Some(ref line) if line.is_empty() => continue,
+                Some(line) => break line,
+            }
+        };
+        if !header.starts_with('@') {
+            return Err(self.malformed("expected a header starting with @"));
+        }
+        let (id, description) = match header[1..].find(char::is_whitespace) {
+            Some(space) => (String::from(&header[1..space+1]),
+                            Some(String::from(header[space+1..].trim()))),
+            None => (String::from(&header[1..]), None),
+        };
+        let bases = match self.next_line()? {
+            Some(line) => line,
+            None => return Err(self.malformed("missing sequence line")),
+        };
+        let mut sequence = Sequence::<N>::new();
+        for (offset, ch) in bases.chars().enumerate() {
+            match N::from_char(ch) {
+                Ok(nt) => sequence.push(nt),
+                Err(e) => return Err(FormatError::InvalidSequence { line: self.line,
+                                                                     error: e.offset_by(offset) }),
+            }
+        }
+        match self.next_line()? {
+            Some(ref line) if line.starts_with('+') => (),
+            _ => return Err(self.malformed("expected a separator line starting with +")),
+        }
+        let quals = match self.next_line()? {
+            Some(line) => line,
+            None => return Err(self.malformed("missing quality line")),
+        };
+        if quals.len() != sequence.len() {
+            return Err(self.malformed("quality and sequence lengths differ"));
+        }
+        let mut quality = Vec::with_capacity(quals.len());
+        for &ch in quals.as_bytes().iter() {
+            match self.encoding.decode(ch) {
+                Some(q) => quality.push(q),
+                None => return Err(self.malformed(
+                    &format!("quality character {:?} out of range", ch as char))),
+            }
+        }
+        Ok(Some(FastqRecord { id, description, sequence, quality }))
+    }
+}
+
+impl<R, N> Iterator for FastqReader<R, N> where R: BufRead, N: StringIO<N=N> + Clone {
+    type Item = Result<FastqRecord<N>, FormatError>;
+    fn next(&mut self) -> Option<Result<FastqRecord<N>, FormatError>> {
+        match self.read_record() {
+            Ok(Some(record)) => Some(Ok(record)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+pub struct FastqWriter<W> {
+    writer: W,
+    encoding: QualityEncoding,
+}
+
+impl<W> FastqWriter<W> where W: Write {
+    pub fn new(writer: W, encoding: QualityEncoding) -> FastqWriter<W> {
+        FastqWriter { writer, encoding }
+    }
+
+    pub fn write<N>(&mut self, record: &FastqRecord<N>) -> Result<(), FormatError>
+        where N: StringIO<N=N> + Clone {
+        match record.description {
+            Some(ref description) => writeln!(self.writer, "@{} {}", record.id, description)?,
+            None => writeln!(self.writer, "@{}", record.id)?,
+        }
+        writeln!(self.writer, "{}", record.sequence.to_string())?;
+        writeln!(self.writer, "+")?;
+        let quals: Vec<u8> = record.quality.iter().map(|&q| self.encoding.encode(q)).collect();
+        self.writer.write_all(&quals)?;
+        writeln!(self.writer)?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{FastqReader, FastqWriter, FastqRecord, QualityEncoding};
+    use sequence::nucleotide::Nucleotide;
+    use sequence::degenerate_nucleotide::DegenerateNucleotide;
+    use sequence::sequence::Sequence;
+    use sequence::error::SequenceError;
+    use io::error::FormatError;
+
+    const FASTQ: &str = "@read1 sample=A\nACGTNACG\n+\nIIII!III\n@read2\nGGCC\n+read2\n##II\n";
+
+    fn record(seq: &str, quality: &[u8]) -> FastqRecord<Nucleotide> {
+        FastqRecord { id: String::from("r"), description: None,
+                      sequence: Sequence::<Nucleotide>::from_str(seq).unwrap(),
+                      quality: quality.to_vec() }
+    }
+
+    #[test]
+    fn parse_and_write() {
+        let reader = FastqReader::<_, DegenerateNucleotide>::new(Cursor::new(FASTQ),
+                                                                QualityEncoding::Phred33);
+        let records: Vec<_> = reader.map(|r| r.unwrap()).collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id, "read1");
+        assert_eq!(records[0].description, Some(String::from("sample=A")));
+        assert_eq!(records[0].sequence.to_string(), "ACGTNACG");
+        assert_eq!(records[0].quality, vec![40, 40, 40, 40, 0, 40, 40, 40]);
+        let mut writer = FastqWriter::new(Vec::new(), QualityEncoding::Phred33);
+        for r in records.iter() {
+            writer.write(r).unwrap();
+        }
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(),
+                   FASTQ.replace("+read2", "+"));
+    }
+
+    #[test]
+    fn ambiguous_calls() {
+        let mut reader = FastqReader::<_, Nucleotide>::new(Cursor::new(FASTQ),
+                                                          QualityEncoding::Phred33);
+        match reader.next() {
+            Some(Err(FormatError::InvalidSequence { line, error })) => {
+                assert_eq!(line, 2);
+                assert_eq!(error, SequenceError::InvalidSymbol { symbol: 'N', offset: 4 });
+            },
+            other => panic!("expected an invalid base, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn phred64() {
+        let input = "@r\nACG\n+\nhB@\n";
+        let mut reader = FastqReader::<_, Nucleotide>::new(Cursor::new(input),
+                                                          QualityEncoding::Phred64);
+        assert_eq!(reader.next().unwrap().unwrap().quality, vec![40, 2, 0]);
+        let mut reader = FastqReader::<_, Nucleotide>::new(Cursor::new("@r\nA\n+\n!\n"),
+                                                          QualityEncoding::Phred64);
+        assert!(reader.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn trim_quality() {
+        let read = record("ACGTACGTAC", &[5, 30, 30, 30, 30, 30, 30, 10, 2, 2]);
+        assert_eq!(read.trim_quality(0, 20).unwrap().sequence.to_string(), "ACGTACG");
+        assert_eq!(read.trim_quality(20, 20).unwrap().sequence.to_string(), "CGTACG");
+        assert_eq!(read.trim_quality(0, 0).unwrap().sequence.to_string(), "ACGTACGTAC");
+        let bad = record("ACG", &[2, 2, 2]);
+        assert_eq!(bad.trim_quality(20, 20).unwrap().sequence.len(), 0);
+        let mismatched = record("ACGT", &[30, 30]);
+        match mismatched.trim_quality(20, 20) {
+            Err(FormatError::Malformed(message)) =>
+                assert_eq!(message, "read r: 2 quality scores for 4 bases"),
+            other => panic!("expected a length error, got {:?}", other),
+        }
+        assert!(mismatched.trim_adapters(&[], 3, 0.1).is_err());
+    }
+
+    #[test]
+    fn trim_adapters() {
+        let adapters = vec![Sequence::<Nucleotide>::from_str("AGATCGGAAGAGC").unwrap()];
+        let read = record("GATTACAAGATCGGAAGAGCACAC", &[30; 24]);
+        assert_eq!(read.trim_adapters(&adapters, 3, 0.1).unwrap().sequence.to_string(),
+                   "GATTACA");
+        // A partial adapter at the 3' end, with one mismatch.
+        let read = record("GATTACAGATTACAAGTTCG", &[30; 20]);
+        assert_eq!(read.trim_adapters(&adapters, 3, 0.2).unwrap().sequence.to_string(),
+                   "GATTACAGATTACA");
+        let read = record("GATTACAGATTACA", &[30; 14]);
+        assert_eq!(read.trim_adapters(&adapters, 3, 0.1).unwrap().sequence.to_string(),
+                   "GATTACAGATTACA");
+    }
+
+    #[test]
+    fn stats() {
+        let read = FastqRecord::<DegenerateNucleotide> {
+            id: String::from("r"), description: None,
+            sequence: Sequence::<DegenerateNucleotide>::from_str("GGCANT").unwrap(),
+            quality: vec![10, 20, 30, 40, 0, 20] };
+        let stats = read.stats();
+        assert_eq!(stats.length, 6);
+        assert_eq!(stats.ambiguous, 1);
+        assert_eq!(stats.min_quality, 0);
+        assert!((stats.mean_quality - 20.0).abs() < 1e-9);
+        assert!((stats.gc_content - 0.6).abs() < 1e-9);
+        assert!((stats.expected_errors - 1.1211).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,389 @@
+use std::io::{BufRead, Write};
+use std::marker::PhantomData;
+
+use sequence::sequence::Sequence;
+use sequence::nucleotide::Nucleotide;
+use sequence::string_io::StringIO;
+use sequence::strand::Strand;
+use sequence::feature::{AnnotatedSequence, Feature, FeatureKind, Topology};
+use io::error::FormatError;
+
+/// Column where feature locations and qualifiers start.
+const QUALIFIER_INDENT: usize = 21;
+
+/// Streaming GenBank parser yielding one annotated sequence per record.
+///
+/// Only the LOCUS line, feature table and ORIGIN section are read; other
+/// header fields are skipped. The `source` feature describes the whole
+/// record and is dropped. Feature labels come from the first of the
+/// `/label`, `/gene`, `/product` or `/note` qualifiers present, falling
+/// back to the feature key. Locations joining pieces are read as their
+/// overall span, which on a circular record may cross the origin.
+///
+/// Sequences are read in the alphabet `N`: as `Nucleotide`, any `N` or
+/// other ambiguity code in ORIGIN is an error, while `DegenerateNucleotide`
+/// keeps them.
+pub struct GenbankReader<R, N = Nucleotide> {
+    reader: R,
+    line: usize,
+    phantom: PhantomData<N>,
+}
+
+/// A feature whose location and qualifiers are still being read.
+struct PendingFeature {
+    key: String,
+    location: String,
+    qualifiers: Vec<(String, String)>,
+    line: usize,
+}
+
+impl<R, N> GenbankReader<R, N> where R: BufRead, N: StringIO<N=N> + Clone {
+    pub fn new(reader: R) -> GenbankReader<R, N> {
+        GenbankReader { reader, line: 0, phantom: PhantomData }
+    }
+
+    fn next_line(&mut self) -> Result<Option<String>, FormatError> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        self.line += 1;
+        while line.ends_with('\n') || line.ends_with('\r') {
+            line.pop();
+        }
+        Ok(Some(line))
+    }
+
+    fn malformed(&self, message: &str) -> FormatError {
+        FormatError::Malformed(format!("line {}: {}", self.line, message))
+    }
+
+    fn read_record(&mut self) -> Result<Option<AnnotatedSequence<N>>, FormatError> {
+        let locus = loop {
+            match self.next_line()? {
+                None => return Ok(None),
+                Some(ref line) if line.trim().is_empty() => continue,
+                Some(line) => {
+                    if !line.starts_with("LOCUS") {
+                        return Err(self.malformed("expected a LOCUS line"));
+                    }
+                    break line;
+                },
+            }
+        };
+        let fields: Vec<&str> = locus.split_whitespace().collect();
+        let name = fields.get(1).cloned().unwrap_or("");
+        let topology = if fields.iter().any(|f| f.eq_ignore_ascii_case("circular")) {
+            Topology::Circular
+        } else {
+            Topology::Linear
+        };
+
+        let mut pending: Vec<PendingFeature> = Vec::new();
+        let mut sequence = Sequence::<N>::new();
+        let mut section = "";
+        loop {
+            let line = match self.next_line()? {
+                Some(line) => line,
+                None => return Err(self.malformed("record ends without //")),
+            };
+            if line.starts_with("//") {
+                break;
+            }
+            if !line.starts_with(' ') && !line.is_empty() {
+                section = if line.starts_with("FEATURES") { "features" }
+                          else if line.starts_with("ORIGIN") { "origin" }
+                          else { "header" };
+                continue;
+            }
+            match section {
+                "features" => self.feature_line(&line, &mut pending)?,
+                "origin" => {
+                    for (column, ch) in line.char_indices() {
+                        if ch.is_ascii_digit() || ch.is_whitespace() {
+                            continue;
+                        }
+                        match N::from_char(ch) {
+                            Ok(nt) => sequence.push(nt),
+                            Err(e) => return Err(FormatError::InvalidSequence {
+                                line: self.line, error: e.offset_by(column) }),
+                        }
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        let mut record = AnnotatedSequence::new(name, sequence, topology);
+        for feature in pending.into_iter().filter(|f| f.key != "source") {
+            record.features.push(feature.into_feature(record.sequence.len(), topology)?);
+        }
+        Ok(Some(record))
+    }
+
+    fn feature_line(&self, line: &str, pending: &mut Vec<PendingFeature>)
+                    -> Result<(), FormatError> {
+        let indent = line.len() - line.trim_start().len();
+        let text = line.trim();
+        if indent < QUALIFIER_INDENT {
+            // A new feature: its key, then the start of its location.
+            let mut parts = text.splitn(2, char::is_whitespace);
+            let key = parts.next().unwrap_or("");
+            let location = parts.next().unwrap_or("").trim();
+            pending.push(PendingFeature { key: String::from(key), location: String::from(location),
+                                          qualifiers: Vec::new(), line: self.line });
+            return Ok(());
+        }
+        let feature = match pending.last_mut() {
+            Some(feature) => feature,
+            None => return Err(self.malformed("qualifier outside a feature")),
+        };
+        if let Some(qualifier) = text.strip_prefix('/') {
+            let mut parts = qualifier.splitn(2, '=');
+            let key = String::from(parts.next().unwrap_or(""));
+            let value = String::from(parts.next().unwrap_or(""));
+            feature.qualifiers.push((key, value));
+        } else if let Some(last) = feature.qualifiers.last_mut() {
+            // A continuation of a long value; protein translations are
+            // wrapped without spaces.
+            if last.0 != "translation" {
+                last.1.push(' ');
+            }
+            last.1.push_str(text);
+        } else {
+            feature.location.push_str(text);
+        }
+        Ok(())
+    }
+}
+
+impl PendingFeature {
+    fn into_feature(self, length: usize, topology: Topology) -> Result<Feature, FormatError> {
+        let PendingFeature { key, location, qualifiers, line } = self;
+        let malformed = || FormatError::Malformed(
+            format!("line {}: cannot parse location {}", line, location));
+        let strand = if location.contains("complement") { Strand::Reverse }
+                     else { Strand::Forward };
+        let cleaned: String = location.chars()
+            .map(|ch| if ch.is_ascii_digit() || ch == '.' || ch == '^' { ch } else { ',' })
+            .collect();
+        let mut ranges = Vec::new();
+        for piece in cleaned.split(',').filter(|p| !p.is_empty()) {
+            let mut bounds = piece.split(['.', '^']).filter(|b| !b.is_empty());
+            let first: usize = bounds.next().and_then(|b| b.parse().ok()).ok_or_else(malformed)?;
+            let last: usize = match bounds.next() {
+                Some(b) => b.parse().map_err(|_| malformed())?,
+                None => first,
+            };
+            if first == 0 || last < first || last > length {
+                return Err(malformed());
+            }
+            ranges.push((first - 1, last));
+        }
+        if ranges.is_empty() {
+            return Err(malformed());
+        }
+        // A circular feature crossing the origin is written as pieces
+        // touching both ends; it then covers everything but the largest gap
+        // between its pieces.
+        ranges.sort();
+        let wraps = topology == Topology::Circular && ranges.len() > 1
+            && ranges[0].0 == 0 && ranges.iter().any(|r| r.1 == length);
+        let (start, end) = match (1..ranges.len()).max_by_key(|&i| ranges[i].0 as isize
+                                                                    - ranges[i-1].1 as isize) {
+            Some(i) if wraps => (ranges[i].0, ranges[i-1].1 + length),
+            _ => (ranges[0].0, ranges.iter().map(|r| r.1).max().unwrap()),
+        };
+
+        let mut qualifiers: Vec<(String, String)> = qualifiers.into_iter()
+            .map(|(key, value)| (key, String::from(value.trim_matches('"'))))
+            .collect();
+        let label = match qualifiers.iter().position(|q| q.0 == "label") {
+            Some(i) => qualifiers.remove(i).1,
+            None => ["gene", "product", "note"].iter()
+                .filter_map(|key| qualifiers.iter().find(|q| q.0 == *key))
+                .map(|q| q.1.clone())
+                .next()
+                .unwrap_or_else(|| key.clone()),
+        };
+        let mut feature = Feature::new(FeatureKind::from_key(&key), &label, start, end, strand);
+        feature.qualifiers = qualifiers;
+        Ok(feature)
+    }
+}
+
+impl<R, N> Iterator for GenbankReader<R, N> where R: BufRead, N: StringIO<N=N> + Clone {
+    type Item = Result<AnnotatedSequence<N>, FormatError>;
+    fn next(&mut self) -> Option<Result<AnnotatedSequence<N>, FormatError>> {
+        match self.read_record() {
+            Ok(Some(record)) => Some(Ok(record)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+pub struct GenbankWriter<W> {
+    writer: W,
+}
+
+impl<W> GenbankWriter<W> where W: Write {
+    pub fn new(writer: W) -> GenbankWriter<W> {
+        GenbankWriter { writer }
+    }
+
+    pub fn write<N>(&mut self, record: &AnnotatedSequence<N>) -> Result<(), FormatError>
+        where N: StringIO<N=N> + Clone {
+        let length = record.sequence.len();
+        let name: String = record.name.chars()
+            .map(|ch| if ch.is_whitespace() { '_' } else { ch }).collect();
+        let topology = match record.topology {
+            Topology::Linear => "linear",
+            Topology::Circular => "circular",
+        };
+        writeln!(self.writer, "LOCUS       {:<16} {:>11} bp    DNA     {}", name, length,
+                 topology)?;
+        writeln!(self.writer, "FEATURES             Location/Qualifiers")?;
+        for feature in record.features.iter() {
+            writeln!(self.writer, "     {:<16}{}", feature.kind.to_key(), location(feature, length))?;
+            // Only write a label the reader would not derive by itself.
+            let implied = ["gene", "product", "note"].iter()
+                .filter_map(|key| feature.qualifier(key)).next()
+                .unwrap_or_else(|| feature.kind.to_key());
+            if implied != feature.label {
+                self.qualifier("label", &feature.label)?;
+            }
+            for (key, value) in feature.qualifiers.iter() {
+                self.qualifier(key, value)?;
+            }
+        }
+        writeln!(self.writer, "ORIGIN")?;
+        let text = record.sequence.to_string().to_lowercase();
+        for (i, line) in text.as_bytes().chunks(60).enumerate() {
+            write!(self.writer, "{:>9}", i * 60 + 1)?;
+            for block in line.chunks(10) {
+                self.writer.write_all(b" ")?;
+                self.writer.write_all(block)?;
+            }
+            writeln!(self.writer)?;
+        }
+        writeln!(self.writer, "//")?;
+        Ok(())
+    }
+
+    /// Write a qualifier, wrapping long values onto continuation lines.
+    fn qualifier(&mut self, key: &str, value: &str) -> Result<(), FormatError> {
+        let text = format!("/{}=\"{}\"", key, value.replace('"', "'"));
+        let chars: Vec<char> = text.chars().collect();
+        for chunk in chars.chunks(79 - QUALIFIER_INDENT) {
+            writeln!(self.writer, "{:indent$}{}", "", chunk.iter().collect::<String>(),
+                     indent = QUALIFIER_INDENT)?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), FormatError> {
+        Ok(self.writer.flush()?)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// A feature's location in GenBank's one-based notation.
+fn location(feature: &Feature, length: usize) -> String {
+    let range = |start: usize, end: usize| {
+        if end == start + 1 { format!("{}", end) } else { format!("{}..{}", start + 1, end) }
+    };
+    let span = if feature.end > length {
+        format!("join({},{})", range(feature.start, length), range(0, feature.end - length))
+    } else {
+        range(feature.start, feature.end)
+    };
+    match feature.strand {
+        Strand::Forward => span,
+        Strand::Reverse => format!("complement({})", span),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{GenbankReader, GenbankWriter};
+    use sequence::nucleotide::Nucleotide;
+    use sequence::degenerate_nucleotide::DegenerateNucleotide;
+    use sequence::strand::Strand;
+    use sequence::feature::{FeatureKind, Topology};
+
+    const RECORD: &str = "\
+LOCUS       pTiny                     40 bp    DNA     circular SYN 01-JAN-2020
+DEFINITION  A tiny plasmid.
+FEATURES             Location/Qualifiers
+     source          1..40
+                     /organism=\"synthetic DNA construct\"
+     CDS             complement(4..12)
+                     /gene=\"tinA\"
+                     /note=\"a rather long note that wraps
+                     onto a second line\"
+     misc_feature    join(36..40,1..3)
+                     /label=wrap
+ORIGIN
+        1 atgcatgcat gcatgcatgc atgcatgcat gcatgcatgc
+//
+";
+
+    #[test]
+    fn read() {
+        let records: Vec<_> = GenbankReader::<_, Nucleotide>::new(Cursor::new(RECORD))
+            .map(|r| r.unwrap()).collect();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!((&record.name[..], record.topology), ("pTiny", Topology::Circular));
+        assert_eq!(record.sequence.len(), 40);
+        assert_eq!(record.features.len(), 2);
+        let cds = &record.features[0];
+        assert_eq!((cds.kind.clone(), &cds.label[..]), (FeatureKind::Cds, "tinA"));
+        assert_eq!((cds.start, cds.end, cds.strand), (3, 12, Strand::Reverse));
+        assert_eq!(cds.qualifier("note"), Some("a rather long note that wraps onto a second line"));
+        let wrap = &record.features[1];
+        assert_eq!((&wrap.label[..], wrap.start, wrap.end), ("wrap", 35, 43));
+    }
+
+    #[test]
+    fn round_trip() {
+        let record = GenbankReader::<_, Nucleotide>::new(Cursor::new(RECORD))
+            .next().unwrap().unwrap();
+        let mut writer = GenbankWriter::new(Vec::new());
+        writer.write(&record).unwrap();
+        let text = String::from_utf8(writer.into_inner()).unwrap();
+        assert!(text.contains("     misc_feature    join(36..40,1..3)\n"));
+        let again = GenbankReader::<_, Nucleotide>::new(Cursor::new(text))
+            .next().unwrap().unwrap();
+        assert_eq!(again, record);
+    }
+
+    #[test]
+    fn bad_input() {
+        let fasta = Cursor::new(">fasta\nACGT\n");
+        assert!(GenbankReader::<_, Nucleotide>::new(fasta).next().unwrap().is_err());
+        let truncated = &RECORD[..RECORD.len() - 3];
+        let truncated = Cursor::new(truncated);
+        assert!(GenbankReader::<_, Nucleotide>::new(truncated).next().unwrap().is_err());
+        let bad = RECORD.replace("1..40", "1..400").replace("4..12", "12..4");
+        assert!(GenbankReader::<_, Nucleotide>::new(Cursor::new(bad)).next().unwrap().is_err());
+    }
+
+    #[test]
+    fn ambiguous_bases() {
+        let gapped = RECORD.replacen("atgcatgcat gcatgcatgc", "atgcatgcat gcannnnngc", 1);
+        let error = GenbankReader::<_, Nucleotide>::new(Cursor::new(&gapped[..])).next().unwrap()
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Line 13: Invalid symbol 'n' at position 25");
+        let record = GenbankReader::<_, DegenerateNucleotide>::new(Cursor::new(&gapped[..]))
+            .next().unwrap().unwrap();
+        assert_eq!(record.sequence.to_string(), "ATGCATGCATGCANNNNNGCATGCATGCATGCATGCATGC");
+        assert_eq!(record.features.len(), 2);
+    }
+}
@@ -0,0 +1,7 @@
+pub mod error;
+pub mod twobit;
+pub mod fai;
+pub mod fastq;
+pub mod fasta;
+pub mod genbank;
+pub mod sbol;
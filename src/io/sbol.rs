@@ -0,0 +1,1596 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+use sequence::sequence::Sequence;
+use sequence::degenerate_nucleotide::DegenerateNucleotide;
+use sequence::string_io::StringIO;
+use sequence::strand::Strand;
+use sequence::feature::{AnnotatedSequence, Feature, FeatureKind, Topology};
+use design::{Element, Plasmid};
+use io::error::FormatError;
+
+const SBOL: &str = "http://sbols.org/v3#";
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const XML: &str = "http://www.w3.org/XML/1998/namespace";
+const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+const SO: &str = "https://identifiers.org/SO:";
+const SBO: &str = "https://identifiers.org/SBO:";
+const EDAM: &str = "https://identifiers.org/edam:";
+
+/// The SBO term for DNA, the only component type read.
+const DNA: &str = "SBO:0000251";
+const LINEAR: &str = "SO:0000987";
+const CIRCULAR: &str = "SO:0000988";
+const PLASMID: &str = "SO:0000155";
+const ENGINEERED_REGION: &str = "SO:0000804";
+/// The EDAM format for IUPAC nucleic acid sequences.
+const IUPAC_DNA: &str = "format_1207";
+
+/// The Sequence Ontology role for a feature kind. Kinds with no term of
+/// their own are written as the generic `sequence_feature`.
+fn role(kind: &FeatureKind) -> &'static str {
+    match *kind {
+        FeatureKind::Cds => "SO:0000316",
+        FeatureKind::Promoter => "SO:0000167",
+        FeatureKind::Rbs => "SO:0000139",
+        FeatureKind::Terminator => "SO:0000141",
+        FeatureKind::Origin => "SO:0000296",
+        FeatureKind::PrimerBind => "SO:0005850",
+        FeatureKind::ProteinBind => "SO:0000410",
+        FeatureKind::MiscFeature | FeatureKind::Other(_) => "SO:0000110",
+    }
+}
+
+fn kind(roles: &[String]) -> FeatureKind {
+    roles.iter().filter_map(|role| match &role[..] {
+        "SO:0000316" => Some(FeatureKind::Cds),
+        "SO:0000167" => Some(FeatureKind::Promoter),
+        "SO:0000139" => Some(FeatureKind::Rbs),
+        "SO:0000141" => Some(FeatureKind::Terminator),
+        "SO:0000296" => Some(FeatureKind::Origin),
+        "SO:0005850" => Some(FeatureKind::PrimerBind),
+        "SO:0000410" => Some(FeatureKind::ProteinBind),
+        _ => None,
+    }).next().unwrap_or(FeatureKind::MiscFeature)
+}
+
+/// A DNA component of an SBOL3 document: a sequence with its features,
+/// made in part from other components.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Component {
+    /// The identifier the component's IRI ends in, made only of letters,
+    /// digits and underscores.
+    pub display_id: String,
+    pub name: Option<String>,
+    /// Sequence Ontology terms such as `SO:0000167`.
+    pub roles: Vec<String>,
+    pub topology: Topology,
+    /// Absent for a design made only of subcomponents. IUPAC elements may
+    /// use any ambiguity code.
+    pub sequence: Option<Sequence<DegenerateNucleotide>>,
+    pub features: Vec<Feature>,
+    pub subcomponents: Vec<SubComponent>,
+}
+
+/// A use of one component within another.
+#[derive(PartialEq, Clone, Debug)]
+pub struct SubComponent {
+    /// The display id of the component used.
+    pub instance_of: String,
+    /// Where it lies in the parent's sequence, zero-based and half-open like
+    /// a feature, if the parent has one.
+    pub location: Option<(usize, usize)>,
+    pub strand: Strand,
+}
+
+/// The components of an SBOL3 document, all in one namespace.
+#[derive(PartialEq, Clone, Debug)]
+pub struct SbolDocument {
+    /// The IRI prefix of every object, such as `https://example.org/lab`.
+    pub namespace: String,
+    pub components: Vec<Component>,
+}
+
+/// A display id made from a free-form name.
+fn display_id(name: &str) -> String {
+    let mut id: String = name.chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' }).collect();
+    if !id.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_') {
+        id.insert(0, '_');
+    }
+    id
+}
+
+/// A sequence in the IUPAC alphabet SBOL3 elements are written in.
+fn iupac<N>(sequence: &Sequence<N>) -> Sequence<DegenerateNucleotide>
+    where N: StringIO<N=N> + Copy + Into<DegenerateNucleotide> {
+    sequence.iter().map(|&nt| nt.into()).collect()
+}
+
+impl Component {
+    pub fn new(display_id: &str) -> Component {
+        Component { display_id: String::from(display_id), name: None, roles: Vec::new(),
+                    topology: Topology::Linear, sequence: None, features: Vec::new(),
+                    subcomponents: Vec::new() }
+    }
+
+    /// A component for an annotated sequence, named after it. Circular
+    /// sequences take the plasmid role.
+    pub fn from_record<N>(record: &AnnotatedSequence<N>) -> Component
+        where N: StringIO<N=N> + Copy + Into<DegenerateNucleotide> {
+        let mut component = Component::new(&display_id(&record.name));
+        component.name = Some(record.name.clone());
+        if record.topology == Topology::Circular {
+            component.roles.push(String::from(PLASMID));
+        }
+        component.topology = record.topology;
+        component.sequence = Some(iupac(&record.sequence));
+        component.features = record.features.clone();
+        component
+    }
+
+    fn label(&self) -> &str {
+        self.name.as_ref().unwrap_or(&self.display_id)
+    }
+}
+
+impl SbolDocument {
+    pub fn new(namespace: &str) -> SbolDocument {
+        SbolDocument { namespace: String::from(namespace.trim_end_matches('/')),
+                       components: Vec::new() }
+    }
+
+    /// A document with one flat component per record. Records whose names
+    /// map to the same display id are told apart by a numeric suffix.
+    pub fn from_records<N>(namespace: &str, records: &[AnnotatedSequence<N>]) -> SbolDocument
+        where N: StringIO<N=N> + Copy + Into<DegenerateNucleotide> {
+        let mut document = SbolDocument::new(namespace);
+        for record in records.iter() {
+            let mut component = Component::from_record(record);
+            let base = component.display_id.clone();
+            let mut n = 1;
+            while document.component(&component.display_id).is_some() {
+                n += 1;
+                component.display_id = format!("{}_{}", base, n);
+            }
+            document.components.push(component);
+        }
+        document
+    }
+
+    /// A document describing a plasmid hierarchically: the plasmid is made
+    /// of its elements, each transcription unit of its parts, and each part
+    /// is a component of its own. Parts that share a name are assumed to be
+    /// the same part.
+    pub fn from_plasmid(namespace: &str, plasmid: &Plasmid) -> SbolDocument {
+        let mut document = SbolDocument::new(namespace);
+        let record = plasmid.assemble();
+        let mut top = Component::from_record(&record);
+        top.features.clear();
+        let mut units = Vec::new();
+        let mut parts: Vec<Component> = Vec::new();
+        let mut offset = 0;
+        for &(ref element, strand) in plasmid.elements.iter() {
+            let element_parts = match *element {
+                Element::Part(ref part) => ::std::slice::from_ref(part),
+                Element::Unit(ref unit) => &unit.parts[..],
+            };
+            for part in element_parts.iter() {
+                let id = display_id(&part.name);
+                if !parts.iter().any(|c| c.display_id == id) {
+                    let mut component = Component::new(&id);
+                    component.name = Some(part.name.clone());
+                    component.roles.push(String::from(role(&part.kind.feature_kind())));
+                    component.sequence = Some(iupac(&part.sequence));
+                    parts.push(component);
+                }
+            }
+            let (id, length) = match *element {
+                Element::Part(ref part) => (display_id(&part.name), part.sequence.len()),
+                Element::Unit(ref unit) => {
+                    let mut component = Component::new(&display_id(&unit.name));
+                    component.name = Some(unit.name.clone());
+                    component.roles.push(String::from(ENGINEERED_REGION));
+                    let mut start = 0;
+                    for part in unit.parts.iter() {
+                        let end = start + part.sequence.len();
+                        component.subcomponents.push(SubComponent {
+                            instance_of: display_id(&part.name), location: Some((start, end)),
+                            strand: Strand::Forward });
+                        start = end;
+                    }
+                    component.sequence = Some(iupac(&unit.sequence()));
+                    let id = component.display_id.clone();
+                    units.push(component);
+                    (id, start)
+                },
+            };
+            top.subcomponents.push(SubComponent {
+                instance_of: id, location: Some((offset, offset + length)), strand });
+            offset += length;
+        }
+        document.components.push(top);
+        document.components.extend(units);
+        document.components.extend(parts);
+        document
+    }
+
+    pub fn component(&self, display_id: &str) -> Option<&Component> {
+        self.components.iter().find(|c| c.display_id == display_id)
+    }
+
+    /// Every component with a sequence as an annotated sequence. Its own
+    /// features are kept, and each subcomponent with a location becomes a
+    /// feature labelled with the name of the component it uses, followed by
+    /// that component's features and subcomponents in turn, placed within
+    /// it. A subcomponent's kind comes from the first role of its component
+    /// with a matching feature kind.
+    pub fn records(&self) -> Vec<AnnotatedSequence<DegenerateNucleotide>> {
+        self.components.iter().filter_map(|component| {
+            let sequence = component.sequence.clone()?;
+            let mut record = AnnotatedSequence::new(component.label(), sequence,
+                                                    component.topology);
+            let length = record.sequence.len();
+            let mut path = vec![&component.display_id[..]];
+            self.flatten(component, 0, length, Strand::Forward, &mut path, &mut record.features);
+            Some(record)
+        }).collect()
+    }
+
+    /// Add the contents of `component`, placed at `start..end` on `strand`.
+    /// Contents that do not fit in that span are left out. `path` holds the
+    /// components being expanded, so a cycle stops rather than recursing
+    /// forever.
+    fn flatten<'a>(&'a self, component: &'a Component, start: usize, end: usize, strand: Strand,
+                   path: &mut Vec<&'a str>, features: &mut Vec<Feature>) {
+        let place = |from: usize, to: usize, inner: Strand| match strand {
+            _ if to > end - start => None,
+            Strand::Forward => Some((start + from, start + to, inner)),
+            Strand::Reverse => Some((end - to, end - from, inner.opposite())),
+        };
+        for feature in component.features.iter() {
+            let (from, to, inner) = match place(feature.start, feature.end, feature.strand) {
+                Some(placed) => placed,
+                None => continue,
+            };
+            let mut placed = feature.clone();
+            placed.start = from;
+            placed.end = to;
+            placed.strand = inner;
+            features.push(placed);
+        }
+        for sub in component.subcomponents.iter() {
+            let (from, to) = match sub.location {
+                Some(location) => location,
+                None => continue,
+            };
+            let (from, to, inner) = match place(from, to, sub.strand) {
+                Some(placed) => placed,
+                None => continue,
+            };
+            let child = match self.component(&sub.instance_of) {
+                Some(child) => child,
+                None => {
+                    features.push(Feature::new(FeatureKind::MiscFeature, &sub.instance_of,
+                                               from, to, inner));
+                    continue;
+                },
+            };
+            features.push(Feature::new(kind(&child.roles), child.label(), from, to, inner));
+            // A child whose sequence does not match its span is not expanded.
+            let fits = child.sequence.as_ref().is_none_or(|seq| seq.len() == to - from);
+            if fits && !path.contains(&&child.display_id[..]) {
+                path.push(&child.display_id);
+                self.flatten(child, from, to, inner, path, features);
+                path.pop();
+            }
+        }
+    }
+}
+
+/// A node or literal in an RDF graph. Blank nodes are named `_:label`.
+#[derive(PartialEq, Clone, Debug)]
+enum Term {
+    Node(String),
+    Literal(String),
+}
+
+/// A parser for the Turtle serialisation of RDF, which includes
+/// N-Triples. Collections are not supported, and literals keep only their
+/// text, dropping any datatype or language tag.
+struct Turtle {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+    prefixes: HashMap<String, String>,
+    base: String,
+    blanks: usize,
+    triples: Vec<(String, String, Term)>,
+}
+
+impl Turtle {
+    fn parse(text: &str) -> Result<Vec<(String, String, Term)>, FormatError> {
+        let mut parser = Turtle { chars: text.chars().collect(), position: 0, line: 1,
+                                  prefixes: HashMap::new(), base: String::new(), blanks: 0,
+                                  triples: Vec::new() };
+        loop {
+            parser.skip_space();
+            if parser.peek().is_none() {
+                return Ok(parser.triples);
+            }
+            parser.statement()?;
+        }
+    }
+
+    fn malformed(&self, message: &str) -> FormatError {
+        FormatError::Malformed(format!("line {}: {}", self.line, message))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).cloned()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.position += 1;
+        if ch == '\n' {
+            self.line += 1;
+        }
+        Some(ch)
+    }
+
+    fn skip_space(&mut self) {
+        while let Some(ch) = self.peek() {
+            if ch == '#' {
+                while self.peek().is_some_and(|ch| ch != '\n') {
+                    self.next_char();
+                }
+            } else if ch.is_whitespace() {
+                self.next_char();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), FormatError> {
+        self.skip_space();
+        match self.next_char() {
+            Some(ch) if ch == expected => Ok(()),
+            Some(ch) => Err(self.malformed(&format!("expected '{}', found '{}'", expected, ch))),
+            None => Err(self.malformed(&format!("expected '{}' at end of file", expected))),
+        }
+    }
+
+    /// A bare word: a keyword, prefixed name or number. Local names may hold
+    /// dots but not end with one, which then ends the statement instead.
+    fn word(&mut self) -> String {
+        let start = self.position;
+        while self.peek().is_some_and(|ch| !ch.is_whitespace() && !";,()[]<\"'#".contains(ch)) {
+            self.next_char();
+        }
+        while self.position > start + 1 && self.chars[self.position - 1] == '.' {
+            self.position -= 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+
+    fn iri_ref(&mut self) -> Result<String, FormatError> {
+        self.expect('<')?;
+        let mut iri = String::new();
+        loop {
+            match self.next_char() {
+                Some('>') => break,
+                Some(ch) if ch.is_whitespace() => return Err(self.malformed("space in IRI")),
+                Some(ch) => iri.push(ch),
+                None => return Err(self.malformed("unterminated IRI")),
+            }
+        }
+        Ok(if iri.contains(':') { iri } else { format!("{}{}", self.base, iri) })
+    }
+
+    fn prefixed(&self, name: &str) -> Result<String, FormatError> {
+        let colon = name.find(':')
+            .ok_or_else(|| self.malformed(&format!("unexpected '{}'", name)))?;
+        let namespace = self.prefixes.get(&name[..colon])
+            .ok_or_else(|| self.malformed(&format!("undeclared prefix '{}'", &name[..colon])))?;
+        Ok(format!("{}{}", namespace, name[colon + 1..].replace('\\', "")))
+    }
+
+    fn statement(&mut self) -> Result<(), FormatError> {
+        match self.peek() {
+            Some('@') => {
+                self.next_char();
+                let keyword = self.word();
+                self.directive(&keyword)?;
+                self.expect('.')
+            },
+            Some('<') | Some('[') | Some('_') => self.triples_statement(),
+            _ => {
+                let start = (self.position, self.line);
+                let keyword = self.word();
+                if keyword.eq_ignore_ascii_case("prefix") || keyword.eq_ignore_ascii_case("base") {
+                    self.directive(&keyword.to_lowercase())
+                } else {
+                    self.position = start.0;
+                    self.line = start.1;
+                    self.triples_statement()
+                }
+            },
+        }
+    }
+
+    fn directive(&mut self, keyword: &str) -> Result<(), FormatError> {
+        self.skip_space();
+        match keyword {
+            "prefix" => {
+                let name = self.word();
+                if !name.ends_with(':') {
+                    return Err(self.malformed(&format!("bad prefix name '{}'", name)));
+                }
+                let iri = self.iri_ref()?;
+                self.prefixes.insert(String::from(&name[..name.len() - 1]), iri);
+            },
+            "base" => self.base = self.iri_ref()?,
+            other => return Err(self.malformed(&format!("unknown directive '{}'", other))),
+        }
+        Ok(())
+    }
+
+    fn triples_statement(&mut self) -> Result<(), FormatError> {
+        let subject = if self.peek() == Some('[') {
+            let node = self.blank_property_list()?;
+            self.skip_space();
+            if self.peek() == Some('.') {
+                return self.expect('.');
+            }
+            node
+        } else {
+            self.node()?
+        };
+        self.predicate_objects(&subject)?;
+        self.expect('.')
+    }
+
+    /// An IRI, prefixed name or labelled blank node.
+    fn node(&mut self) -> Result<String, FormatError> {
+        self.skip_space();
+        match self.peek() {
+            Some('<') => self.iri_ref(),
+            _ => {
+                let name = self.word();
+                if name.starts_with("_:") {
+                    Ok(name)
+                } else {
+                    self.prefixed(&name)
+                }
+            },
+        }
+    }
+
+    fn blank_property_list(&mut self) -> Result<String, FormatError> {
+        self.expect('[')?;
+        self.blanks += 1;
+        let node = format!("_:b{}", self.blanks);
+        self.skip_space();
+        if self.peek() != Some(']') {
+            self.predicate_objects(&node)?;
+        }
+        self.expect(']')?;
+        Ok(node)
+    }
+
+    fn predicate_objects(&mut self, subject: &str) -> Result<(), FormatError> {
+        loop {
+            self.skip_space();
+            let predicate = if self.peek() == Some('a') && self.chars.get(self.position + 1)
+                .is_some_and(|ch| ch.is_whitespace() || *ch == '<' || *ch == '[') {
+                self.next_char();
+                String::from(RDF_TYPE)
+            } else {
+                self.node()?
+            };
+            loop {
+                let object = self.object()?;
+                self.triples.push((String::from(subject), predicate.clone(), object));
+                self.skip_space();
+                if self.peek() != Some(',') {
+                    break;
+                }
+                self.next_char();
+            }
+            // Any number of semicolons may separate, or trail, the pairs.
+            let mut more = false;
+            while self.peek() == Some(';') {
+                self.next_char();
+                self.skip_space();
+                more = true;
+            }
+            if !more || matches!(self.peek(), Some('.') | Some(']')) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Term, FormatError> {
+        self.skip_space();
+        match self.peek() {
+            Some('"') | Some('\'') => {
+                let text = self.string()?;
+                match self.peek() {
+                    Some('@') => {
+                        self.word();
+                    },
+                    Some('^') => {
+                        self.expect('^')?;
+                        self.expect('^')?;
+                        self.node()?;
+                    },
+                    _ => (),
+                }
+                Ok(Term::Literal(text))
+            },
+            Some('[') => Ok(Term::Node(self.blank_property_list()?)),
+            Some('(') => Err(self.malformed("collections are not supported")),
+            Some('<') => Ok(Term::Node(self.iri_ref()?)),
+            Some(ch) if ch.is_ascii_digit() || ch == '-' || ch == '+' || ch == '.' =>
+                Ok(Term::Literal(self.word())),
+            Some(_) => {
+                let start = (self.position, self.line);
+                let word = self.word();
+                if word == "true" || word == "false" {
+                    Ok(Term::Literal(word))
+                } else {
+                    self.position = start.0;
+                    self.line = start.1;
+                    Ok(Term::Node(self.node()?))
+                }
+            },
+            None => Err(self.malformed("expected an object at end of file")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, FormatError> {
+        let quote = self.next_char().unwrap();
+        let long = self.chars.get(self.position) == Some(&quote)
+            && self.chars.get(self.position + 1) == Some(&quote);
+        if long {
+            self.next_char();
+            self.next_char();
+        } else if self.peek() == Some(quote) {
+            self.next_char();
+            return Ok(String::new());
+        }
+        let mut text = String::new();
+        loop {
+            match self.next_char() {
+                None => return Err(self.malformed("unterminated string")),
+                Some(ch) if ch == quote && !long => return Ok(text),
+                Some(ch) if ch == quote && self.chars[self.position..].starts_with(&[quote, quote])
+                    && self.chars.get(self.position + 2) != Some(&quote) => {
+                    self.next_char();
+                    self.next_char();
+                    return Ok(text);
+                },
+                Some('\n') if !long => return Err(self.malformed("newline in string")),
+                Some('\\') => text.push(self.escape()?),
+                Some(ch) => text.push(ch),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, FormatError> {
+        let digits = match self.next_char() {
+            Some('t') => return Ok('\t'),
+            Some('n') => return Ok('\n'),
+            Some('r') => return Ok('\r'),
+            Some('b') => return Ok('\u{8}'),
+            Some('f') => return Ok('\u{c}'),
+            Some(ch) if "\"'\\".contains(ch) => return Ok(ch),
+            Some('u') => 4,
+            Some('U') => 8,
+            _ => return Err(self.malformed("bad escape in string")),
+        };
+        let hex: String = (0..digits).filter_map(|_| self.next_char()).collect();
+        u32::from_str_radix(&hex, 16).ok().and_then(::std::char::from_u32)
+            .ok_or_else(|| self.malformed("bad escape in string"))
+    }
+}
+
+/// An XML element, with its name and the names of its attributes expanded
+/// into IRIs by joining namespace and local name, as RDF/XML does. Names
+/// without a prefix have no namespace unless a default one is declared.
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlElement>,
+    /// The character data directly inside the element.
+    text: String,
+    line: usize,
+}
+
+impl XmlElement {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|a| a.0 == name).map(|a| &a.1[..])
+    }
+}
+
+/// A parser for the XML that RDF/XML is written in. Document type
+/// declarations may not declare entities, so only the predefined and
+/// numeric character references are understood.
+struct Xml {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+}
+
+impl Xml {
+    fn parse(text: &str) -> Result<XmlElement, FormatError> {
+        let mut parser = Xml { chars: text.chars().collect(), position: 0, line: 1 };
+        parser.misc()?;
+        let root = parser.element(&[(String::from("xml"), String::from(XML))])?;
+        parser.misc()?;
+        match parser.peek() {
+            None => Ok(root),
+            Some(_) => Err(parser.malformed("content after the root element")),
+        }
+    }
+
+    fn malformed(&self, message: &str) -> FormatError {
+        FormatError::Malformed(format!("line {}: {}", self.line, message))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).cloned()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.position += 1;
+        if ch == '\n' {
+            self.line += 1;
+        }
+        Some(ch)
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars().enumerate().all(|(i, ch)| self.chars.get(self.position + i) == Some(&ch))
+    }
+
+    fn skip_space(&mut self) {
+        while self.peek().is_some_and(|ch| ch.is_whitespace()) {
+            self.next_char();
+        }
+    }
+
+    /// Skip past the next `end`.
+    fn skip_past(&mut self, end: &str) -> Result<(), FormatError> {
+        while !self.starts_with(end) {
+            if self.next_char().is_none() {
+                return Err(self.malformed(&format!("expected '{}' at end of file", end)));
+            }
+        }
+        for _ in end.chars() {
+            self.next_char();
+        }
+        Ok(())
+    }
+
+    /// Space, comments, processing instructions and a document type
+    /// declaration outside the root element.
+    fn misc(&mut self) -> Result<(), FormatError> {
+        loop {
+            self.skip_space();
+            if self.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.starts_with("<!DOCTYPE") {
+                while self.peek().is_some_and(|ch| ch != '>') {
+                    if self.next_char() == Some('[') {
+                        return Err(self.malformed("document type declarations are not supported"));
+                    }
+                }
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, FormatError> {
+        let start = self.position;
+        while self.peek().is_some_and(|ch| !ch.is_whitespace() && !"=/>".contains(ch)) {
+            self.next_char();
+        }
+        if self.position == start {
+            return Err(self.malformed("expected a name"));
+        }
+        Ok(self.chars[start..self.position].iter().collect())
+    }
+
+    /// A character reference or one of the predefined entities, after its
+    /// `&`.
+    fn reference(&mut self) -> Result<char, FormatError> {
+        let mut name = String::new();
+        loop {
+            match self.next_char() {
+                Some(';') => break,
+                Some(ch) if name.len() < 10 => name.push(ch),
+                _ => return Err(self.malformed("unterminated reference")),
+            }
+        }
+        let code = match &name[..] {
+            "lt" => return Ok('<'),
+            "gt" => return Ok('>'),
+            "amp" => return Ok('&'),
+            "quot" => return Ok('"'),
+            "apos" => return Ok('\''),
+            _ if name.starts_with("#x") => u32::from_str_radix(&name[2..], 16).ok(),
+            _ if name.starts_with('#') => name[1..].parse().ok(),
+            _ => None,
+        };
+        code.and_then(::std::char::from_u32)
+            .ok_or_else(|| self.malformed(&format!("unknown reference '&{};'", name)))
+    }
+
+    fn attribute_value(&mut self) -> Result<String, FormatError> {
+        self.skip_space();
+        let quote = match self.next_char() {
+            Some(quote) if quote == '"' || quote == '\'' => quote,
+            _ => return Err(self.malformed("expected a quoted attribute value")),
+        };
+        let mut value = String::new();
+        loop {
+            match self.next_char() {
+                Some(ch) if ch == quote => return Ok(value),
+                Some('&') => value.push(self.reference()?),
+                Some('<') | None => return Err(self.malformed("unterminated attribute value")),
+                Some(ch) => value.push(ch),
+            }
+        }
+    }
+
+    /// Expand a prefixed name with the namespaces in scope, innermost last.
+    fn expand(&self, name: &str, namespaces: &[(String, String)], attribute: bool)
+              -> Result<String, FormatError> {
+        let (prefix, local) = match name.find(':') {
+            Some(colon) => (&name[..colon], &name[colon + 1..]),
+            None if attribute => return Ok(String::from(name)),
+            None => ("", name),
+        };
+        match namespaces.iter().rev().find(|n| n.0 == prefix) {
+            Some(namespace) => Ok(format!("{}{}", namespace.1, local)),
+            None if prefix.is_empty() => Ok(String::from(local)),
+            None => Err(self.malformed(&format!("undeclared prefix '{}'", prefix))),
+        }
+    }
+
+    fn element(&mut self, namespaces: &[(String, String)]) -> Result<XmlElement, FormatError> {
+        let line = self.line;
+        if self.next_char() != Some('<') {
+            return Err(self.malformed("expected an element"));
+        }
+        let tag = self.name()?;
+        let mut raw = Vec::new();
+        let empty = loop {
+            self.skip_space();
+            if self.starts_with("/>") {
+                self.next_char();
+                self.next_char();
+                break true;
+            }
+            if self.peek() == Some('>') {
+                self.next_char();
+                break false;
+            }
+            let name = self.name()?;
+            self.skip_space();
+            if self.next_char() != Some('=') {
+                return Err(self.malformed(&format!("expected '=' after '{}'", name)));
+            }
+            raw.push((name, self.attribute_value()?));
+        };
+        let mut scope = namespaces.to_vec();
+        for (name, value) in raw.iter() {
+            if name == "xmlns" {
+                scope.push((String::new(), value.clone()));
+            } else if let Some(prefix) = name.strip_prefix("xmlns:") {
+                scope.push((String::from(prefix), value.clone()));
+            }
+        }
+        let mut attributes = Vec::new();
+        for (name, value) in raw {
+            if name != "xmlns" && !name.starts_with("xmlns:") {
+                attributes.push((self.expand(&name, &scope, true)?, value));
+            }
+        }
+        let mut element = XmlElement { name: self.expand(&tag, &scope, false)?, attributes,
+                                    children: Vec::new(), text: String::new(), line };
+        if empty {
+            return Ok(element);
+        }
+        loop {
+            if self.starts_with("</") {
+                self.next_char();
+                self.next_char();
+                let close = self.name()?;
+                self.skip_space();
+                if close != tag || self.next_char() != Some('>') {
+                    return Err(self.malformed(&format!("expected '</{}>'", tag)));
+                }
+                return Ok(element);
+            } else if self.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.starts_with("<![CDATA[") {
+                let start = self.position + "<![CDATA[".len();
+                self.skip_past("]]>")?;
+                element.text.extend(self.chars[start..self.position - 3].iter());
+            } else if self.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else {
+                match self.peek() {
+                    Some('<') => element.children.push(self.element(&scope)?),
+                    Some('&') => {
+                        self.next_char();
+                        element.text.push(self.reference()?);
+                    },
+                    Some(_) => element.text.push(self.next_char().unwrap()),
+                    None => return Err(self.malformed(&format!("expected '</{}>'", tag))),
+                }
+            }
+        }
+    }
+}
+
+/// Reads the triples of an RDF/XML document. Typed node elements,
+/// `rdf:Description`, `rdf:resource`, `rdf:nodeID`, nested nodes, property
+/// attributes and `rdf:parseType="Resource"` are understood; other parse
+/// types and reification are not. Literals keep only their text, and IRIs
+/// are taken as written, without resolving them against `xml:base`.
+struct RdfXml {
+    blanks: usize,
+    triples: Vec<(String, String, Term)>,
+}
+
+impl RdfXml {
+    fn parse(text: &str) -> Result<Vec<(String, String, Term)>, FormatError> {
+        let root = Xml::parse(text)?;
+        let mut parser = RdfXml { blanks: 0, triples: Vec::new() };
+        if root.name == format!("{}RDF", RDF) {
+            for node in root.children.iter() {
+                parser.node(node)?;
+            }
+        } else {
+            parser.node(&root)?;
+        }
+        Ok(parser.triples)
+    }
+
+    fn blank(&mut self) -> String {
+        self.blanks += 1;
+        format!("_:b{}", self.blanks)
+    }
+
+    fn rdf<'a>(element: &'a XmlElement, name: &str) -> Option<&'a str> {
+        element.attribute(&format!("{}{}", RDF, name))
+    }
+
+    /// Add the triples of a node element and return its subject.
+    fn node(&mut self, element: &XmlElement) -> Result<String, FormatError> {
+        let subject = match (RdfXml::rdf(element, "about"), RdfXml::rdf(element, "nodeID")) {
+            (Some(about), _) => String::from(about),
+            (None, Some(id)) => format!("_:{}", id),
+            (None, None) => self.blank(),
+        };
+        if element.name != format!("{}Description", RDF) {
+            self.triples.push((subject.clone(), String::from(RDF_TYPE),
+                               Term::Node(element.name.clone())));
+        }
+        for (name, value) in element.attributes.iter() {
+            if name == RDF_TYPE {
+                self.triples.push((subject.clone(), name.clone(), Term::Node(value.clone())));
+            } else if !name.starts_with(RDF) && !name.starts_with(XML) {
+                self.triples.push((subject.clone(), name.clone(), Term::Literal(value.clone())));
+            }
+        }
+        if !element.text.trim().is_empty() {
+            return Err(FormatError::Malformed(format!("line {}: text in a node element",
+                                                      element.line)));
+        }
+        for property in element.children.iter() {
+            self.property(&subject, property)?;
+        }
+        Ok(subject)
+    }
+
+    fn property(&mut self, subject: &str, element: &XmlElement) -> Result<(), FormatError> {
+        let malformed = |message: &str| FormatError::Malformed(
+            format!("line {}: {}", element.line, message));
+        let object = match RdfXml::rdf(element, "parseType") {
+            Some("Resource") => {
+                let node = self.blank();
+                for property in element.children.iter() {
+                    self.property(&node, property)?;
+                }
+                Term::Node(node)
+            },
+            Some(other) => return Err(malformed(&format!("parseType {} is not supported", other))),
+            None => match (RdfXml::rdf(element, "resource"), RdfXml::rdf(element, "nodeID"),
+                           &element.children[..]) {
+                (Some(resource), _, []) => Term::Node(String::from(resource)),
+                (None, Some(id), []) => Term::Node(format!("_:{}", id)),
+                (None, None, [node]) => Term::Node(self.node(node)?),
+                (None, None, []) => Term::Literal(element.text.clone()),
+                _ => return Err(malformed("property element with more than one object")),
+            },
+        };
+        self.triples.push((String::from(subject), element.name.clone(), object));
+        Ok(())
+    }
+}
+
+/// Whether a document starts like XML rather than Turtle or N-Triples: with
+/// a declaration, comment or document type, or an `RDF` root element.
+fn is_xml(text: &str) -> bool {
+    let text = text.trim_start();
+    if text.starts_with("<?") || text.starts_with("<!") {
+        return true;
+    }
+    let tag: String = text.chars().skip(1)
+        .take_while(|&ch| !ch.is_whitespace() && ch != '>' && ch != '/').collect();
+    text.starts_with('<') && (tag == "RDF" || tag.ends_with(":RDF"))
+}
+
+/// The triples of a graph, grouped by subject in order of appearance.
+struct Graph {
+    subjects: Vec<String>,
+    properties: HashMap<String, Vec<(String, Term)>>,
+}
+
+impl Graph {
+    fn new(triples: Vec<(String, String, Term)>) -> Graph {
+        let mut graph = Graph { subjects: Vec::new(), properties: HashMap::new() };
+        for (subject, predicate, object) in triples {
+            if !graph.properties.contains_key(&subject) {
+                graph.subjects.push(subject.clone());
+            }
+            graph.properties.entry(subject).or_default().push((predicate, object));
+        }
+        graph
+    }
+
+    fn objects(&self, subject: &str, predicate: &str) -> Vec<&Term> {
+        self.properties.get(subject).into_iter().flat_map(|p| p.iter())
+            .filter(|p| p.0 == predicate).map(|p| &p.1).collect()
+    }
+
+    fn nodes(&self, subject: &str, property: &str) -> Vec<&str> {
+        let predicate = format!("{}{}", SBOL, property);
+        self.objects(subject, &predicate).into_iter().filter_map(|term| match *term {
+            Term::Node(ref node) => Some(&node[..]),
+            Term::Literal(_) => None,
+        }).collect()
+    }
+
+    fn literal(&self, subject: &str, property: &str) -> Option<&str> {
+        let predicate = format!("{}{}", SBOL, property);
+        self.objects(subject, &predicate).into_iter().filter_map(|term| match *term {
+            Term::Literal(ref text) => Some(&text[..]),
+            Term::Node(_) => None,
+        }).next()
+    }
+
+    fn is_a(&self, subject: &str, class: &str) -> bool {
+        let class = format!("{}{}", SBOL, class);
+        self.objects(subject, RDF_TYPE).iter().any(|term| **term == Term::Node(class.clone()))
+    }
+
+    fn display_id(&self, subject: &str) -> String {
+        match self.literal(subject, "displayId") {
+            Some(id) => String::from(id),
+            None => String::from(subject.rsplit(['/', '#', ':']).next().unwrap_or(subject)),
+        }
+    }
+}
+
+/// An ontology term such as `SO:0000167` from an IRI in any of the usual
+/// identifiers.org forms.
+fn term(iri: &str) -> Option<String> {
+    ["SO:", "SBO:"].iter().filter_map(|prefix| iri.rfind(prefix).map(|i| String::from(&iri[i..])))
+        .next()
+}
+
+/// Reads SBOL3 documents written as Turtle, N-Triples or RDF/XML, which is
+/// recognised by an XML declaration or `rdf:RDF` root at the start.
+///
+/// Only DNA components are read. A component's features are its
+/// `SequenceFeature`s, which keep their name as label and role as kind but
+/// no other properties, and its `SubComponent`s; other kinds of feature are
+/// skipped. Locations are read as `Range`s and `EntireSequence`s; a
+/// feature located by two ranges that meet across the origin of a circular
+/// sequence spans it, and any other set of ranges is read as its overall
+/// span.
+pub struct SbolReader<R> {
+    reader: R,
+}
+
+impl<R> SbolReader<R> where R: BufRead {
+    pub fn new(reader: R) -> SbolReader<R> {
+        SbolReader { reader }
+    }
+
+    /// Read the whole document. The namespace is taken from the first
+    /// component that declares one.
+    pub fn read(mut self) -> Result<SbolDocument, FormatError> {
+        let mut text = String::new();
+        self.reader.read_to_string(&mut text)?;
+        let triples = if is_xml(&text) { RdfXml::parse(&text)? } else { Turtle::parse(&text)? };
+        let graph = Graph::new(triples);
+        let mut document = SbolDocument::new("");
+        for subject in graph.subjects.iter().filter(|s| graph.is_a(s, "Component")) {
+            let types: Vec<String> = graph.nodes(subject, "type").iter()
+                .filter_map(|t| term(t)).collect();
+            if !types.iter().any(|t| t == DNA) {
+                continue;
+            }
+            if document.namespace.is_empty() {
+                if let Some(namespace) = graph.nodes(subject, "hasNamespace").first() {
+                    document.namespace = String::from(*namespace);
+                }
+            }
+            document.components.push(read_component(&graph, subject, &types)?);
+        }
+        check_lengths(&document)?;
+        Ok(document)
+    }
+}
+
+/// Check that every located subcomponent spans exactly the sequence of the
+/// component it uses, where that has one.
+fn check_lengths(document: &SbolDocument) -> Result<(), FormatError> {
+    for component in document.components.iter() {
+        for sub in component.subcomponents.iter() {
+            let child = document.component(&sub.instance_of)
+                .and_then(|child| child.sequence.as_ref());
+            if let (Some((start, end)), Some(child)) = (sub.location, child) {
+                if child.len() != end - start {
+                    return Err(FormatError::Malformed(format!(
+                        "component {}: {} is {} bp but is located at {}..{}",
+                        component.display_id, sub.instance_of, child.len(), start + 1, end)));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_component(graph: &Graph, subject: &str, types: &[String])
+                  -> Result<Component, FormatError> {
+    let mut component = Component::new(&graph.display_id(subject));
+    let malformed = |message: &str| FormatError::Malformed(
+        format!("component {}: {}", graph.display_id(subject), message));
+    component.name = graph.literal(subject, "name").map(String::from);
+    component.roles = graph.nodes(subject, "role").iter().filter_map(|r| term(r)).collect();
+    if types.iter().any(|t| t == CIRCULAR) {
+        component.topology = Topology::Circular;
+    }
+    if let Some(node) = graph.nodes(subject, "hasSequence").first() {
+        if graph.nodes(node, "encoding").iter().any(|e| !e.ends_with(IUPAC_DNA)) {
+            return Err(malformed("sequence is not IUPAC DNA"));
+        }
+        let elements = graph.literal(node, "elements").unwrap_or("");
+        let seq = Sequence::<DegenerateNucleotide>::from_str(elements)
+            .map_err(|e| malformed(&e.to_string()))?;
+        component.sequence = Some(seq);
+    }
+    let length = component.sequence.as_ref().map(|seq| seq.len());
+    for feature in graph.nodes(subject, "hasFeature") {
+        let location = location(graph, feature, length, component.topology)
+            .map_err(|message| malformed(&message))?;
+        if graph.is_a(feature, "SequenceFeature") {
+            let (start, end, strand) = location
+                .ok_or_else(|| malformed("sequence feature without a location"))?;
+            let roles: Vec<String> = graph.nodes(feature, "role").iter()
+                .filter_map(|r| term(r)).collect();
+            let label = graph.literal(feature, "name").map(String::from)
+                .unwrap_or_else(|| graph.display_id(feature));
+            component.features.push(Feature::new(kind(&roles), &label, start, end, strand));
+        } else if graph.is_a(feature, "SubComponent") {
+            let instance = graph.nodes(feature, "instanceOf").first().cloned()
+                .ok_or_else(|| malformed("subcomponent without instanceOf"))?;
+            component.subcomponents.push(SubComponent {
+                instance_of: graph.display_id(instance),
+                location: location.map(|(start, end, _)| (start, end)),
+                strand: location.map_or(Strand::Forward, |l| l.2),
+            });
+        }
+    }
+    Ok(component)
+}
+
+/// The overall location of a feature as zero-based, half-open coordinates
+/// and a strand, or `None` if it has no location.
+fn location(graph: &Graph, feature: &str, length: Option<usize>, topology: Topology)
+            -> Result<Option<(usize, usize, Strand)>, String> {
+    let mut ranges = Vec::new();
+    let mut strand = Strand::Forward;
+    for node in graph.nodes(feature, "hasLocation") {
+        if graph.nodes(node, "orientation").iter().any(|o| o.ends_with("reverseComplement")
+                                                          || o.ends_with("SO:0001031")) {
+            strand = Strand::Reverse;
+        }
+        if graph.is_a(node, "EntireSequence") {
+            let length = length.ok_or("location on a component without a sequence")?;
+            ranges.push((1, length));
+            continue;
+        }
+        if !graph.is_a(node, "Range") {
+            continue;
+        }
+        let bound = |property: &str| graph.literal(node, property)
+            .and_then(|text| text.parse::<usize>().ok())
+            .ok_or(format!("range without a valid {}", property));
+        let (start, end) = (bound("start")?, bound("end")?);
+        if start == 0 || end < start || length.is_some_and(|length| end > length) {
+            return Err(format!("range {}..{} out of bounds", start, end));
+        }
+        ranges.push((start, end));
+    }
+    if ranges.is_empty() {
+        return Ok(None);
+    }
+    if let (Some(length), 2, Topology::Circular) = (length, ranges.len(), topology) {
+        let (first, second) = (ranges[0], ranges[1]);
+        for &(a, b) in [(first, second), (second, first)].iter() {
+            if a.1 == length && b.0 == 1 {
+                return Ok(Some((a.0 - 1, length + b.1, strand)));
+            }
+        }
+    }
+    let start = ranges.iter().map(|r| r.0).min().unwrap();
+    let end = ranges.iter().map(|r| r.1).max().unwrap();
+    Ok(Some((start - 1, end, strand)))
+}
+
+/// The RDF syntax an `SbolWriter` writes.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum RdfSyntax {
+    Turtle,
+    RdfXml,
+}
+
+/// Writes SBOL3 documents as Turtle, or as RDF/XML if asked.
+///
+/// Feature qualifiers have no SBOL counterpart and are not written, and
+/// feature kinds without a Sequence Ontology term of their own are written
+/// as the generic `sequence_feature`, which reads back as `misc_feature`.
+pub struct SbolWriter<W> {
+    writer: W,
+    syntax: RdfSyntax,
+}
+
+/// A property value to be written.
+enum Value {
+    Iri(String),
+    Text(String),
+    Integer(usize),
+}
+
+/// The properties of one object.
+type Properties = Vec<(&'static str, Vec<Value>)>;
+
+/// The prefixes declared in Turtle output.
+const PREFIXES: [(&str, &str); 4] = [("sbol", SBOL), ("SO", SO), ("SBO", SBO), ("EDAM", EDAM)];
+
+fn string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in text.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// An IRI as a prefixed name if it lies in a declared namespace, or
+/// written out in full.
+fn compact(iri: &str) -> String {
+    for &(prefix, namespace) in PREFIXES.iter() {
+        if let Some(local) = iri.strip_prefix(namespace) {
+            let simple = local.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+            if !local.is_empty() && simple {
+                return format!("{}:{}", prefix, local);
+            }
+        }
+    }
+    format!("<{}>", iri)
+}
+
+/// The display id of a child object: the last part of its IRI.
+fn local_id(iri: &str) -> String {
+    String::from(iri.rsplit('/').next().unwrap())
+}
+
+fn turtle(value: &Value) -> String {
+    match *value {
+        Value::Iri(ref iri) => compact(iri),
+        Value::Text(ref text) => string(text),
+        Value::Integer(n) => n.to_string(),
+    }
+}
+
+/// Text escaped for XML content or a double-quoted attribute.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// The IRI of an ontology term such as `SO:0000167`; other roles are
+/// taken to be IRIs already.
+fn ontology(term: &str) -> Value {
+    if term.starts_with("SO:") || term.starts_with("SBO:") {
+        Value::Iri(format!("https://identifiers.org/{}", term))
+    } else {
+        Value::Iri(String::from(term))
+    }
+}
+
+impl<W> SbolWriter<W> where W: Write {
+    pub fn new(writer: W) -> SbolWriter<W> {
+        SbolWriter { writer, syntax: RdfSyntax::Turtle }
+    }
+
+    pub fn with_syntax(mut self, syntax: RdfSyntax) -> SbolWriter<W> {
+        self.syntax = syntax;
+        self
+    }
+
+    pub fn write(&mut self, document: &SbolDocument) -> Result<(), FormatError> {
+        match self.syntax {
+            RdfSyntax::Turtle => for &(prefix, namespace) in PREFIXES.iter() {
+                writeln!(self.writer, "@prefix {}: <{}> .", prefix, namespace)?;
+            },
+            RdfSyntax::RdfXml => {
+                writeln!(self.writer, "<?xml version=\"1.0\" encoding=\"utf-8\"?>")?;
+                writeln!(self.writer, "<rdf:RDF xmlns:rdf=\"{}\" xmlns:sbol=\"{}\">", RDF, SBOL)?;
+            },
+        }
+        let namespace = &document.namespace;
+        for component in document.components.iter() {
+            let subject = format!("{}/{}", namespace, component.display_id);
+            let sequence = format!("{}_sequence", subject);
+            let topology = match component.topology {
+                Topology::Linear => LINEAR,
+                Topology::Circular => CIRCULAR,
+            };
+            let mut properties: Properties = vec![
+                ("displayId", vec![Value::Text(component.display_id.clone())]),
+                ("hasNamespace", vec![Value::Iri(namespace.clone())]),
+                ("type", vec![ontology(DNA), ontology(topology)]),
+            ];
+            if let Some(ref name) = component.name {
+                properties.insert(1, ("name", vec![Value::Text(name.clone())]));
+            }
+            if !component.roles.is_empty() {
+                properties.push(("role", component.roles.iter().map(|r| ontology(r)).collect()));
+            }
+            if component.sequence.is_some() {
+                properties.push(("hasSequence", vec![Value::Iri(sequence.clone())]));
+            }
+            let features: Vec<String> = (1..=component.features.len())
+                .map(|i| format!("{}/SequenceFeature{}", subject, i))
+                .chain((1..=component.subcomponents.len())
+                    .map(|i| format!("{}/SubComponent{}", subject, i)))
+                .collect();
+            if !features.is_empty() {
+                properties.push(("hasFeature",
+                                 features.iter().map(|f| Value::Iri(f.clone())).collect()));
+            }
+            self.object(&subject, "Component", properties)?;
+
+            let length = component.sequence.as_ref().map_or(0, |seq| seq.len());
+            for (feature, node) in component.features.iter().zip(features.iter()) {
+                let ranges = self.ranges(node, &sequence, feature.start, feature.end,
+                                         feature.strand, length)?;
+                self.object(node, "SequenceFeature", vec![
+                    ("displayId", vec![Value::Text(local_id(node))]),
+                    ("name", vec![Value::Text(feature.label.clone())]),
+                    ("role", vec![ontology(role(&feature.kind))]),
+                    ("hasLocation", ranges),
+                ])?;
+            }
+            for (sub, node) in component.subcomponents.iter()
+                .zip(features.iter().skip(component.features.len())) {
+                let mut properties: Properties = vec![
+                    ("displayId", vec![Value::Text(local_id(node))]),
+                    ("instanceOf", vec![Value::Iri(format!("{}/{}", namespace, sub.instance_of))]),
+                ];
+                if let Some((start, end)) = sub.location {
+                    let ranges = self.ranges(node, &sequence, start, end, sub.strand, length)?;
+                    properties.push(("hasLocation", ranges));
+                }
+                self.object(node, "SubComponent", properties)?;
+            }
+            if let Some(ref seq) = component.sequence {
+                self.object(&sequence, "Sequence", vec![
+                    ("displayId", vec![Value::Text(format!("{}_sequence", component.display_id))]),
+                    ("hasNamespace", vec![Value::Iri(namespace.clone())]),
+                    ("elements", vec![Value::Text(seq.to_string().to_lowercase())]),
+                    ("encoding", vec![Value::Iri(format!("{}{}", EDAM, IUPAC_DNA))]),
+                ])?;
+            }
+        }
+        if self.syntax == RdfSyntax::RdfXml {
+            writeln!(self.writer, "</rdf:RDF>")?;
+        }
+        Ok(())
+    }
+
+    /// Write the ranges locating `start..end` and return their IRIs: one
+    /// range, or two for a span across the origin.
+    fn ranges(&mut self, feature: &str, sequence: &str, start: usize, end: usize, strand: Strand,
+              length: usize) -> Result<Vec<Value>, FormatError> {
+        let spans = if end > length && length > 0 {
+            vec![(start, length), (0, end - length)]
+        } else {
+            vec![(start, end)]
+        };
+        let mut nodes = Vec::new();
+        for (i, &(from, to)) in spans.iter().enumerate() {
+            let node = format!("{}/Range{}", feature, i + 1);
+            let orientation = match strand {
+                Strand::Forward => "inline",
+                Strand::Reverse => "reverseComplement",
+            };
+            self.object(&node, "Range", vec![
+                ("displayId", vec![Value::Text(format!("Range{}", i + 1))]),
+                ("start", vec![Value::Integer(from + 1)]),
+                ("end", vec![Value::Integer(to)]),
+                ("orientation", vec![Value::Iri(format!("{}{}", SBOL, orientation))]),
+                ("hasSequence", vec![Value::Iri(String::from(sequence))]),
+            ])?;
+            nodes.push(Value::Iri(node));
+        }
+        Ok(nodes)
+    }
+
+    fn object(&mut self, subject: &str, class: &str, properties: Properties)
+              -> Result<(), FormatError> {
+        if self.syntax == RdfSyntax::Turtle {
+            write!(self.writer, "\n<{}> a sbol:{}", subject, class)?;
+            for (property, values) in properties.iter() {
+                let values: Vec<String> = values.iter().map(turtle).collect();
+                write!(self.writer, " ;\n    sbol:{} {}", property, values.join(", "))?;
+            }
+            writeln!(self.writer, " .")?;
+            return Ok(());
+        }
+        writeln!(self.writer, "  <sbol:{} rdf:about=\"{}\">", class, escape_xml(subject))?;
+        for (property, values) in properties.iter() {
+            for value in values.iter() {
+                match *value {
+                    Value::Iri(ref iri) => writeln!(
+                        self.writer, "    <sbol:{} rdf:resource=\"{}\"/>", property,
+                        escape_xml(iri))?,
+                    Value::Text(ref text) => writeln!(
+                        self.writer, "    <sbol:{0}>{1}</sbol:{0}>", property, escape_xml(text))?,
+                    Value::Integer(n) => writeln!(
+                        self.writer, "    <sbol:{0} rdf:datatype=\"{1}\">{2}</sbol:{0}>", property,
+                        XSD_INTEGER, n)?,
+                }
+            }
+        }
+        writeln!(self.writer, "  </sbol:{}>", class)?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), FormatError> {
+        Ok(self.writer.flush()?)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{SbolDocument, SbolReader, SbolWriter, RdfSyntax, SubComponent, Turtle, RdfXml,
+                Term};
+    use sequence::sequence::Sequence;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::strand::Strand;
+    use sequence::feature::{FeatureKind, Topology};
+    use design::{Part, PartKind, TranscriptionUnit, Plasmid};
+    use io::error::FormatError;
+
+    const CASSETTE: &str = include_str!("../../fixtures/sbol3/expression_cassette.ttl");
+    const PROMOTER: &str = include_str!("../../fixtures/sbol3/promoter.nt");
+    const CASSETTE_XML: &str = include_str!("../../fixtures/sbol3/expression_cassette.xml");
+
+    fn read(text: &str) -> SbolDocument {
+        SbolReader::new(Cursor::new(text)).read().unwrap()
+    }
+
+    fn write(document: &SbolDocument) -> String {
+        let mut writer = SbolWriter::new(Vec::new());
+        writer.write(document).unwrap();
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn turtle() {
+        let text = "@prefix ex: <http://example.org/> .\nPREFIX x: <urn:x:>\n\
+                    ex:a a ex:Thing ; ex:label \"\"\"two\nlines\"\"\"@en , 'it\\'s' ;\n\
+                    ex:size 12 ; ex:part [ ex:n \"\\u0041\"^^ex:t ] ; .\n\
+                    # a comment\n<http://example.org/b> x:flag true .";
+        let triples = Turtle::parse(text).unwrap();
+        assert_eq!(triples.len(), 7);
+        assert_eq!(triples[1].2, Term::Literal(String::from("two\nlines")));
+        assert_eq!(triples[2].2, Term::Literal(String::from("it's")));
+        assert_eq!(triples[4], (String::from("_:b1"), String::from("http://example.org/n"),
+                                Term::Literal(String::from("A"))));
+        assert_eq!(triples[6].1, "urn:x:flag");
+        assert!(Turtle::parse("ex:a ex:b ex:c .").is_err());
+        assert!(Turtle::parse("<a> <b> (<c>) .").is_err());
+        assert!(Turtle::parse("<a> <b> \"open .").is_err());
+    }
+
+    #[test]
+    fn rdf_xml() {
+        let text = "<?xml version=\"1.0\"?>\n<!DOCTYPE rdf:RDF>\n\
+                    <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\"\n\
+                    xmlns=\"http://example.org/\"><!-- nodes -->\n\
+                    <Thing rdf:about=\"http://example.org/a\" label=\"x &amp; y\">\n\
+                    <size rdf:datatype=\"http://www.w3.org/2001/XMLSchema#integer\">12</size>\n\
+                    <part rdf:parseType=\"Resource\"><n>&#65;<![CDATA[<b>]]></n></part>\n\
+                    <next><rdf:Description rdf:nodeID=\"c\"/></next>\n\
+                    <see rdf:resource=\"urn:x:b\"/></Thing></rdf:RDF>";
+        let triples = RdfXml::parse(text).unwrap();
+        assert_eq!(triples.len(), 7);
+        assert_eq!(triples[0].2, Term::Node(String::from("http://example.org/Thing")));
+        assert_eq!(triples[1].2, Term::Literal(String::from("x & y")));
+        assert_eq!(triples[2].2, Term::Literal(String::from("12")));
+        assert_eq!(triples[3], (String::from("_:b1"), String::from("http://example.org/n"),
+                                Term::Literal(String::from("A<b>"))));
+        assert_eq!(triples[5].2, Term::Node(String::from("_:c")));
+        assert_eq!(triples[6].2, Term::Node(String::from("urn:x:b")));
+        assert!(RdfXml::parse("<rdf:RDF xmlns:rdf=\"x\"><a:b/></rdf:RDF>").is_err());
+        assert!(RdfXml::parse("<rdf:RDF xmlns:rdf=\"x\"><b></c></rdf:RDF>").is_err());
+        assert!(RdfXml::parse("<r><b><p>1</p><p>2</p></b></r>").is_err());
+    }
+
+    #[test]
+    fn read_fixtures() {
+        let document = read(CASSETTE);
+        assert_eq!(document.namespace, "https://example.org/lab");
+        // The GFP protein component is not DNA and is skipped.
+        let ids: Vec<&str> = document.components.iter().map(|c| &c.display_id[..]).collect();
+        assert_eq!(ids, vec!["B0015", "B0034", "ColE1", "gfp", "gfp_cassette", "pGFP", "pTet"]);
+        let plasmid = document.component("pGFP").unwrap();
+        assert_eq!((plasmid.topology, plasmid.features.len()), (Topology::Circular, 1));
+        let wrap = &plasmid.features[0];
+        assert_eq!((&wrap.label[..], wrap.kind.clone()), ("wrap", FeatureKind::MiscFeature));
+        assert_eq!((wrap.start, wrap.end, wrap.strand), (66, 72, Strand::Reverse));
+        assert_eq!(plasmid.subcomponents[0], SubComponent {
+            instance_of: String::from("gfp_cassette"), location: Some((0, 60)),
+            strand: Strand::Reverse });
+
+        let records = document.records();
+        let record = records.iter().find(|r| r.name == "pGFP").unwrap();
+        assert_eq!((record.topology, record.sequence.len()), (Topology::Circular, 70));
+        let gfp = record.features.iter().find(|f| f.label == "GFP").unwrap();
+        assert_eq!((gfp.kind.clone(), gfp.strand), (FeatureKind::Cds, Strand::Reverse));
+        assert_eq!(record.extract(gfp).to_string(), "ATGCGTAAAGGCGAAGAGTAA");
+        let cassette = record.features.iter().find(|f| f.label == "GFP cassette").unwrap();
+        assert_eq!((cassette.start, cassette.end), (0, 60));
+
+        let promoter = read(PROMOTER);
+        assert_eq!(promoter.namespace, "https://example.org/lab");
+        let component = &promoter.components[0];
+        assert_eq!(component.roles, vec![String::from("SO:0000167")]);
+        assert_eq!(component.sequence.as_ref().unwrap().to_string(), "TTGACAGCTAGCTCAGTCCTAGG");
+        assert_eq!(component.features[0].kind, FeatureKind::MiscFeature);
+        assert_eq!((component.features[0].start, component.features[0].end), (0, 6));
+    }
+
+    #[test]
+    fn round_trip() {
+        let document = read(CASSETTE);
+        let text = write(&document);
+        assert!(text.contains("    sbol:orientation sbol:reverseComplement ;\n"));
+        assert_eq!(read(&text), document);
+        let promoter = read(PROMOTER);
+        assert_eq!(read(&write(&promoter)), promoter);
+    }
+
+    #[test]
+    fn rdf_xml_round_trip() {
+        let document = read(CASSETTE_XML);
+        assert_eq!(document, read(CASSETTE));
+        let mut writer = SbolWriter::new(Vec::new()).with_syntax(RdfSyntax::RdfXml);
+        writer.write(&document).unwrap();
+        let text = String::from_utf8(writer.into_inner()).unwrap();
+        assert!(text.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<rdf:RDF "));
+        assert!(text.contains("    <sbol:orientation \
+                               rdf:resource=\"http://sbols.org/v3#reverseComplement\"/>\n"));
+        assert_eq!(read(&text), document);
+        assert_eq!(read(&write(&read(&text))), document);
+    }
+
+    #[test]
+    fn from_design() {
+        let dna = |s: &str| Sequence::<Nucleotide>::from_str(s).unwrap();
+        let unit = TranscriptionUnit::new("tu")
+            .part(Part::new("pLac", PartKind::Promoter, dna("TTTACACTTTATGC")))
+            .part(Part::new("rbs", PartKind::Rbs, dna("AGGAGG")))
+            .part(Part::new("lacZ alpha", PartKind::Cds, dna("ATGACCATGTAA")))
+            .part(Part::new("term", PartKind::Terminator, dna("CCAGGCATCAAA")));
+        let plasmid = Plasmid::new("pDemo")
+            .part(Part::new("ori", PartKind::Origin, dna("TTGAGATCCTTT")), Strand::Forward)
+            .unit(unit, Strand::Reverse);
+        let document = read(&write(&SbolDocument::from_plasmid("https://example.org/lab/",
+                                                               &plasmid)));
+        assert_eq!(document.components.len(), 7);
+        assert_eq!(document.component("lacZ_alpha").unwrap().name.as_ref().unwrap(), "lacZ alpha");
+        let record = &document.records()[0];
+        let assembled = plasmid.assemble();
+        assert_eq!(record.sequence.to_string(), assembled.sequence.to_string());
+        for feature in assembled.features.iter() {
+            assert!(record.features.contains(feature), "{:?} missing", feature);
+        }
+        assert_eq!(record.features.len(), assembled.features.len() + 1);
+
+        let flat = SbolDocument::from_records("https://example.org", &[assembled.clone(),
+                                                                       assembled]);
+        assert_eq!(flat.components[1].display_id, "pDemo_2");
+        assert_eq!(read(&write(&flat)).records()[0].features.len(), 5);
+    }
+
+    #[test]
+    fn ambiguity_codes() {
+        let document = read(&CASSETTE.replace("\"ttgaga", "\"ttnrga"));
+        let origin = document.component("ColE1").unwrap();
+        assert_eq!(origin.sequence.as_ref().unwrap().to_string(), "TTNRGATCCT");
+        assert_eq!(read(&write(&document)), document);
+    }
+
+    #[test]
+    fn bad_input() {
+        let bad = CASSETTE.replace("sbol:end 70", "sbol:end 71");
+        assert!(SbolReader::new(Cursor::new(bad)).read().is_err());
+        let bad = CASSETTE.replace("\"ttgaga", "\"ttgxga");
+        match SbolReader::new(Cursor::new(bad)).read() {
+            Err(FormatError::Malformed(message)) => assert_eq!(
+                message, "component ColE1: Invalid symbol 'x' at position 4"),
+            other => panic!("unexpected {:?}", other),
+        }
+        let bad = CASSETTE.replace("@prefix sbol:", "@prefix sbl:");
+        assert!(SbolReader::new(Cursor::new(bad)).read().is_err());
+        // The cassette is 60 bp, so cannot fill 1..50 of the plasmid.
+        let range = "sbol:end {} ;\n    sbol:hasSequence <https://example.org/lab/pGFP_seq>";
+        let bad = CASSETTE.replace(&range.replace("{}", "60"), &range.replace("{}", "50"));
+        match SbolReader::new(Cursor::new(bad)).read() {
+            Err(FormatError::Malformed(message)) => assert_eq!(
+                message, "component pGFP: gfp_cassette is 60 bp but is located at 1..50"),
+            other => panic!("unexpected {:?}", other),
+        }
+        // A document built in code with the same mistake still flattens.
+        let mut document = read(CASSETTE);
+        let plasmid = document.components.iter_mut().find(|c| c.display_id == "pGFP").unwrap();
+        plasmid.subcomponents[0].location = Some((0, 50));
+        let records = document.records();
+        let record = records.iter().find(|r| r.name == "pGFP").unwrap();
+        assert!(record.features.iter().any(|f| f.label == "GFP cassette"));
+        assert!(!record.features.iter().any(|f| f.label == "GFP"));
+    }
+}
@@ -0,0 +1,374 @@
+use std::fs::File;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use sequence::sequence::Sequence;
+use sequence::nucleotide::Nucleotide;
+use sequence::degenerate_nucleotide::DegenerateNucleotide;
+use sequence::error::SequenceError;
+use io::error::FormatError;
+
+const SIGNATURE: u32 = 0x1A41_2743;
+
+/// Bases in packed .2bit DNA, indexed by their two-bit code.
+const BASES: [DegenerateNucleotide; 4] = [DegenerateNucleotide::T, DegenerateNucleotide::C,
+                                         DegenerateNucleotide::A, DegenerateNucleotide::G];
+
+/// Random-access reader for UCSC .2bit genome files.
+///
+/// Only the header and record index are read up front; range queries decode
+/// just the bytes they cover, so flanking sequence can be pulled from a
+/// chromosome without loading it. `open` memory-maps the file, while
+/// `from_bytes` reads from any in-memory buffer.
+pub struct TwoBitFile<B> {
+    data: B,
+    big_endian: bool,
+    records: Vec<(String, usize)>,
+}
+
+/// The sequence of a .2bit range together with its annotations, as
+/// half-open intervals relative to the start of the range.
+#[derive(Debug, PartialEq)]
+pub struct TwoBitRegion {
+    pub sequence: Sequence<DegenerateNucleotide>,
+    pub n_blocks: Vec<(usize, usize)>,
+    pub soft_masked: Vec<(usize, usize)>,
+}
+
+/// Parsed header of a single sequence record; the block fields are the file
+/// positions of the N-block and mask-block lists.
+struct RecordHeader {
+    dna_size: usize,
+    n_blocks: usize,
+    mask_blocks: usize,
+    packed_dna: usize,
+}
+
+impl TwoBitFile<Mmap> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<TwoBitFile<Mmap>, FormatError> {
+        let file = File::open(path)?;
+        // Safety: the map is read-only, and .2bit files are not expected to
+        // be modified while they are being queried.
+        let mmap = unsafe { Mmap::map(&file)? };
+        TwoBitFile::from_bytes(mmap)
+    }
+}
+
+impl<B> TwoBitFile<B> where B: AsRef<[u8]> {
+    pub fn from_bytes(data: B) -> Result<TwoBitFile<B>, FormatError> {
+        let mut twobit = TwoBitFile::<B> { data, big_endian: false, records: Vec::new() };
+        let signature = twobit.u32_at(0)?;
+        if signature == SIGNATURE.swap_bytes() {
+            twobit.big_endian = true;
+        } else if signature != SIGNATURE {
+            return Err(FormatError::Malformed(String::from("not a .2bit file")));
+        }
+        let version = twobit.u32_at(4)?;
+        if version > 1 {
+            return Err(FormatError::Malformed(format!("unsupported .2bit version {}", version)));
+        }
+        let count = twobit.u32_at(8)? as usize;
+        let mut pos = 16;
+        for _ in 0..count {
+            let name_size = twobit.bytes(pos, 1)?[0] as usize;
+            let name = String::from_utf8_lossy(twobit.bytes(pos+1, name_size)?).into_owned();
+            pos += 1 + name_size;
+            let offset = if version == 1 {
+                let offset = twobit.u64_at(pos)? as usize;
+                pos += 8;
+                offset
+            } else {
+                let offset = twobit.u32_at(pos)? as usize;
+                pos += 4;
+                offset
+            };
+            twobit.records.push((name, offset));
+        }
+        Ok(twobit)
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.records.iter().map(|r| &r.0[..]).collect()
+    }
+
+    pub fn length(&self, name: &str) -> Result<usize, FormatError> {
+        Ok(self.header(name)?.dna_size)
+    }
+
+    /// Fetch bases `start..end` of `name`, with N-blocks decoded as `N`.
+    pub fn sequence(&self, name: &str, start: usize, end: usize)
+                    -> Result<Sequence<DegenerateNucleotide>, FormatError> {
+        let header = self.header(name)?;
+        self.check_range(&header, start, end)?;
+        self.decode(&header, start, end)
+    }
+
+    /// Fetch bases `start..end` of `name`, failing if the range overlaps an
+    /// N-block.
+    pub fn nucleotides(&self, name: &str, start: usize, end: usize)
+                       -> Result<Sequence<Nucleotide>, FormatError> {
+        let header = self.header(name)?;
+        self.check_range(&header, start, end)?;
+        if let Some(&(n_start, _)) = self.overlapping(header.n_blocks, start, end)?.first() {
+            return Err(FormatError::Sequence(
+                SequenceError::InvalidSymbol { symbol: 'N', offset: n_start }));
+        }
+        let mut seq = Sequence::<Nucleotide>::new();
+        for i in start..end {
+            seq.push(match self.base(&header, i)? {
+                DegenerateNucleotide::A => Nucleotide::A,
+                DegenerateNucleotide::C => Nucleotide::C,
+                DegenerateNucleotide::G => Nucleotide::G,
+                _ => Nucleotide::T,
+            });
+        }
+        Ok(seq)
+    }
+
+    /// Fetch bases `start..end` of `name` along with the N-blocks and
+    /// soft-masked (lower-case) intervals that overlap them.
+    pub fn region(&self, name: &str, start: usize, end: usize)
+                  -> Result<TwoBitRegion, FormatError> {
+        let header = self.header(name)?;
+        self.check_range(&header, start, end)?;
+        Ok(TwoBitRegion {
+            sequence: self.decode(&header, start, end)?,
+            n_blocks: self.overlapping(header.n_blocks, start, end)?,
+            soft_masked: self.overlapping(header.mask_blocks, start, end)?,
+        })
+    }
+
+    fn bytes(&self, pos: usize, len: usize) -> Result<&[u8], FormatError> {
+        let data = self.data.as_ref();
+        match pos.checked_add(len) {
+            Some(end) if end <= data.len() => Ok(&data[pos..end]),
+            _ => Err(FormatError::Malformed(String::from("unexpected end of file"))),
+        }
+    }
+
+    fn u32_at(&self, pos: usize) -> Result<u32, FormatError> {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(self.bytes(pos, 4)?);
+        Ok(if self.big_endian { u32::from_be_bytes(buf) } else { u32::from_le_bytes(buf) })
+    }
+
+    fn u64_at(&self, pos: usize) -> Result<u64, FormatError> {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(self.bytes(pos, 8)?);
+        Ok(if self.big_endian { u64::from_be_bytes(buf) } else { u64::from_le_bytes(buf) })
+    }
+
+    fn header(&self, name: &str) -> Result<RecordHeader, FormatError> {
+        let offset = match self.records.iter().find(|r| r.0 == name) {
+            Some(&(_, offset)) => offset,
+            None => return Err(FormatError::UnknownRecord(String::from(name))),
+        };
+        let dna_size = self.u32_at(offset)? as usize;
+        let n_blocks = offset + 4;
+        let mask_blocks = n_blocks + 4 + 8*(self.u32_at(n_blocks)? as usize);
+        // Skip the mask arrays and the reserved word.
+        let packed_dna = mask_blocks + 4 + 8*(self.u32_at(mask_blocks)? as usize) + 4;
+        self.bytes(packed_dna, dna_size.div_ceil(4))?;
+        Ok(RecordHeader { dna_size, n_blocks, mask_blocks, packed_dna })
+    }
+
+    fn check_range(&self, header: &RecordHeader, start: usize, end: usize)
+                   -> Result<(), FormatError> {
+        if start > end || end > header.dna_size {
+            return Err(FormatError::Sequence(
+                SequenceError::IndexOutOfBounds { index: end.max(start),
+                                                  length: header.dna_size }));
+        }
+        Ok(())
+    }
+
+    fn base(&self, header: &RecordHeader, index: usize) -> Result<DegenerateNucleotide, FormatError> {
+        let byte = self.bytes(header.packed_dna + index/4, 1)?[0];
+        Ok(BASES[((byte >> (6 - 2*(index % 4))) & 0b11) as usize])
+    }
+
+    fn decode(&self, header: &RecordHeader, start: usize, end: usize)
+              -> Result<Sequence<DegenerateNucleotide>, FormatError> {
+        let n_blocks = self.overlapping(header.n_blocks, start, end)?;
+        let mut blocks = n_blocks.iter().peekable();
+        let mut seq = Sequence::<DegenerateNucleotide>::new();
+        for i in 0..end-start {
+            while blocks.peek().map(|b| b.1 <= i).unwrap_or(false) {
+                blocks.next();
+            }
+            match blocks.peek() {
+                Some(&&(block_start, _)) if block_start <= i => seq.push(DegenerateNucleotide::N),
+                _ => seq.push(self.base(header, start + i)?),
+            }
+        }
+        Ok(seq)
+    }
+
+    /// Blocks of the list at `pos` that overlap `start..end`, clipped and
+    /// made relative to `start`.
+    fn overlapping(&self, pos: usize, start: usize, end: usize)
+                   -> Result<Vec<(usize, usize)>, FormatError> {
+        let count = self.u32_at(pos)? as usize;
+        let starts = pos + 4;
+        let sizes = count.checked_mul(4).and_then(|size| starts.checked_add(size))
+            .ok_or_else(|| FormatError::Malformed(String::from("block list too long")))?;
+        let block = |i: usize| -> Result<(usize, usize), FormatError> {
+            let block_start = self.u32_at(starts + 4*i)? as usize;
+            let block_end = block_start.checked_add(self.u32_at(sizes + 4*i)? as usize)
+                .ok_or_else(|| FormatError::Malformed(String::from("block too long")))?;
+            Ok((block_start, block_end))
+        };
+        // Blocks are sorted and disjoint, so find the first that ends after
+        // `start` by binary search and walk forward from there.
+        let (mut lo, mut hi) = (0, count);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if block(mid)?.1 <= start { lo = mid + 1; } else { hi = mid; }
+        }
+        let mut blocks = Vec::new();
+        for i in lo..count {
+            let (block_start, block_end) = block(i)?;
+            if block_start >= end {
+                break;
+            }
+            // Only a list out of order can hold a block ending before `start`.
+            let clipped_end = block_end.min(end).checked_sub(start)
+                .ok_or_else(|| FormatError::Malformed(String::from("blocks out of order")))?;
+            blocks.push((block_start.max(start) - start, clipped_end));
+        }
+        Ok(blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::TwoBitFile;
+    use sequence::error::SequenceError;
+    use io::error::FormatError;
+
+    /// Encode records in .2bit format, treating lower-case bases as soft-masked
+    /// and N as unknown.
+    fn encode(records: &[(&str, &str)]) -> Vec<u8> {
+        fn push32(out: &mut Vec<u8>, value: usize) {
+            out.extend_from_slice(&(value as u32).to_le_bytes());
+        }
+        fn blocks(seq: &str, pred: &dyn Fn(char) -> bool) -> Vec<(usize, usize)> {
+            let mut blocks: Vec<(usize, usize)> = Vec::new();
+            for (i, ch) in seq.chars().enumerate() {
+                if pred(ch) {
+                    match blocks.last_mut() {
+                        Some(block) if block.0 + block.1 == i => block.1 += 1,
+                        _ => blocks.push((i, 1)),
+                    }
+                }
+            }
+            blocks
+        }
+        let mut out = Vec::new();
+        push32(&mut out, 0x1A41_2743);
+        push32(&mut out, 0);
+        push32(&mut out, records.len());
+        push32(&mut out, 0);
+        let index_size: usize = records.iter().map(|r| 1 + r.0.len() + 4).sum();
+        let mut bodies = Vec::new();
+        for &(name, seq) in records.iter() {
+            out.push(name.len() as u8);
+            out.extend_from_slice(name.as_bytes());
+            push32(&mut out, 16 + index_size + bodies.len());
+            push32(&mut bodies, seq.len());
+            let unknown = |ch: char| ch == 'N' || ch == 'n';
+            let masked = |ch: char| ch.is_lowercase();
+            for pred in [&unknown as &dyn Fn(char) -> bool, &masked].iter() {
+                let blocks = blocks(seq, *pred);
+                push32(&mut bodies, blocks.len());
+                for b in blocks.iter() { push32(&mut bodies, b.0); }
+                for b in blocks.iter() { push32(&mut bodies, b.1); }
+            }
+            push32(&mut bodies, 0);
+            let codes: Vec<u8> = seq.to_uppercase().bytes().map(|b| match b {
+                b'T' | b'N' => 0, b'C' => 1, b'A' => 2, _ => 3 }).collect();
+            for chunk in codes.chunks(4) {
+                let mut byte = 0u8;
+                for (i, code) in chunk.iter().enumerate() {
+                    byte |= code << (6 - 2*i);
+                }
+                bodies.push(byte);
+            }
+        }
+        out.extend(bodies);
+        out
+    }
+
+    #[test]
+    fn ranges() {
+        let twobit = TwoBitFile::from_bytes(encode(&[("chrA", "ACGTacgtNNNNGATTACA"),
+                                                     ("chrB", "GGGCC")])).unwrap();
+        assert_eq!(twobit.names(), vec!["chrA", "chrB"]);
+        assert_eq!(twobit.length("chrA").unwrap(), 19);
+        assert_eq!(twobit.sequence("chrA", 0, 19).unwrap().to_string(), "ACGTACGTNNNNGATTACA");
+        assert_eq!(twobit.sequence("chrA", 10, 15).unwrap().to_string(), "NNGAT");
+        assert_eq!(twobit.nucleotides("chrA", 13, 19).unwrap().to_string(), "ATTACA");
+        assert_eq!(twobit.nucleotides("chrB", 1, 4).unwrap().to_string(), "GGC");
+        let region = twobit.region("chrA", 6, 14).unwrap();
+        assert_eq!(region.sequence.to_string(), "GTNNNNGA");
+        assert_eq!(region.soft_masked, vec![(0, 2)]);
+        assert_eq!(region.n_blocks, vec![(2, 6)]);
+    }
+
+    #[test]
+    fn bad_requests() {
+        let twobit = TwoBitFile::from_bytes(encode(&[("chrA", "ACGTNNAC")])).unwrap();
+        match twobit.nucleotides("chrA", 2, 8) {
+            Err(FormatError::Sequence(e)) =>
+                assert_eq!(e, SequenceError::InvalidSymbol { symbol: 'N', offset: 2 }),
+            other => panic!("expected an N-block error, got {:?}", other),
+        }
+        match twobit.sequence("chrA", 2, 9) {
+            Err(FormatError::Sequence(SequenceError::IndexOutOfBounds { .. })) => (),
+            other => panic!("expected a range error, got {:?}", other),
+        }
+        match twobit.sequence("chrZ", 0, 1) {
+            Err(FormatError::UnknownRecord(name)) => assert_eq!(name, "chrZ"),
+            other => panic!("expected an unknown-record error, got {:?}", other),
+        }
+        assert!(TwoBitFile::from_bytes(vec![0u8; 16]).is_err());
+
+        // A version 1 index whose record offset runs off the address space.
+        let mut data = Vec::new();
+        for word in [0x1A41_2743u32, 1, 1, 0].iter() {
+            data.extend_from_slice(&word.to_le_bytes());
+        }
+        data.push(1);
+        data.push(b'x');
+        data.extend_from_slice(&(u64::MAX - 2).to_le_bytes());
+        let twobit = TwoBitFile::from_bytes(data).unwrap();
+        match twobit.length("x") {
+            Err(FormatError::Malformed(_)) => (),
+            other => panic!("expected a malformed-file error, got {:?}", other),
+        }
+
+        // N blocks listed out of order, the last before the range asked for.
+        let mut data = encode(&[("chrA", "ACGNANANA")]);
+        let starts: Vec<u8> = [3u32, 5, 7].iter().flat_map(|s| s.to_le_bytes()).collect();
+        let at = data.windows(12).position(|w| w == &starts[..]).unwrap();
+        data[at + 8] = 0;
+        let twobit = TwoBitFile::from_bytes(data).unwrap();
+        match twobit.sequence("chrA", 2, 9) {
+            Err(FormatError::Malformed(_)) => (),
+            other => panic!("expected a malformed-file error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn open() {
+        let path = env::temp_dir().join(format!("constructor-twobit-{}.2bit", ::std::process::id()));
+        fs::write(&path, encode(&[("chrM", "GATCACAGGTCT")])).unwrap();
+        let twobit = TwoBitFile::open(&path).unwrap();
+        assert_eq!(twobit.sequence("chrM", 4, 8).unwrap().to_string(), "ACAG");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,3 +1,28 @@
+extern crate memmap2;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+
 pub mod sequence;
+pub mod io;
+pub mod align;
+pub mod variants;
+pub mod diff;
+pub mod search;
+pub mod digest;
+pub mod orf;
+pub mod synthesis;
+pub mod library;
+pub mod degenerate_codon;
+pub mod design;
+pub mod registry;
+pub mod map;
+pub mod view;
+pub mod annotate;
 pub use sequence::sequence::Sequence;
 pub use sequence::nucleotide::Nucleotide;
+pub use sequence::error::SequenceError;
+pub use sequence::strand::Strand;
+pub use sequence::feature::{AnnotatedSequence, Feature, FeatureKind, Topology};
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+
+use sequence::sequence::Sequence;
+use sequence::nucleotide::Nucleotide;
+use sequence::degenerate_nucleotide::DegenerateNucleotide;
+use sequence::codon::Codon;
+use sequence::amino_acid::AminoAcid;
+use sequence::genetic_code::CodonTable;
+
+/// Every concrete sequence a degenerate sequence stands for, in order with
+/// the last position changing fastest.
+pub struct Variants {
+    choices: Vec<&'static [Nucleotide]>,
+    next: Option<Vec<usize>>,
+}
+
+impl Iterator for Variants {
+    type Item = Sequence<Nucleotide>;
+
+    fn next(&mut self) -> Option<Sequence<Nucleotide>> {
+        let digits = self.next.take()?;
+        let mut variant = Sequence::<Nucleotide>::new();
+        for (choice, &digit) in self.choices.iter().zip(digits.iter()) {
+            variant.push(choice[digit]);
+        }
+        // Advance like an odometer; running off the front ends the iteration.
+        let mut digits = digits;
+        for i in (0..digits.len()).rev() {
+            digits[i] += 1;
+            if digits[i] < self.choices[i].len() {
+                self.next = Some(digits);
+                break;
+            }
+            digits[i] = 0;
+        }
+        Some(variant)
+    }
+}
+
+/// Random variants drawn with replacement, as from an equimolar synthesis.
+/// The same seed always gives the same samples.
+pub struct Samples {
+    choices: Vec<&'static [Nucleotide]>,
+    rng: SplitMix64,
+}
+
+impl Iterator for Samples {
+    type Item = Sequence<Nucleotide>;
+
+    fn next(&mut self) -> Option<Sequence<Nucleotide>> {
+        let mut variant = Sequence::<Nucleotide>::new();
+        for choice in self.choices.iter() {
+            variant.push(choice[self.rng.below(choice.len())]);
+        }
+        Some(variant)
+    }
+}
+
+/// A small, fast generator whose output is fixed for a given seed, so
+/// samples are reproducible without depending on an external crate's
+/// algorithm choices.
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`, by rejection so no value is favoured.
+    fn below(&mut self, n: usize) -> usize {
+        let n = n as u64;
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return (value % n) as usize;
+            }
+        }
+    }
+}
+
+impl Sequence<DegenerateNucleotide> {
+    /// Lazily enumerate every concrete sequence. An empty library yields
+    /// one empty sequence.
+    pub fn variants(&self) -> Variants {
+        Variants { choices: self.iter().map(|dnt| dnt.expand()).collect(),
+                   next: Some(vec![0; self.len()]) }
+    }
+
+    /// An endless stream of random variants from the given seed.
+    pub fn samples(&self, seed: u64) -> Samples {
+        Samples { choices: self.iter().map(|dnt| dnt.expand()).collect(),
+                  rng: SplitMix64 { state: seed } }
+    }
+}
+
+/// How much of a library a number of transformants is expected to cover,
+/// assuming each transformant carries one variant drawn at random.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Coverage {
+    /// Expected number of distinct variants seen.
+    pub distinct: f64,
+    /// `distinct` as a fraction of the library's diversity.
+    pub fraction: f64,
+    /// Probability that every variant is seen at least once.
+    pub completeness: f64,
+}
+
+/// Distinct variants that are equally likely, grouped as `(probability,
+/// count)`; the whole of a DNA library is one such class.
+fn coverage(classes: &[(f64, f64)], transformants: f64) -> Coverage {
+    let diversity: f64 = classes.iter().map(|&(_, count)| count).sum();
+    let mut distinct = 0.0;
+    let mut log_complete = 0.0;
+    for &(p, count) in classes.iter() {
+        // (1 - p)^T, computed in logs to survive tiny p and huge T.
+        let missed = (transformants * (-p).ln_1p()).exp();
+        distinct += count * (1.0 - missed);
+        log_complete += count * (-missed).ln_1p();
+    }
+    Coverage { distinct, fraction: if diversity > 0.0 { distinct / diversity } else { 0.0 },
+               completeness: log_complete.exp() }
+}
+
+/// Number of distinct DNA sequences. Returned as a float since libraries of
+/// a few dozen degenerate positions overflow any integer type.
+pub fn dna_diversity(library: &Sequence<DegenerateNucleotide>) -> f64 {
+    library.iter().map(|dnt| dnt.expand().len() as f64).product()
+}
+
+pub fn dna_coverage(library: &Sequence<DegenerateNucleotide>, transformants: f64) -> Coverage {
+    let diversity = dna_diversity(library);
+    coverage(&[(1.0 / diversity, diversity)], transformants)
+}
+
+/// For each complete codon, how many of its concrete codons encode each
+/// amino acid or stop.
+fn codon_counts<C>(library: &Sequence<DegenerateNucleotide>, code: &C)
+                   -> Vec<HashMap<AminoAcid, usize>> where C: CodonTable + ?Sized {
+    let positions: Vec<&'static [Nucleotide]> = library.iter().map(|dnt| dnt.expand()).collect();
+    positions.chunks(3).filter(|codon| codon.len() == 3).map(|codon| {
+        let mut counts = HashMap::new();
+        for &first in codon[0].iter() {
+            for &second in codon[1].iter() {
+                for &third in codon[2].iter() {
+                    let amino_acid = code.translate(&Codon::from_slice(&[first, second, third]));
+                    *counts.entry(amino_acid).or_insert(0) += 1;
+                }
+            }
+        }
+        counts
+    }).collect()
+}
+
+/// Number of distinct proteins encoded in frame from the first base,
+/// counting a stop as one more residue. Trailing bases that do not fill a
+/// codon are ignored.
+pub fn protein_diversity<C>(library: &Sequence<DegenerateNucleotide>, code: &C) -> f64
+    where C: CodonTable + ?Sized {
+    codon_counts(library, code).iter().map(|counts| counts.len() as f64).product()
+}
+
+/// Fraction of DNA variants with no stop codon in frame.
+pub fn stop_free_fraction<C>(library: &Sequence<DegenerateNucleotide>, code: &C) -> f64
+    where C: CodonTable + ?Sized {
+    codon_counts(library, code).iter().map(|counts| {
+        let total: usize = counts.values().sum();
+        let stops = counts.get(&AminoAcid::STOP).cloned().unwrap_or(0);
+        (total - stops) as f64 / total as f64
+    }).product()
+}
+
+/// Primes up to 64, the most codons one amino acid can take at a position.
+const PRIMES: [usize; 18] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61];
+
+fn factorise(mut n: usize) -> [u16; 18] {
+    let mut exponents = [0; 18];
+    for (i, &p) in PRIMES.iter().enumerate() {
+        while n.is_multiple_of(p) {
+            n /= p;
+            exponents[i] += 1;
+        }
+    }
+    exponents
+}
+
+/// Protein-level coverage. Proteins are not equally likely when amino acids
+/// take different numbers of codons, so they are grouped by the product of
+/// their per-position codon counts, keyed exactly by its prime factors, and
+/// each group counted at its own probability.
+pub fn protein_coverage<C>(library: &Sequence<DegenerateNucleotide>, code: &C,
+                           transformants: f64) -> Coverage where C: CodonTable + ?Sized {
+    let mut classes: HashMap<[u16; 18], f64> = HashMap::new();
+    classes.insert([0; 18], 1.0);
+    let mut log_total = 0.0;
+    for counts in codon_counts(library, code).iter() {
+        log_total += (counts.values().sum::<usize>() as f64).ln();
+        let mut multiplicities: HashMap<usize, f64> = HashMap::new();
+        for &m in counts.values() {
+            *multiplicities.entry(m).or_insert(0.0) += 1.0;
+        }
+        let mut next = HashMap::new();
+        for (key, &count) in classes.iter() {
+            for (&m, &residues) in multiplicities.iter() {
+                let mut product = *key;
+                for (e, f) in product.iter_mut().zip(factorise(m).iter()) {
+                    *e += f;
+                }
+                *next.entry(product).or_insert(0.0) += count * residues;
+            }
+        }
+        classes = next;
+    }
+    let classes: Vec<(f64, f64)> = classes.into_iter().map(|(key, count)| {
+        let log_product: f64 = key.iter().zip(PRIMES.iter())
+            .map(|(&e, &p)| e as f64 * (p as f64).ln()).sum();
+        ((log_product - log_total).exp(), count)
+    }).collect();
+    coverage(&classes, transformants)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dna_diversity, dna_coverage, protein_diversity, protein_coverage,
+                stop_free_fraction};
+    use sequence::sequence::Sequence;
+    use sequence::degenerate_nucleotide::DegenerateNucleotide;
+    use sequence::genetic_code::GeneticCode;
+
+    fn library(s: &str) -> Sequence<DegenerateNucleotide> {
+        Sequence::<DegenerateNucleotide>::from_str(s).unwrap()
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn enumerate_and_sample() {
+        let lib = library("ARY");
+        let all: Vec<String> = lib.variants().map(|v| v.to_string()).collect();
+        assert_eq!(all, vec!["AAC", "AAT", "AGC", "AGT"]);
+        assert_eq!(library("").variants().count(), 1);
+        let first: Vec<String> = lib.samples(7).take(20).map(|v| v.to_string()).collect();
+        let again: Vec<String> = lib.samples(7).take(20).map(|v| v.to_string()).collect();
+        assert_eq!(first, again);
+        assert!(first.iter().all(|v| all.contains(v)));
+    }
+
+    #[test]
+    fn diversity() {
+        let nnk = library("NNKNNK");
+        let code = GeneticCode::standard();
+        assert_eq!(dna_diversity(&nnk), 1024.0);
+        // NNK encodes all twenty amino acids and the amber stop.
+        assert_eq!(protein_diversity(&nnk, code), 441.0);
+        assert!(close(stop_free_fraction(&nnk, code), (31.0f64 / 32.0).powi(2)));
+    }
+
+    #[test]
+    fn coverage() {
+        let lib = library("NNN");
+        let cover = dna_coverage(&lib, 192.0);
+        assert!(close(cover.distinct, 64.0 * (1.0 - (63.0f64 / 64.0).powi(192))));
+        assert!(cover.completeness > 0.0 && cover.completeness < 1.0);
+        assert!(dna_coverage(&lib, 1e6).completeness > 0.999);
+        // Codon bias makes protein-level coverage differ from the DNA level.
+        let code = GeneticCode::standard();
+        let protein = protein_coverage(&library("NNK"), code, 32.0);
+        let total: f64 = protein.distinct / protein.fraction;
+        assert!(close(total, 21.0));
+        assert!(protein.fraction > dna_coverage(&library("NNK"), 32.0).fraction);
+    }
+}
@@ -1,26 +1,54 @@
 extern crate argparse;
 extern crate constructor;
 
+mod cli;
 
-use constructor::Sequence;
-use constructor::Nucleotide;
-//use amino_acid::AminoAcid;
+use std::process;
 
-struct CommandLineArgs {
-    nts: String,
-}
+use argparse::{ArgumentParser, Store, List};
+
+use cli::CliError;
+use cli::commands;
 
 fn main() {
-    let mut args = CommandLineArgs{nts: String::new()};
+    let mut command = String::new();
+    let mut args: Vec<String> = Vec::new();
     {
-        let mut ap = argparse::ArgumentParser::new();
-        ap.set_description("test description");
-        ap.refer(&mut args.nts).required()
-            .add_option(&["--nts"],argparse::Store,
-                        "nucleotide input");
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Tools for DNA constructs. Commands: revcomp, translate, orfs, \
+                            digest, stats, search, convert, parts and annotate; run a command with --help for its options. \
+                            Records are read and written one at a time, so commands work as \
+                            filters in pipelines. Exits with 0 on success, 1 when input cannot \
+                            be read or output written, and 2 on usage errors.");
+        ap.refer(&mut command).required()
+            .add_argument("command", Store, "Command to run");
+        ap.refer(&mut args)
+            .add_argument("arguments", List, "Arguments for the command");
+        ap.stop_on_first_argument(true);
         ap.parse_args_or_exit();
     }
-    let nts = Sequence::<Nucleotide>::from_str(&args.nts).unwrap();
-    println!("reverse complement is {}",nts.reverse_complement().to_string());
-    println!("translation is {}",nts.translate().unwrap().to_string());
+
+    args.insert(0, format!("constructor {}", command));
+    let result = match &command[..] {
+        "revcomp" => commands::revcomp(args),
+        "translate" => commands::translate(args),
+        "orfs" => commands::orfs(args),
+        "digest" => commands::digest(args),
+        "stats" => commands::stats(args),
+        "search" => commands::search(args),
+        "convert" => commands::convert(args),
+        "parts" => commands::parts(args),
+        "annotate" => commands::annotate(args),
+        other => Err(CliError::Usage(format!("unknown command {}", other))),
+    };
+    if let Err(e) = result {
+        if e.is_broken_pipe() {
+            return;
+        }
+        // The argument parser has already printed its own message.
+        if !matches!(e, CliError::Parse(_)) {
+            eprintln!("constructor: {}", e);
+        }
+        process::exit(e.exit_code());
+    }
 }
@@ -0,0 +1,568 @@
+use std::f64::consts::PI;
+use std::fmt::Write;
+
+use sequence::nucleotide::{Nucleotide, Complement};
+use sequence::string_io::StringIO;
+use sequence::strand::Strand;
+use sequence::feature::{AnnotatedSequence, Feature, FeatureKind, Topology};
+use sequence::genetic_code::GeneticCode;
+use digest::{Enzyme, cut_sites};
+
+const FONT: &str = "Helvetica, Arial, sans-serif";
+const MONOSPACE: &str = "Menlo, Consolas, monospace";
+const FONT_SIZE: f64 = 11.0;
+/// Width of a character as a fraction of the font size, for estimating
+/// how much room a label needs.
+const CHAR_WIDTH: f64 = 0.6;
+const LINE_HEIGHT: f64 = 14.0;
+const LANE_HEIGHT: f64 = 16.0;
+
+fn colour(kind: &FeatureKind) -> &'static str {
+    match *kind {
+        FeatureKind::Cds => "#f4a261",
+        FeatureKind::Promoter => "#2a9d8f",
+        FeatureKind::Rbs => "#e9c46a",
+        FeatureKind::Terminator => "#e76f51",
+        FeatureKind::Origin => "#8ab17d",
+        FeatureKind::PrimerBind => "#9b5de5",
+        FeatureKind::ProteinBind => "#577590",
+        FeatureKind::MiscFeature | FeatureKind::Other(_) => "#adb5bd",
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn text_width(text: &str, size: f64) -> f64 {
+    text.chars().count() as f64 * size * CHAR_WIDTH
+}
+
+/// A round tick interval giving at most about `target` ticks over `length`.
+fn tick_step(length: usize, target: usize) -> usize {
+    let raw = (length.max(1) as f64 / target as f64).max(1.0);
+    let magnitude = 10f64.powi(raw.log10().floor() as i32);
+    let step = [1.0, 2.0, 5.0, 10.0].iter().map(|m| m * magnitude)
+        .find(|&step| step >= raw).unwrap();
+    step as usize
+}
+
+/// Move sorted positions apart so that neighbours are at least `gap` apart,
+/// keeping their order and, where there is room, staying within
+/// `min..max`.
+fn spread(desired: &[f64], gap: f64, min: f64, max: f64) -> Vec<f64> {
+    let mut placed: Vec<f64> = Vec::with_capacity(desired.len());
+    for &position in desired.iter() {
+        let floor = placed.last().map_or(min, |&last| last + gap);
+        placed.push(position.max(floor));
+    }
+    let mut ceiling = max;
+    for position in placed.iter_mut().rev() {
+        *position = position.min(ceiling);
+        ceiling = *position - gap;
+    }
+    placed
+}
+
+/// Give each interval the lowest lane in which it overlaps no interval
+/// placed before it. On a circle of `period` an interval may run past the
+/// period and overlaps intervals at the start.
+pub(crate) fn lanes(extents: &[(f64, f64)], period: Option<f64>) -> Vec<usize> {
+    let overlaps = |a: (f64, f64), b: (f64, f64)| {
+        let shifts = match period {
+            Some(period) => vec![-period, 0.0, period],
+            None => vec![0.0],
+        };
+        shifts.iter().any(|shift| a.0 < b.1 + shift && b.0 + shift < a.1)
+    };
+    let mut placed: Vec<Vec<(f64, f64)>> = Vec::new();
+    extents.iter().map(|&extent| {
+        let lane = placed.iter()
+            .position(|lane| lane.iter().all(|&other| !overlaps(extent, other)))
+            .unwrap_or(placed.len());
+        if lane == placed.len() {
+            placed.push(Vec::new());
+        }
+        placed[lane].push(extent);
+        lane
+    }).collect()
+}
+
+/// An SVG document under construction, growing its bounds to fit what is
+/// drawn.
+struct Canvas {
+    body: String,
+    bounds: (f64, f64, f64, f64),
+}
+
+impl Canvas {
+    fn new(width: f64, height: f64) -> Canvas {
+        Canvas { body: String::new(), bounds: (0.0, 0.0, width, height) }
+    }
+
+    fn include(&mut self, x: f64, y: f64) {
+        self.bounds.0 = self.bounds.0.min(x);
+        self.bounds.1 = self.bounds.1.min(y);
+        self.bounds.2 = self.bounds.2.max(x);
+        self.bounds.3 = self.bounds.3.max(y);
+    }
+
+    fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, stroke: &str, width: f64) {
+        writeln!(self.body, "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" \
+                             stroke=\"{}\" stroke-width=\"{}\"/>",
+                 x1, y1, x2, y2, stroke, width).unwrap();
+    }
+
+    /// Text anchored at `start`, `middle` or `end`, with its baseline at `y`.
+    fn text(&mut self, x: f64, y: f64, anchor: &str, size: f64, text: &str) {
+        let width = text_width(text, size);
+        let left = match anchor {
+            "start" => x,
+            "end" => x - width,
+            _ => x - width / 2.0,
+        };
+        self.include(left - 2.0, y - size);
+        self.include(left + width + 2.0, y + size / 3.0);
+        writeln!(self.body, "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"{}\" font-size=\"{}\">{}\
+                             </text>", x, y, anchor, size, escape(text)).unwrap();
+    }
+
+    fn finish(self, title: &str) -> String {
+        let (x0, y0, x1, y1) = self.bounds;
+        let (x0, y0) = ((x0 - 10.0).floor(), (y0 - 10.0).floor());
+        let (width, height) = ((x1 + 10.0).ceil() - x0, (y1 + 10.0).ceil() - y0);
+        format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+                 viewBox=\"{x} {y} {w} {h}\" font-family=\"{font}\">\n<title>{title}</title>\n\
+                 <rect x=\"{x}\" y=\"{y}\" width=\"{w}\" height=\"{h}\" fill=\"white\"/>\n\
+                 {body}</svg>\n",
+                x = x0, y = y0, w = width, h = height, font = FONT, title = escape(title),
+                body = self.body)
+    }
+}
+
+/// A label to be placed clear of its neighbours, pointing at `anchor`.
+struct Label {
+    text: String,
+    anchor: f64,
+}
+
+/// Draws an annotated sequence as an SVG map: features as arrows, cut sites
+/// of chosen enzymes, a scale and labels laid out so they do not overlap.
+pub struct Map<'a> {
+    record: &'a AnnotatedSequence,
+    enzymes: Vec<&'static Enzyme>,
+    width: f64,
+}
+
+impl<'a> Map<'a> {
+    pub fn new(record: &'a AnnotatedSequence) -> Map<'a> {
+        Map { record, enzymes: Vec::new(), width: 800.0 }
+    }
+
+    /// Mark the cut sites of these enzymes.
+    pub fn with_enzymes(mut self, enzymes: &[&'static Enzyme]) -> Map<'a> {
+        self.enzymes = enzymes.to_vec();
+        self
+    }
+
+    /// Set the nominal width of the drawing in pixels; labels may widen it.
+    pub fn with_width(mut self, width: f64) -> Map<'a> {
+        self.width = width;
+        self
+    }
+
+    /// A circular map for a circular sequence and a linear one otherwise.
+    pub fn to_svg(&self) -> String {
+        match self.record.topology {
+            Topology::Circular => self.circular_svg(),
+            Topology::Linear => self.linear_svg(),
+        }
+    }
+
+    /// Every cut site as a position and the names of the enzymes cutting
+    /// there.
+    fn cut_sites(&self) -> Vec<(usize, String)> {
+        cut_sites(&self.record.sequence, self.record.topology, &self.enzymes).into_iter()
+            .map(|(cut, names)| (cut, names.join(", "))).collect()
+    }
+
+    fn subtitle(&self) -> String {
+        format!("{} bp", self.record.sequence.len())
+    }
+
+    /// The map drawn round a circle, starting at the top and running
+    /// clockwise.
+    pub fn circular_svg(&self) -> String {
+        let record = self.record;
+        let length = record.sequence.len().max(1) as f64;
+        let size = self.width;
+        let (cx, cy) = (size / 2.0, size / 2.0);
+        let radius = size * 0.28;
+        let angle = |position: f64| 2.0 * PI * position / length;
+        let point = |r: f64, a: f64| (cx + r * a.sin(), cy - r * a.cos());
+        let mut canvas = Canvas::new(size, size);
+
+        writeln!(canvas.body, "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"none\" \
+                               stroke=\"#333\" stroke-width=\"2\"/>", cx, cy, radius).unwrap();
+        let step = tick_step(record.sequence.len(), 12);
+        let mut position = 0;
+        while position < record.sequence.len() {
+            let a = angle(position as f64);
+            let (x1, y1) = point(radius, a);
+            let (x2, y2) = point(radius - 6.0, a);
+            canvas.line(x1, y1, x2, y2, "#333", 1.0);
+            let (x, y) = point(radius - 18.0, a);
+            canvas.text(x, y + 3.0, "middle", FONT_SIZE - 2.0, &position.to_string());
+            position += step;
+        }
+
+        // Features sit in lanes outside the backbone, innermost first.
+        let extents: Vec<(f64, f64)> = record.features.iter()
+            .map(|f| (f.start as f64, f.end as f64)).collect();
+        let lane_of = lanes(&extents, Some(length));
+        let mut labels = Vec::new();
+        let mut outermost = radius;
+        for (feature, &lane) in record.features.iter().zip(lane_of.iter()) {
+            let inner = radius + 6.0 + lane as f64 * LANE_HEIGHT;
+            let outer = inner + LANE_HEIGHT - 4.0;
+            outermost = outermost.max(outer);
+            let (a0, a1) = (angle(feature.start as f64), angle(feature.end as f64));
+            let head = (8.0 / outer).min((a1 - a0) / 2.0);
+            let arc = |r: f64, from: f64, to: f64, sweep: u8| {
+                let (x, y) = point(r, to);
+                let large = if (to - from).abs() > PI { 1 } else { 0 };
+                format!("A {r:.1} {r:.1} 0 {large} {sweep} {x:.1} {y:.1}", r = r, large = large,
+                        sweep = sweep, x = x, y = y)
+            };
+            let at = |r: f64, a: f64| {
+                let (x, y) = point(r, a);
+                format!("{:.1} {:.1}", x, y)
+            };
+            let middle = (inner + outer) / 2.0;
+            let path = match feature.strand {
+                Strand::Forward => format!("M {} {} L {} L {} {} Z", at(outer, a0),
+                                           arc(outer, a0, a1 - head, 1), at(middle, a1),
+                                           at(inner, a1 - head), arc(inner, a1 - head, a0, 0)),
+                Strand::Reverse => format!("M {} L {} {} L {} {} Z", at(middle, a0),
+                                           at(outer, a0 + head), arc(outer, a0 + head, a1, 1),
+                                           at(inner, a1), arc(inner, a1, a0 + head, 0)),
+            };
+            writeln!(canvas.body, "<path d=\"{}\" fill=\"{}\" stroke=\"#555\" \
+                                   stroke-width=\"0.5\"><title>{}</title></path>",
+                     path, colour(&feature.kind), escape(&feature.label)).unwrap();
+            labels.push(Label { text: feature.label.clone(),
+                                anchor: (feature.start + feature.end) as f64 / 2.0 });
+        }
+        for (cut, names) in self.cut_sites() {
+            let a = angle(cut as f64);
+            let (x1, y1) = point(radius - 4.0, a);
+            let (x2, y2) = point(radius + 4.0, a);
+            canvas.line(x1, y1, x2, y2, "#c0392b", 1.5);
+            labels.push(Label { text: format!("{} ({})", names, cut), anchor: cut as f64 });
+        }
+
+        // Labels go in two columns either side of the circle, each spread
+        // vertically so no two overlap, with a leader line to what they name.
+        let label_radius = outermost + 24.0;
+        for side in [1.0, -1.0].iter() {
+            let mut column: Vec<(f64, &Label)> = labels.iter()
+                .map(|label| (angle(label.anchor % length), label))
+                .filter(|&(a, _)| (a.sin() >= 0.0) == (*side > 0.0))
+                .map(|(a, label)| (cy - label_radius * a.cos(), label))
+                .collect();
+            column.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+            let desired: Vec<f64> = column.iter().map(|c| c.0).collect();
+            let placed = spread(&desired, LINE_HEIGHT, cy - label_radius - 20.0,
+                                cy + label_radius + 20.0);
+            for (&(_, label), &y) in column.iter().zip(placed.iter()) {
+                let a = angle(label.anchor % length);
+                let dy = (y - cy).abs().min(label_radius);
+                let x = cx + side * (label_radius * label_radius - dy * dy).sqrt().max(12.0);
+                let (x0, y0) = point(outermost + 2.0, a);
+                canvas.line(x0, y0, x, y - 4.0, "#999", 0.5);
+                let anchor = if *side > 0.0 { "start" } else { "end" };
+                canvas.text(x + side * 3.0, y, anchor, FONT_SIZE, &label.text);
+            }
+        }
+
+        canvas.text(cx, cy - 4.0, "middle", FONT_SIZE + 5.0, &record.name);
+        canvas.text(cx, cy + 14.0, "middle", FONT_SIZE, &self.subtitle());
+        canvas.finish(&record.name)
+    }
+
+    /// The map drawn along a line, with the scale on top, cut sites above it
+    /// and features in lanes below.
+    pub fn linear_svg(&self) -> String {
+        let record = self.record;
+        let length = record.sequence.len().max(1);
+        let margin = 40.0;
+        let scale = (self.width - 2.0 * margin) / length as f64;
+        let x = |position: usize| margin + position as f64 * scale;
+        let mut canvas = Canvas::new(self.width, 100.0);
+        canvas.text(margin, 20.0, "start", FONT_SIZE + 5.0,
+                    &format!("{} ({})", record.name, self.subtitle()));
+
+        // Cut site labels stack in rows above the axis.
+        let sites = self.cut_sites();
+        let extents: Vec<(f64, f64)> = sites.iter().map(|&(cut, ref names)| {
+            let width = text_width(names, FONT_SIZE) + 6.0;
+            (x(cut) - width / 2.0, x(cut) + width / 2.0)
+        }).collect();
+        let rows = lanes(&extents, None);
+        let row_count = rows.iter().max().map_or(0, |&r| r + 1);
+        let axis = 40.0 + row_count as f64 * LINE_HEIGHT + 10.0;
+        for (&(cut, ref names), &row) in sites.iter().zip(rows.iter()) {
+            let y = axis - 10.0 - row as f64 * LINE_HEIGHT;
+            canvas.line(x(cut), axis - 4.0, x(cut), y + 2.0, "#c0392b", 0.8);
+            canvas.text(x(cut), y, "middle", FONT_SIZE, names);
+        }
+
+        canvas.line(x(0), axis, x(length), axis, "#333", 2.0);
+        let step = tick_step(length, 10);
+        let mut position = 0;
+        while position <= length {
+            canvas.line(x(position), axis, x(position), axis + 5.0, "#333", 1.0);
+            canvas.text(x(position), axis + 16.0, "middle", FONT_SIZE - 2.0,
+                        &position.to_string());
+            position += step;
+        }
+
+        // A feature crossing the origin of a circular sequence is drawn as
+        // its two pieces.
+        let mut pieces: Vec<(&Feature, usize, usize)> = Vec::new();
+        for feature in record.features.iter() {
+            if feature.end > length {
+                pieces.push((feature, feature.start, length));
+                pieces.push((feature, 0, feature.end - length));
+            } else {
+                pieces.push((feature, feature.start, feature.end));
+            }
+        }
+        // A label goes inside its arrow when it fits and after it otherwise,
+        // and takes up room in the lane either way.
+        let inside: Vec<bool> = pieces.iter().map(|&(f, start, end)| {
+            text_width(&f.label, FONT_SIZE) + 8.0 < (end - start) as f64 * scale
+        }).collect();
+        let extents: Vec<(f64, f64)> = pieces.iter().zip(inside.iter())
+            .map(|(&(f, start, end), &inside)| {
+                let label = if inside { 0.0 } else { text_width(&f.label, FONT_SIZE) + 6.0 };
+                (x(start), x(end) + label + 4.0)
+            }).collect();
+        let lane_of = lanes(&extents, None);
+        let top = axis + 28.0;
+        for ((&(feature, start, end), &lane), &inside) in pieces.iter().zip(lane_of.iter())
+            .zip(inside.iter()) {
+            let y = top + lane as f64 * (LANE_HEIGHT + 6.0);
+            let (x0, x1) = (x(start), x(end));
+            let head = 8f64.min((x1 - x0) / 2.0);
+            let h = LANE_HEIGHT;
+            let points = match feature.strand {
+                Strand::Forward => format!("{:.1},{:.1} {:.1},{:.1} {:.1},{:.1} {:.1},{:.1} \
+                                            {:.1},{:.1}", x0, y, x1 - head, y, x1, y + h / 2.0,
+                                           x1 - head, y + h, x0, y + h),
+                Strand::Reverse => format!("{:.1},{:.1} {:.1},{:.1} {:.1},{:.1} {:.1},{:.1} \
+                                            {:.1},{:.1}", x0, y + h / 2.0, x0 + head, y, x1, y,
+                                           x1, y + h, x0 + head, y + h),
+            };
+            writeln!(canvas.body, "<polygon points=\"{}\" fill=\"{}\" stroke=\"#555\" \
+                                   stroke-width=\"0.5\"><title>{}</title></polygon>",
+                     points, colour(&feature.kind), escape(&feature.label)).unwrap();
+            canvas.include(x1, y + h);
+            if inside {
+                canvas.text((x0 + x1) / 2.0, y + h - 4.0, "middle", FONT_SIZE, &feature.label);
+            } else {
+                canvas.text(x1 + 4.0, y + h - 4.0, "start", FONT_SIZE, &feature.label);
+            }
+        }
+        canvas.finish(&record.name)
+    }
+
+    /// The sequence itself, `bases_per_line` to a line, with the position of
+    /// each line, the complementary strand, features and cut sites, and the
+    /// standard-code translation of each CDS under it.
+    pub fn sequence_svg(&self, bases_per_line: usize) -> String {
+        let record = self.record;
+        let bases: Vec<Nucleotide> = record.sequence.iter().cloned().collect();
+        let length = bases.len();
+        let bases_per_line = bases_per_line.max(1);
+        let char_width = 8.0;
+        let margin = 70.0;
+        let x = |column: usize| margin + column as f64 * char_width;
+        let mut canvas = Canvas::new(x(bases_per_line) + 20.0, 40.0);
+        canvas.text(10.0, 20.0, "start", FONT_SIZE + 5.0,
+                    &format!("{} ({})", record.name, self.subtitle()));
+
+        // Each residue of each CDS, as the position of its codon's middle
+        // base, its one-letter code and whether it reads forwards.
+        let mut residues: Vec<Vec<(usize, char)>> = Vec::new();
+        for feature in record.features.iter().filter(|f| f.kind == FeatureKind::Cds) {
+            let protein = record.extract(feature).translate_with(GeneticCode::standard());
+            residues.push(protein.iter().enumerate().map(|(i, aa)| {
+                let position = match feature.strand {
+                    Strand::Forward => feature.start + 3 * i + 1,
+                    Strand::Reverse => feature.end - 3 * i - 2,
+                };
+                (position % length.max(1), aa.to_char())
+            }).collect());
+        }
+        let sites = self.cut_sites();
+
+        let mut y = 50.0;
+        for line_start in (0..length).step_by(bases_per_line) {
+            let line_end = (line_start + bases_per_line).min(length);
+            let columns = line_end - line_start;
+            let strand_text = |complement: bool| -> String {
+                bases[line_start..line_end].iter()
+                    .map(|nt| if complement { nt.complement().to_char() } else { nt.to_char() })
+                    .collect()
+            };
+            let line_sites: Vec<&(usize, String)> = sites.iter()
+                .filter(|site| site.0 >= line_start && site.0 < line_end).collect();
+            if !line_sites.is_empty() {
+                let extents: Vec<(f64, f64)> = line_sites.iter().map(|site| {
+                    let left = x(site.0 - line_start);
+                    (left, left + text_width(&site.1, FONT_SIZE - 1.0) + 4.0)
+                }).collect();
+                let rows = lanes(&extents, None);
+                let row_count = rows.iter().max().unwrap() + 1;
+                for (site, &row) in line_sites.iter().zip(rows.iter()) {
+                    let left = x(site.0 - line_start);
+                    let label_y = y + (row_count - row - 1) as f64 * LINE_HEIGHT;
+                    canvas.text(left, label_y, "start", FONT_SIZE - 1.0, &site.1);
+                    canvas.line(left, label_y + 2.0, left, y + row_count as f64 * LINE_HEIGHT + 2.0,
+                                "#c0392b", 0.8);
+                }
+                y += row_count as f64 * LINE_HEIGHT;
+            }
+            y += LINE_HEIGHT;
+            canvas.text(margin - 10.0, y, "end", FONT_SIZE, &(line_start + 1).to_string());
+            for (row, complement) in [false, true].iter().enumerate() {
+                let row_y = y + row as f64 * LINE_HEIGHT;
+                writeln!(canvas.body, "<text x=\"{:.1}\" y=\"{:.1}\" font-family=\"{}\" \
+                                       font-size=\"{}\" textLength=\"{:.1}\" \
+                                       lengthAdjust=\"spacing\"{}>{}</text>",
+                         x(0), row_y, MONOSPACE, FONT_SIZE + 1.0, columns as f64 * char_width,
+                         if *complement { " fill=\"#777\"" } else { "" },
+                         strand_text(*complement)).unwrap();
+            }
+            canvas.include(x(columns), y);
+            y += LINE_HEIGHT + 6.0;
+
+            // Features overlapping the line, as bars in lanes.
+            let mut bars = Vec::new();
+            for feature in record.features.iter() {
+                let mut pieces = vec![(feature.start, feature.end.min(length))];
+                if feature.end > length {
+                    pieces.push((0, feature.end - length));
+                }
+                for (start, end) in pieces {
+                    let (from, to) = (start.max(line_start), end.min(line_end));
+                    if from < to {
+                        bars.push((feature, from - line_start, to - line_start));
+                    }
+                }
+            }
+            let extents: Vec<(f64, f64)> = bars.iter().map(|&(f, from, to)| {
+                (x(from), x(to).max(x(from) + text_width(&f.label, FONT_SIZE - 1.0) + 4.0))
+            }).collect();
+            let lane_of = lanes(&extents, None);
+            let lane_count = lane_of.iter().max().map_or(0, |&l| l + 1);
+            for (&(feature, from, to), &lane) in bars.iter().zip(lane_of.iter()) {
+                let bar_y = y + lane as f64 * (LINE_HEIGHT + 4.0);
+                writeln!(canvas.body, "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" \
+                                       height=\"4\" fill=\"{}\"/>",
+                         x(from), bar_y, (to - from) as f64 * char_width,
+                         colour(&feature.kind)).unwrap();
+                let arrow = match feature.strand {
+                    Strand::Forward => "",
+                    Strand::Reverse => "\u{2190} ",
+                };
+                canvas.text(x(from), bar_y + 14.0, "start", FONT_SIZE - 1.0,
+                            &format!("{}{}", arrow, feature.label));
+            }
+            y += lane_count as f64 * (LINE_HEIGHT + 4.0);
+
+            // Translations of CDSs, one track each.
+            for track in residues.iter() {
+                let on_line: Vec<&(usize, char)> = track.iter()
+                    .filter(|r| r.0 >= line_start && r.0 < line_end).collect();
+                if on_line.is_empty() {
+                    continue;
+                }
+                y += LINE_HEIGHT;
+                for &&(position, residue) in on_line.iter() {
+                    writeln!(canvas.body, "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" \
+                                           font-family=\"{}\" font-size=\"{}\" fill=\"#b5651d\">\
+                                           {}</text>",
+                             x(position - line_start) + char_width / 2.0, y, MONOSPACE,
+                             FONT_SIZE + 1.0, escape(&residue.to_string())).unwrap();
+                }
+            }
+            canvas.include(x(0), y);
+            y += 20.0;
+        }
+        canvas.finish(&record.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tick_step, spread, lanes, Map};
+    use sequence::sequence::Sequence;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::strand::Strand;
+    use sequence::feature::{AnnotatedSequence, Feature, FeatureKind, Topology};
+    use digest::Enzyme;
+
+    fn plasmid() -> AnnotatedSequence {
+        let seq = "GAATTCATGAAACGTTAAGGATCCTTTTTTTTTTAAAACCCGGGTTTTAAAACCCCGGGGTTTTAAAACC";
+        let seq = Sequence::<Nucleotide>::from_str(seq).unwrap();
+        let mut record = AnnotatedSequence::new("pTest & co", seq, Topology::Circular);
+        record.features.push(Feature::new(FeatureKind::Cds, "orf", 6, 18, Strand::Forward));
+        record.features.push(Feature::new(FeatureKind::Promoter, "<p>", 10, 30, Strand::Reverse));
+        record.features.push(Feature::new(FeatureKind::Origin, "ori", 60, 74, Strand::Forward));
+        record
+    }
+
+    #[test]
+    fn layout() {
+        assert_eq!((tick_step(70, 12), tick_step(5386, 10), tick_step(3, 10)), (10, 1000, 1));
+        assert_eq!(spread(&[10.0, 11.0, 12.0], 5.0, 0.0, 100.0), vec![10.0, 15.0, 20.0]);
+        assert_eq!(spread(&[95.0, 99.0], 5.0, 0.0, 100.0), vec![95.0, 100.0]);
+        assert_eq!(spread(&[98.0, 99.0], 5.0, 0.0, 100.0), vec![95.0, 100.0]);
+        assert_eq!(lanes(&[(0.0, 10.0), (5.0, 15.0), (10.0, 20.0), (2.0, 3.0)], None),
+                   vec![0, 1, 0, 1]);
+        // On a circle of 100 an interval across the origin meets one at the start.
+        assert_eq!(lanes(&[(90.0, 110.0), (5.0, 8.0)], Some(100.0)), vec![0, 1]);
+    }
+
+    #[test]
+    fn render() {
+        let record = plasmid();
+        let enzymes = [Enzyme::from_name("EcoRI").unwrap(), Enzyme::from_name("BamHI").unwrap()];
+        let map = Map::new(&record).with_enzymes(&enzymes);
+        let circular = map.to_svg();
+        assert!(circular.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(circular.ends_with("</svg>\n"));
+        assert!(circular.contains("<title>pTest &amp; co</title>"));
+        assert!(circular.contains(">&lt;p&gt;</text>"));
+        assert!(circular.contains(">EcoRI (1)</text>") && circular.contains(">BamHI (19)</text>"));
+        assert_eq!(circular.matches("<path ").count(), 3);
+
+        let linear = map.linear_svg();
+        // The origin-spanning feature is drawn in two pieces.
+        assert_eq!(linear.matches("<polygon ").count(), 4);
+        assert!(linear.contains(">EcoRI</text>"));
+
+        let sequence = map.sequence_svg(30);
+        assert!(sequence.contains(">GAATTCATGAAACGTTAAGGATCCTTTTTT</text>"));
+        assert!(sequence.contains(">CTTAAGTACTTTGCAATTCCTAGGAAAAAA</text>"));
+        // The CDS reads M K R *, each residue over the middle of its codon.
+        let residues: Vec<&str> = sequence.split("fill=\"#b5651d\">").skip(1)
+            .map(|s| &s[..1]).collect();
+        assert_eq!(residues, vec!["M", "K", "R", "*"]);
+        assert!(sequence.contains(">31</text>") && sequence.contains(">61</text>"));
+        // So is the one in the sequence view, ending the last line and starting the first.
+        assert_eq!(sequence.matches(">ori</text>").count(), 2);
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use sequence::sequence::Sequence;
+use sequence::nucleotide::Nucleotide;
+use sequence::amino_acid::AminoAcid;
+use sequence::codon::Codon;
+use sequence::genetic_code::CodonTable;
+use sequence::strand::Strand;
+use sequence::feature::Topology;
+
+/// An open reading frame from an ATG to the next in-frame stop codon, which
+/// it includes. Coordinates are on the forward strand; on a circular
+/// sequence an ORF spanning the origin has an `end` past the sequence
+/// length, as features do.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Orf {
+    pub start: usize,
+    pub end: usize,
+    pub strand: Strand,
+    /// The translated ORF, without its stop codon.
+    pub protein: Sequence<AminoAcid>,
+}
+
+impl Orf {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+    pub fn is_empty(&self) -> bool {
+        self.end == self.start
+    }
+}
+
+/// Find complete ORFs of at least `min_codons` codons, not counting the
+/// stop, on both strands.
+///
+/// Each ORF starts at the first ATG after the previous in-frame stop, so
+/// nested starts are not reported separately. ORFs running off the end of
+/// a linear sequence have no stop and are skipped. Results are ordered by
+/// position.
+pub fn find_orfs<C: CodonTable + ?Sized>(sequence: &Sequence<Nucleotide>, topology: Topology,
+                                         code: &C, min_codons: usize) -> Vec<Orf> {
+    let length = sequence.len();
+    let mut orfs = Vec::new();
+    for &strand in [Strand::Forward, Strand::Reverse].iter() {
+        let mut bases: Vec<Nucleotide> = match strand {
+            Strand::Forward => sequence.iter().cloned().collect(),
+            Strand::Reverse => sequence.reverse_complement().iter().cloned().collect(),
+        };
+        if topology == Topology::Circular {
+            // Scan twice round so ORFs crossing the origin are seen whole.
+            bases.extend_from_within(..);
+        }
+        // The longest ORF ending at each stop, so a start found again on
+        // the second pass round a circle does not repeat an ORF.
+        let mut by_stop: HashMap<usize, (usize, usize)> = HashMap::new();
+        for frame in 0..3 {
+            let mut open: Option<usize> = None;
+            let mut i = frame;
+            while i + 3 <= bases.len() {
+                let codon = Codon::<Nucleotide>::from_slice(&bases[i..i+3]);
+                if code.is_stop(&codon) {
+                    if let Some(start) = open.take() {
+                        let end = i + 3;
+                        if start < length && end - start <= length
+                            && (end - start) / 3 > min_codons {
+                            let best = by_stop.entry(end % length).or_insert((start, end));
+                            if end - start > best.1 - best.0 {
+                                *best = (start, end);
+                            }
+                        }
+                    }
+                } else if open.is_none() && codon.to_string() == "ATG" {
+                    open = Some(i);
+                }
+                i += 3;
+            }
+        }
+        for (_, (start, end)) in by_stop.into_iter() {
+            let mut cds = Sequence::<Nucleotide>::new();
+            for &nt in bases[start..end-3].iter() {
+                cds.push(nt);
+            }
+            let protein = cds.translate_with(code);
+            let (start, end) = match strand {
+                Strand::Forward => (start, end),
+                // Map back from the reverse complement, keeping `start` in
+                // the first copy of the sequence.
+                Strand::Reverse if end > length => (2*length - end, 2*length - start),
+                Strand::Reverse => (length - end, length - start),
+            };
+            orfs.push(Orf { start, end, strand, protein });
+        }
+    }
+    orfs.sort_by_key(|orf| (orf.start, orf.end));
+    orfs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_orfs;
+    use sequence::sequence::Sequence;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::genetic_code::GeneticCode;
+    use sequence::strand::Strand;
+    use sequence::feature::Topology;
+
+    #[test]
+    fn both_strands() {
+        // A forward ORF with a nested start, and a reverse one.
+        let forward = "CCATGAAAATGCCCGGGTAACC";
+        let reverse = Sequence::<Nucleotide>::from_str("ATGTTTGGGTGA").unwrap()
+            .reverse_complement().to_string();
+        let seq = Sequence::<Nucleotide>::from_str(&format!("{}{}A", forward, reverse)).unwrap();
+        let orfs = find_orfs(&seq, Topology::Linear, GeneticCode::standard(), 3);
+        assert_eq!(orfs.len(), 2);
+        assert_eq!((orfs[0].start, orfs[0].end, orfs[0].strand), (2, 20, Strand::Forward));
+        assert_eq!(orfs[0].protein.to_string(), "MKMPG");
+        assert_eq!((orfs[1].start, orfs[1].end, orfs[1].strand), (22, 34, Strand::Reverse));
+        assert_eq!(orfs[1].protein.to_string(), "MFG");
+        assert_eq!(find_orfs(&seq, Topology::Linear, GeneticCode::standard(), 4).len(), 1);
+    }
+
+    #[test]
+    fn across_origin() {
+        let seq = Sequence::<Nucleotide>::from_str("AAATTTTAGCCCCCATGGGG").unwrap();
+        assert!(find_orfs(&seq, Topology::Linear, GeneticCode::standard(), 2).is_empty());
+        let orfs = find_orfs(&seq, Topology::Circular, GeneticCode::standard(), 2);
+        assert_eq!(orfs.len(), 1);
+        assert_eq!((orfs[0].start, orfs[0].end), (14, 29));
+        assert_eq!(orfs[0].protein.to_string(), "MGKF");
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use sequence::sequence::Sequence;
+use sequence::nucleotide::Nucleotide;
+use sequence::feature::FeatureKind;
+use io::error::FormatError;
+
+/// One version of a part held in a registry.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Entry {
+    pub name: String,
+    /// Numbered from 1; zero until the entry is added to a registry.
+    pub version: u32,
+    pub kind: FeatureKind,
+    pub tags: Vec<String>,
+    /// Free-form `(key, value)` pairs, such as a source or a reference, in
+    /// the order added.
+    pub metadata: Vec<(String, String)>,
+    pub sequence: Sequence<Nucleotide>,
+}
+
+impl Entry {
+    pub fn new(name: &str, kind: FeatureKind, sequence: Sequence<Nucleotide>) -> Entry {
+        Entry { name: String::from(name), version: 0, kind, tags: Vec::new(),
+                metadata: Vec::new(), sequence }
+    }
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+    pub fn metadata(&self, key: &str) -> Option<&str> {
+        self.metadata.iter().find(|m| m.0 == key).map(|m| &m.1[..])
+    }
+
+    /// Whether two entries differ only in their version.
+    fn same_content(&self, other: &Entry) -> bool {
+        self.name == other.name && self.kind == other.kind && self.tags == other.tags
+            && self.metadata == other.metadata && self.sequence == other.sequence
+    }
+}
+
+/// A part whose sequence resembles a query, with the fraction of the
+/// query's distinct k-mers found on either strand of the part.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Hit {
+    pub entry: Entry,
+    pub score: f64,
+}
+
+/// A directory of versioned parts, one subdirectory per part and one file
+/// per version, named `<version>.part`.
+///
+/// Each file is plain text with one tab-separated field per line: `type`
+/// holding the GenBank feature key, `tag` once per tag, `meta` followed by
+/// a key and value, and the `sequence`. Tabs and line breaks in tags and
+/// metadata are replaced by spaces. Part names may hold letters, digits,
+/// `-`, `_` and `.`, and may not start with `.`.
+pub struct Registry {
+    root: PathBuf,
+}
+
+fn check_name(name: &str) -> Result<(), FormatError> {
+    let valid = !name.is_empty() && !name.starts_with('.')
+        && name.chars().all(|ch| ch.is_ascii_alphanumeric() || "-_.".contains(ch));
+    if valid { Ok(()) } else { Err(FormatError::InvalidName(String::from(name))) }
+}
+
+fn clean(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ")
+}
+
+/// Every distinct k-mer on either strand of `seq`.
+fn kmers(seq: &Sequence<Nucleotide>, k: usize) -> HashSet<u64> {
+    seq.kmers(k).chain(seq.reverse_complement().kmers(k)).collect()
+}
+
+impl Registry {
+    /// Open the registry at `root`, creating the directory if needed.
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<Registry, FormatError> {
+        fs::create_dir_all(root.as_ref())?;
+        Ok(Registry { root: root.as_ref().to_path_buf() })
+    }
+
+    fn path(&self, name: &str, version: u32) -> PathBuf {
+        self.root.join(name).join(format!("{}.part", version))
+    }
+
+    /// Store `entry` as the next version of its part and return the version
+    /// number. If the latest version already has the same content, nothing
+    /// is written and that version is returned.
+    pub fn add(&self, entry: &Entry) -> Result<u32, FormatError> {
+        check_name(&entry.name)?;
+        let mut entry = entry.clone();
+        entry.tags = entry.tags.iter().map(|tag| clean(tag)).collect();
+        entry.metadata = entry.metadata.iter().map(|(k, v)| (clean(k), clean(v))).collect();
+        let versions = self.versions(&entry.name)?;
+        if let Some(&latest) = versions.last() {
+            if self.get(&entry.name, Some(latest))?.same_content(&entry) {
+                return Ok(latest);
+            }
+        }
+        let version = versions.last().map_or(1, |v| v + 1);
+        fs::create_dir_all(self.root.join(&entry.name))?;
+        // Write under a temporary name so a reader never sees half a part.
+        let path = self.path(&entry.name, version);
+        let temporary = path.with_extension("tmp");
+        {
+            let mut file = io::BufWriter::new(fs::File::create(&temporary)?);
+            writeln!(file, "type\t{}", clean(entry.kind.to_key()))?;
+            for tag in entry.tags.iter() {
+                writeln!(file, "tag\t{}", tag)?;
+            }
+            for (key, value) in entry.metadata.iter() {
+                writeln!(file, "meta\t{}\t{}", key, value)?;
+            }
+            writeln!(file, "sequence\t{}", entry.sequence.to_string())?;
+            file.flush()?;
+        }
+        fs::rename(&temporary, &path)?;
+        Ok(version)
+    }
+
+    /// A version of a part, or its latest if `version` is `None`.
+    pub fn get(&self, name: &str, version: Option<u32>) -> Result<Entry, FormatError> {
+        check_name(name)?;
+        let version = match version {
+            Some(version) => version,
+            None => *self.versions(name)?.last()
+                .ok_or_else(|| FormatError::UnknownRecord(String::from(name)))?,
+        };
+        let path = self.path(name, version);
+        let file = match fs::File::open(&path) {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound =>
+                return Err(FormatError::UnknownRecord(format!("{} version {}", name, version))),
+            Err(e) => return Err(FormatError::Io(e)),
+        };
+        let malformed = |line: usize, message: &str| FormatError::Malformed(
+            format!("{} line {}: {}", path.display(), line, message));
+        let mut entry = Entry::new(name, FeatureKind::MiscFeature, Sequence::new());
+        entry.version = version;
+        let mut seen_sequence = false;
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            let fields: Vec<&str> = line.split('\t').collect();
+            match (fields[0], fields.len()) {
+                ("type", 2) => entry.kind = FeatureKind::from_key(fields[1]),
+                ("tag", 2) => entry.tags.push(String::from(fields[1])),
+                ("meta", 3) => entry.metadata.push((String::from(fields[1]),
+                                                    String::from(fields[2]))),
+                ("sequence", 2) => {
+                    entry.sequence = Sequence::from_str(fields[1])
+                        .map_err(|e| FormatError::InvalidSequence { line: i + 1, error: e })?;
+                    seen_sequence = true;
+                },
+                ("", 1) => (),
+                _ => return Err(malformed(i + 1, &format!("unexpected field '{}'", fields[0]))),
+            }
+        }
+        if !seen_sequence {
+            return Err(FormatError::Malformed(format!("{}: no sequence", path.display())));
+        }
+        Ok(entry)
+    }
+
+    /// The stored versions of a part, oldest first; empty if there are none.
+    pub fn versions(&self, name: &str) -> Result<Vec<u32>, FormatError> {
+        check_name(name)?;
+        let directory = match fs::read_dir(self.root.join(name)) {
+            Ok(directory) => directory,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(FormatError::Io(e)),
+        };
+        let mut versions = Vec::new();
+        for file in directory {
+            let file = file?.file_name();
+            let file = file.to_string_lossy();
+            if let Some(version) = file.strip_suffix(".part").and_then(|v| v.parse().ok()) {
+                versions.push(version);
+            }
+        }
+        versions.sort();
+        Ok(versions)
+    }
+
+    /// The names of every part with at least one version, in sorted order.
+    pub fn names(&self) -> Result<Vec<String>, FormatError> {
+        let mut names = Vec::new();
+        for directory in fs::read_dir(&self.root)? {
+            let name = directory?.file_name().to_string_lossy().into_owned();
+            if check_name(&name).is_ok() && !self.versions(&name)?.is_empty() {
+                names.push(name);
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// The latest version of every part, by name.
+    pub fn latest(&self) -> Result<Vec<Entry>, FormatError> {
+        self.names()?.iter().map(|name| self.get(name, None)).collect()
+    }
+
+    /// Latest versions of parts whose names contain `query`, ignoring case.
+    pub fn search_name(&self, query: &str) -> Result<Vec<Entry>, FormatError> {
+        let query = query.to_lowercase();
+        Ok(self.latest()?.into_iter().filter(|e| e.name.to_lowercase().contains(&query)).collect())
+    }
+
+    /// Latest versions of parts carrying `tag`, ignoring case.
+    pub fn search_tag(&self, tag: &str) -> Result<Vec<Entry>, FormatError> {
+        Ok(self.latest()?.into_iter().filter(|e| e.has_tag(tag)).collect())
+    }
+
+    /// Latest versions of parts sharing at least `min_score` of the distinct
+    /// `k`-mers of `query`, on either strand, best first. A query shorter
+    /// than `k` matches nothing.
+    ///
+    /// Panics if `k` is zero or over 32.
+    pub fn search_sequence(&self, query: &Sequence<Nucleotide>, k: usize, min_score: f64)
+                           -> Result<Vec<Hit>, FormatError> {
+        let wanted: HashSet<u64> = query.kmers(k).collect();
+        if wanted.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut hits = Vec::new();
+        for entry in self.latest()? {
+            let found = kmers(&entry.sequence, k);
+            let score = wanted.iter().filter(|kmer| found.contains(kmer)).count() as f64
+                / wanted.len() as f64;
+            if score >= min_score && score > 0.0 {
+                hits.push(Hit { entry, score });
+            }
+        }
+        hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap()
+                     .then_with(|| a.entry.name.cmp(&b.entry.name)));
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::{Entry, Registry};
+    use sequence::sequence::Sequence;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::feature::FeatureKind;
+    use io::error::FormatError;
+
+    fn dna(text: &str) -> Sequence<Nucleotide> {
+        Sequence::<Nucleotide>::from_str(text).unwrap()
+    }
+
+    fn registry(test: &str) -> Registry {
+        let root = env::temp_dir().join(format!("constructor-registry-{}-{}", test,
+                                                ::std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        Registry::open(root).unwrap()
+    }
+
+    #[test]
+    fn versions() {
+        let registry = registry("versions");
+        let mut entry = Entry::new("pTac", FeatureKind::Promoter, dna("TTGACAATTAATCATCGGCTCG"));
+        entry.tags.push(String::from("inducible"));
+        entry.metadata.push((String::from("source"), String::from("de Boer\t1983")));
+        assert_eq!(registry.add(&entry).unwrap(), 1);
+        assert_eq!(registry.add(&entry).unwrap(), 1);
+        entry.sequence = dna("TTGACAATTAATCATCGGCTCGTATAATG");
+        assert_eq!(registry.add(&entry).unwrap(), 2);
+        assert_eq!(registry.versions("pTac").unwrap(), vec![1, 2]);
+
+        let first = registry.get("pTac", Some(1)).unwrap();
+        assert_eq!((first.version, first.sequence.len()), (1, 22));
+        assert_eq!(first.metadata("source"), Some("de Boer 1983"));
+        let latest = registry.get("pTac", None).unwrap();
+        assert_eq!((latest.version, latest.kind.clone(), latest.has_tag("Inducible")),
+                   (2, FeatureKind::Promoter, true));
+
+        match registry.get("pTac", Some(3)) {
+            Err(FormatError::UnknownRecord(name)) => assert_eq!(name, "pTac version 3"),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(registry.get("missing", None).is_err());
+        match registry.add(&Entry::new("../escape", FeatureKind::Cds, dna("ATG"))) {
+            Err(FormatError::InvalidName(name)) => assert_eq!(name, "../escape"),
+            other => panic!("unexpected {:?}", other),
+        }
+        fs::write(registry.path("pTac", 3), "type\tpromoter\n").unwrap();
+        match registry.get("pTac", Some(3)) {
+            Err(FormatError::Malformed(message)) =>
+                assert!(message.ends_with("3.part: no sequence")),
+            other => panic!("unexpected {:?}", other),
+        }
+        fs::remove_dir_all(&registry.root).unwrap();
+    }
+
+    #[test]
+    fn search() {
+        let registry = registry("search");
+        let gfp = "ATGAGTAAAGGAGAAGAACTTTTCACTGGAGTTGTCCCAATTCTTGTTGAATTAGATGGTGATGTTAATGGG";
+        let mut entries = [
+            Entry::new("gfp", FeatureKind::Cds, dna(gfp)),
+            Entry::new("rbs-B0034", FeatureKind::Rbs, dna("AAAGAGGAGAAA")),
+            Entry::new("lacI", FeatureKind::Cds,
+                       dna("GTGAAACCAGTAACGTTATACGATGTCGCAGAGTATGCCGGTGTCTCTTATCAGACC")),
+        ];
+        entries[0].tags.push(String::from("reporter"));
+        for entry in entries.iter() {
+            registry.add(entry).unwrap();
+        }
+        assert_eq!(registry.names().unwrap(), vec!["gfp", "lacI", "rbs-B0034"]);
+        let names = |found: Vec<Entry>| found.into_iter().map(|e| e.name).collect::<Vec<_>>();
+        assert_eq!(names(registry.search_name("LAC").unwrap()), vec!["lacI"]);
+        assert_eq!(names(registry.search_tag("reporter").unwrap()), vec!["gfp"]);
+
+        // A reverse-complemented fragment of GFP with one substitution.
+        let mut fragment: Vec<char> = dna(&gfp[10..50]).reverse_complement().to_string()
+            .chars().collect();
+        fragment[20] = if fragment[20] == 'A' { 'C' } else { 'A' };
+        let query = dna(&fragment.into_iter().collect::<String>());
+        let hits = registry.search_sequence(&query, 8, 0.5).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entry.name, "gfp");
+        assert!(hits[0].score > 0.7 && hits[0].score < 1.0);
+        assert!(registry.search_sequence(&dna("ACGT"), 8, 0.0).unwrap().is_empty());
+        fs::remove_dir_all(&registry.root).unwrap();
+    }
+}
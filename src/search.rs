@@ -0,0 +1,92 @@
+use sequence::sequence::Sequence;
+use sequence::nucleotide::Nucleotide;
+use sequence::degenerate_nucleotide::DegenerateNucleotide;
+use sequence::strand::Strand;
+use sequence::feature::Topology;
+
+/// An occurrence of a pattern. Coordinates are on the forward strand; on a
+/// circular sequence a match spanning the origin has an `end` past the
+/// sequence length, as features do.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+    pub strand: Strand,
+}
+
+/// Bitmask of the bases a pattern symbol accepts, A C G T from the low bit.
+fn mask(symbol: &DegenerateNucleotide) -> u8 {
+    symbol.expand().iter().fold(0, |mask, nt| mask | bit(nt))
+}
+
+fn bit(nt: &Nucleotide) -> u8 {
+    match *nt {
+        Nucleotide::A => 1,
+        Nucleotide::C => 2,
+        Nucleotide::G => 4,
+        Nucleotide::T => 8,
+    }
+}
+
+/// Find every occurrence of a possibly degenerate pattern on both strands,
+/// ordered by position. A pattern that is its own reverse complement is
+/// reported once, on the forward strand.
+pub fn search(sequence: &Sequence<Nucleotide>, pattern: &Sequence<DegenerateNucleotide>,
+              topology: Topology) -> Vec<Match> {
+    let bases: Vec<u8> = sequence.iter().map(bit).collect();
+    let (length, width) = (bases.len(), pattern.len());
+    if width == 0 || width > length {
+        return Vec::new();
+    }
+    let reverse = pattern.reverse_complement();
+    let palindrome = reverse == *pattern;
+    let mut strands = vec![(Strand::Forward, pattern.iter().map(mask).collect::<Vec<u8>>())];
+    if !palindrome {
+        strands.push((Strand::Reverse, reverse.iter().map(mask).collect()));
+    }
+    let last = match topology {
+        Topology::Linear => length - width + 1,
+        Topology::Circular => length,
+    };
+    let mut matches = Vec::new();
+    for start in 0..last {
+        for &(strand, ref masks) in strands.iter() {
+            if masks.iter().enumerate().all(|(i, &m)| bases[(start + i) % length] & m != 0) {
+                matches.push(Match { start, end: start + width, strand });
+            }
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{search, Match};
+    use sequence::sequence::Sequence;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::degenerate_nucleotide::DegenerateNucleotide;
+    use sequence::strand::Strand;
+    use sequence::feature::Topology;
+
+    #[test]
+    fn both_strands() {
+        let seq = Sequence::<Nucleotide>::from_str("GAATTCAAGGTCTCAAGAGACCTT").unwrap();
+        let bsa = Sequence::<DegenerateNucleotide>::from_str("GGTCTC").unwrap();
+        let hits = search(&seq, &bsa, Topology::Linear);
+        assert_eq!(hits, vec![Match { start: 8, end: 14, strand: Strand::Forward },
+                              Match { start: 16, end: 22, strand: Strand::Reverse }]);
+        let eco = Sequence::<DegenerateNucleotide>::from_str("GAATTC").unwrap();
+        assert_eq!(search(&seq, &eco, Topology::Linear).len(), 1);
+        let degenerate = Sequence::<DegenerateNucleotide>::from_str("AAGNNN").unwrap();
+        assert_eq!(search(&seq, &degenerate, Topology::Linear).len(), 3);
+    }
+
+    #[test]
+    fn across_origin() {
+        let seq = Sequence::<Nucleotide>::from_str("TTCAAAAAAAAGAA").unwrap();
+        let eco = Sequence::<DegenerateNucleotide>::from_str("GAATTC").unwrap();
+        assert!(search(&seq, &eco, Topology::Linear).is_empty());
+        assert_eq!(search(&seq, &eco, Topology::Circular),
+                   vec![Match { start: 11, end: 17, strand: Strand::Forward }]);
+    }
+}
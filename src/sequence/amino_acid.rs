@@ -1,13 +1,64 @@
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
 use sequence::string_io::StringIO;
+use sequence::error::SequenceError;
 
+/// A residue in IUPAC one-letter notation.
+///
+/// Besides the twenty standard amino acids this covers selenocysteine (`U`),
+/// pyrrolysine (`O`), the ambiguity codes `B` (Asp or Asn), `Z` (Glu or Gln)
+/// and `J` (Leu or Ile), and `X` for an unknown residue. Residues introduced
+/// by genetic code expansion are carried as `NonCanonical` and written as
+/// `X` in one-letter sequences.
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub enum AminoAcid {
-    A, C, D, E, F, G, H, I, K, L, M, N, P, Q, R, S, T, V, W, Y, STOP
+    A, C, D, E, F, G, H, I, K, L, M, N, P, Q, R, S, T, V, W, Y,
+    U, O, B, Z, J, X,
+    NonCanonical(Arc<NonCanonical>),
+    STOP
+}
+
+/// A user-defined non-canonical amino acid, such as p-azido-L-phenylalanine
+/// incorporated by an orthogonal tRNA/synthetase pair.
+///
+/// Two residues are the same when their abbreviations match, ignoring case.
+#[derive(Debug)]
+pub struct NonCanonical {
+    /// Short code used in place of a three-letter code, such as `AzF`.
+    pub abbreviation: String,
+    pub name: String,
+    /// Average and monoisotopic masses of the residue, that is of the free
+    /// amino acid less one water.
+    pub average_mass: f64,
+    pub monoisotopic_mass: f64,
+}
+
+impl NonCanonical {
+    pub fn new(abbreviation: &str, name: &str, average_mass: f64, monoisotopic_mass: f64)
+               -> NonCanonical {
+        NonCanonical { abbreviation: String::from(abbreviation), name: String::from(name),
+                       average_mass, monoisotopic_mass }
+    }
+}
+
+impl PartialEq for NonCanonical {
+    fn eq(&self, other: &NonCanonical) -> bool {
+        self.abbreviation.eq_ignore_ascii_case(&other.abbreviation)
+    }
+}
+
+impl Eq for NonCanonical {}
+
+impl Hash for NonCanonical {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.abbreviation.to_uppercase().hash(state);
+    }
 }
 
 impl StringIO for AminoAcid {
     type N = AminoAcid;
-    fn from_char(input: char) -> Result<AminoAcid, String> {
+    fn from_char(input: char) -> Result<AminoAcid, SequenceError> {
         match input {
             'A' | 'a' => Ok(AminoAcid::A),
             'C' | 'c' => Ok(AminoAcid::C),
@@ -29,8 +80,14 @@ impl StringIO for AminoAcid {
             'V' | 'v' => Ok(AminoAcid::V),
             'W' | 'w' => Ok(AminoAcid::W),
             'Y' | 'y' => Ok(AminoAcid::Y),
+            'U' | 'u' => Ok(AminoAcid::U),
+            'O' | 'o' => Ok(AminoAcid::O),
+            'B' | 'b' => Ok(AminoAcid::B),
+            'Z' | 'z' => Ok(AminoAcid::Z),
+            'J' | 'j' => Ok(AminoAcid::J),
+            'X' | 'x' => Ok(AminoAcid::X),
             '*' => Ok(AminoAcid::STOP),
-            bad_aa => Err(format!("Bad amino-acid specifier {}",bad_aa)),
+            bad_aa => Err(SequenceError::InvalidSymbol { symbol: bad_aa, offset: 0 }),
         }
     }
     fn to_char(&self) -> char {
@@ -55,13 +112,21 @@ impl StringIO for AminoAcid {
             &AminoAcid::V => 'V',
             &AminoAcid::W => 'W',
             &AminoAcid::Y => 'Y',
+            &AminoAcid::U => 'U',
+            &AminoAcid::O => 'O',
+            &AminoAcid::B => 'B',
+            &AminoAcid::Z => 'Z',
+            &AminoAcid::J => 'J',
+            &AminoAcid::X | &AminoAcid::NonCanonical(_) => 'X',
             &AminoAcid::STOP => '*',
         }
     }
 }
 
 impl AminoAcid {
-    pub fn from_three_letter_code(input: &str) -> Result<AminoAcid, String> {
+    /// Parse a three-letter code. Non-canonical residues are not known here;
+    /// see `from_three_letter_code_with`.
+    pub fn from_three_letter_code(input: &str) -> Result<AminoAcid, SequenceError> {
         match &String::from(input).to_uppercase()[..] {
             "ALA" => Ok(AminoAcid::A),
             "CYS" => Ok(AminoAcid::C),
@@ -83,11 +148,27 @@ impl AminoAcid {
             "VAL" => Ok(AminoAcid::V),
             "TRP" => Ok(AminoAcid::W),
             "TYR" => Ok(AminoAcid::Y),
+            "SEC" => Ok(AminoAcid::U),
+            "PYL" => Ok(AminoAcid::O),
+            "ASX" => Ok(AminoAcid::B),
+            "GLX" => Ok(AminoAcid::Z),
+            "XLE" => Ok(AminoAcid::J),
+            "XAA" | "UNK" => Ok(AminoAcid::X),
             "*" | " * " => Ok(AminoAcid::STOP),
-            bad_aa => Err(format!("Bad amino-acid specifier {}",bad_aa)),
+            bad_aa => Err(SequenceError::InvalidThreeLetterCode { code: String::from(bad_aa) }),
+        }
+    }
+    /// Parse a three-letter code, also accepting the abbreviations of the
+    /// given non-canonical residues.
+    pub fn from_three_letter_code_with(input: &str, non_canonical: &[Arc<NonCanonical>])
+                                       -> Result<AminoAcid, SequenceError> {
+        match non_canonical.iter().find(|nc| nc.abbreviation.eq_ignore_ascii_case(input)) {
+            Some(nc) => Ok(AminoAcid::NonCanonical(nc.clone())),
+            None => AminoAcid::from_three_letter_code(input),
         }
     }
-    pub fn to_three_letter_code(&self) -> &'static str {
+    /// The three-letter code, or the abbreviation of a non-canonical residue.
+    pub fn to_three_letter_code(&self) -> &str {
         match self {
             &AminoAcid::A => "ALA",
             &AminoAcid::C => "CYS",
@@ -109,6 +190,13 @@ impl AminoAcid {
             &AminoAcid::V => "VAL",
             &AminoAcid::W => "TRP",
             &AminoAcid::Y => "TYR",
+            &AminoAcid::U => "SEC",
+            &AminoAcid::O => "PYL",
+            &AminoAcid::B => "ASX",
+            &AminoAcid::Z => "GLX",
+            &AminoAcid::J => "XLE",
+            &AminoAcid::X => "XAA",
+            AminoAcid::NonCanonical(nc) => &nc.abbreviation,
             &AminoAcid::STOP => " * ",
         }
     }
@@ -116,14 +204,19 @@ impl AminoAcid {
 
 #[cfg(test)]
 mod tests {
-    use super::AminoAcid;
+    use std::sync::Arc;
+
+    use super::{AminoAcid, NonCanonical};
     use sequence::string_io::StringIO;
     use sequence::sequence::Sequence;
 
-    const AMINO_ACID_CHARS: &'static str = "ACDEFGHIKLMNPQRSTVWY*";
-    const DISALLOWED_AMINO_ACID_CHARS: &'static str = "BJOUXZ";
+    #[allow(clippy::redundant_static_lifetimes)]
+    const AMINO_ACID_CHARS: &'static str = "ACDEFGHIKLMNPQRSTVWYUOBZJX*";
+    #[allow(clippy::redundant_static_lifetimes)]
+    const DISALLOWED_AMINO_ACID_CHARS: &'static str = "-.1@";
 
     #[test]
+    #[allow(clippy::single_match, non_fmt_panics)]
     fn char() {
         for ch in AMINO_ACID_CHARS.chars() {
             let aa = AminoAcid::from_char(ch).unwrap();
@@ -152,4 +245,16 @@ mod tests {
         let seq = Sequence::<AminoAcid>::from_str(AMINO_ACID_CHARS).unwrap();
         assert_eq!(seq.to_string(),AMINO_ACID_CHARS);
     }
+
+    #[test]
+    fn non_canonical() {
+        let azf = Arc::new(NonCanonical::new("AzF", "p-azido-L-phenylalanine", 188.19, 188.0698));
+        let known = vec![azf.clone()];
+        let aa = AminoAcid::from_three_letter_code_with("azf", &known).unwrap();
+        assert_eq!(aa, AminoAcid::NonCanonical(azf));
+        assert_eq!(aa.to_three_letter_code(), "AzF");
+        assert_eq!(aa.to_char(), 'X');
+        assert!(AminoAcid::from_three_letter_code("AzF").is_err());
+        assert_eq!(AminoAcid::from_three_letter_code_with("Sec", &[]).unwrap(), AminoAcid::U);
+    }
 }
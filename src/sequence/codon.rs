@@ -1,6 +1,7 @@
 use sequence::string_io::StringIO;
+use sequence::error::SequenceError;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Codon<N> {
     data: [N;3],
 }
@@ -11,17 +12,24 @@ impl<N> Codon<N> where N: StringIO<N=N> + Clone {
                             input[1].clone(),
                             input[2].clone()] }
     }
-    pub fn from_str(input: &str) -> Result<Codon<N>, String> {
-        let mut chars = input.chars();
-        Ok(Codon::<N> { data: [N::from_char(chars.next().unwrap())?,
-                               N::from_char(chars.next().unwrap())?,
-                               N::from_char(chars.next().unwrap())?] } )
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(input: &str) -> Result<Codon<N>, SequenceError> {
+        let chars: Vec<char> = input.chars().take(4).collect();
+        if chars.len() < 3 {
+            return Err(SequenceError::IncompleteCodon { index: 0, available: chars.len() });
+        }
+        // A codon has no fourth symbol, so anything past the third is invalid.
+        if let Some(&extra) = chars.get(3) {
+            return Err(SequenceError::InvalidSymbol { symbol: extra, offset: 3 });
+        }
+        Codon::<N>::from_chars(chars[0], chars[1], chars[2])
     }
-    pub fn from_chars(ch1: char, ch2: char, ch3: char) -> Result<Codon<N>, String> {
+    pub fn from_chars(ch1: char, ch2: char, ch3: char) -> Result<Codon<N>, SequenceError> {
         Ok(Codon::<N> { data: [N::from_char(ch1)?,
-                               N::from_char(ch2)?,
-                               N::from_char(ch3)?] } )
+                               N::from_char(ch2).map_err(|e| e.offset_by(1))?,
+                               N::from_char(ch3).map_err(|e| e.offset_by(2))?] } )
     }
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
         let mut output = String::new();
         for nt in self.data.iter() {
@@ -36,3 +44,20 @@ impl<N> PartialEq for Codon<N> where N: StringIO + PartialEq {
         self.data == other.data
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Codon;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::error::SequenceError;
+
+    #[test]
+    fn bad_codon_specs() {
+        assert_eq!(Codon::<Nucleotide>::from_str("GA").unwrap_err(),
+                   SequenceError::IncompleteCodon { index: 0, available: 2 });
+        assert_eq!(Codon::<Nucleotide>::from_str("GAX").unwrap_err(),
+                   SequenceError::InvalidSymbol { symbol: 'X', offset: 2 });
+        assert_eq!(Codon::<Nucleotide>::from_str("ATGC").unwrap_err(),
+                   SequenceError::InvalidSymbol { symbol: 'C', offset: 3 });
+    }
+}
@@ -1,5 +1,6 @@
 use sequence::nucleotide::{Nucleotide, Complement};
 use sequence::string_io::StringIO;
+use sequence::error::SequenceError;
 
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub enum DegenerateNucleotide {
@@ -7,7 +8,8 @@ pub enum DegenerateNucleotide {
 }
 
 impl DegenerateNucleotide {
-    fn expand(&self) -> &'static [Nucleotide] {
+    #[allow(clippy::match_ref_pats)]
+    pub fn expand(&self) -> &'static [Nucleotide] {
         static A: [Nucleotide;1] = [Nucleotide::A];
         static C: [Nucleotide;1] = [Nucleotide::C];
         static G: [Nucleotide;1] = [Nucleotide::G];
@@ -46,7 +48,7 @@ impl DegenerateNucleotide {
 
 impl StringIO for DegenerateNucleotide {
     type N = DegenerateNucleotide;
-    fn from_char(input: char) -> Result<DegenerateNucleotide, String> {
+    fn from_char(input: char) -> Result<DegenerateNucleotide, SequenceError> {
         match input {
             'A' | 'a' => Ok(DegenerateNucleotide::A),
             'C' | 'c' => Ok(DegenerateNucleotide::C),
@@ -63,9 +65,10 @@ impl StringIO for DegenerateNucleotide {
             'H' | 'h' => Ok(DegenerateNucleotide::H),
             'V' | 'v' => Ok(DegenerateNucleotide::V),
             'N' | 'n' => Ok(DegenerateNucleotide::N),
-            bad_nt => Err(format!("Bad degenerate nucleotide specifier: {}",bad_nt))
+            bad_nt => Err(SequenceError::InvalidSymbol { symbol: bad_nt, offset: 0 })
         }
     }
+    #[allow(clippy::match_ref_pats)]
     fn to_char(&self) -> char {
         match self {
             &DegenerateNucleotide::A => 'A',
@@ -89,6 +92,7 @@ impl StringIO for DegenerateNucleotide {
 
 impl Complement for DegenerateNucleotide {
     type N = DegenerateNucleotide;
+    #[allow(clippy::match_ref_pats)]
     fn complement(&self) -> DegenerateNucleotide {
         match self {
             &DegenerateNucleotide::A => DegenerateNucleotide::T,
@@ -110,6 +114,18 @@ impl Complement for DegenerateNucleotide {
     }
 }
 
+/// Every base is also a degenerate base standing for itself alone.
+impl From<Nucleotide> for DegenerateNucleotide {
+    fn from(nt: Nucleotide) -> DegenerateNucleotide {
+        match nt {
+            Nucleotide::A => DegenerateNucleotide::A,
+            Nucleotide::C => DegenerateNucleotide::C,
+            Nucleotide::G => DegenerateNucleotide::G,
+            Nucleotide::T => DegenerateNucleotide::T,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
@@ -117,6 +133,7 @@ mod tests {
     use sequence::nucleotide::{Nucleotide, Complement};
     use sequence::string_io::StringIO;
 
+    #[allow(clippy::redundant_static_lifetimes)]
     const DEGENERATE_NUCLEOTIDE_CHARS: &'static str = "ACGTRYSWKMBDHVN";
 
     #[test]
@@ -0,0 +1,79 @@
+use std::error::Error;
+use std::fmt;
+
+/// Failures raised while parsing, indexing or translating sequences.
+///
+/// Positions are zero-based offsets into the input that was being processed,
+/// counted in symbols (bases or residues) rather than bytes. The `Display`
+/// output reports them one-based, as a user reading the sequence would count.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum SequenceError {
+    /// A character that is not part of the alphabet being parsed.
+    InvalidSymbol { symbol: char, offset: usize },
+    /// A three-letter amino-acid code that does not name a residue.
+    InvalidThreeLetterCode { code: String },
+    /// An index at or past the end of a sequence of `length` symbols.
+    IndexOutOfBounds { index: usize, length: usize },
+    /// Fewer than three symbols were available to build a codon at `index`.
+    IncompleteCodon { index: usize, available: usize },
+    /// A codon with no translation, starting at `offset`.
+    UntranslatableCodon { codon: String, offset: usize },
+}
+
+impl SequenceError {
+    /// Shift the position carried by this error by `base`.
+    ///
+    /// Used when an error raised on a fragment of a larger input (a single
+    /// character, a codon, a line of a file) needs to be reported relative
+    /// to the whole input.
+    pub fn offset_by(self, base: usize) -> SequenceError {
+        match self {
+            SequenceError::InvalidSymbol { symbol, offset } =>
+                SequenceError::InvalidSymbol { symbol, offset: base + offset },
+            SequenceError::IncompleteCodon { index, available } =>
+                SequenceError::IncompleteCodon { index: base + index, available },
+            SequenceError::UntranslatableCodon { codon, offset } =>
+                SequenceError::UntranslatableCodon { codon, offset: base + offset },
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SequenceError::InvalidSymbol { symbol, offset } =>
+                write!(f, "Invalid symbol '{}' at position {}", symbol, offset + 1),
+            SequenceError::InvalidThreeLetterCode { ref code } =>
+                write!(f, "Invalid three-letter amino-acid code {}", code),
+            SequenceError::IndexOutOfBounds { index, length } =>
+                write!(f, "Index {} out of bounds for sequence of length {}", index, length),
+            SequenceError::IncompleteCodon { index, available } =>
+                write!(f, "Incomplete codon at position {}: only {} bases available",
+                       index + 1, available),
+            SequenceError::UntranslatableCodon { ref codon, offset } =>
+                write!(f, "Untranslatable codon {} at position {}", codon, offset + 1),
+        }
+    }
+}
+
+impl Error for SequenceError {}
+
+#[cfg(test)]
+mod tests {
+    use super::SequenceError;
+
+    #[test]
+    fn offset_by() {
+        let err = SequenceError::InvalidSymbol { symbol: 'X', offset: 2 };
+        assert_eq!(err.offset_by(10), SequenceError::InvalidSymbol { symbol: 'X', offset: 12 });
+        let err = SequenceError::IndexOutOfBounds { index: 4, length: 3 };
+        assert_eq!(err.clone().offset_by(10), err);
+    }
+
+    #[test]
+    fn display() {
+        let err = SequenceError::InvalidSymbol { symbol: 'X', offset: 9 };
+        assert_eq!(err.to_string(), "Invalid symbol 'X' at position 10");
+    }
+}
@@ -0,0 +1,163 @@
+use sequence::sequence::Sequence;
+use sequence::nucleotide::{Nucleotide, Complement};
+use sequence::string_io::StringIO;
+use sequence::strand::Strand;
+
+/// Whether a sequence is a linear fragment or a circular molecule.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Topology {
+    Linear,
+    Circular,
+}
+
+/// The kind of an annotated feature, following GenBank feature keys.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum FeatureKind {
+    Cds,
+    Promoter,
+    Rbs,
+    Terminator,
+    Origin,
+    PrimerBind,
+    ProteinBind,
+    MiscFeature,
+    Other(String),
+}
+
+impl FeatureKind {
+    pub fn from_key(key: &str) -> FeatureKind {
+        match key {
+            "CDS" => FeatureKind::Cds,
+            "promoter" => FeatureKind::Promoter,
+            "RBS" => FeatureKind::Rbs,
+            "terminator" => FeatureKind::Terminator,
+            "rep_origin" => FeatureKind::Origin,
+            "primer_bind" => FeatureKind::PrimerBind,
+            "protein_bind" => FeatureKind::ProteinBind,
+            "misc_feature" => FeatureKind::MiscFeature,
+            other => FeatureKind::Other(String::from(other)),
+        }
+    }
+    pub fn to_key(&self) -> &str {
+        match *self {
+            FeatureKind::Cds => "CDS",
+            FeatureKind::Promoter => "promoter",
+            FeatureKind::Rbs => "RBS",
+            FeatureKind::Terminator => "terminator",
+            FeatureKind::Origin => "rep_origin",
+            FeatureKind::PrimerBind => "primer_bind",
+            FeatureKind::ProteinBind => "protein_bind",
+            FeatureKind::MiscFeature => "misc_feature",
+            FeatureKind::Other(ref key) => key,
+        }
+    }
+}
+
+/// An annotated interval of a sequence.
+///
+/// Coordinates are zero-based and half-open on the forward strand. On a
+/// circular sequence a feature spanning the origin has an `end` past the
+/// sequence length, so `start..end` is always increasing.
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Feature {
+    pub kind: FeatureKind,
+    pub label: String,
+    pub start: usize,
+    pub end: usize,
+    pub strand: Strand,
+    /// Additional `(key, value)` qualifiers, in file order.
+    pub qualifiers: Vec<(String, String)>,
+}
+
+impl Feature {
+    pub fn new(kind: FeatureKind, label: &str, start: usize, end: usize, strand: Strand)
+               -> Feature {
+        Feature { kind, label: String::from(label), start, end, strand, qualifiers: Vec::new() }
+    }
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+    pub fn is_empty(&self) -> bool {
+        self.end == self.start
+    }
+    /// Whether the feature covers `position` of a sequence of `length` bases.
+    pub fn contains(&self, position: usize, length: usize) -> bool {
+        (position >= self.start && position < self.end)
+            || (self.end > length && position + length < self.end)
+    }
+    pub fn qualifier(&self, key: &str) -> Option<&str> {
+        self.qualifiers.iter().find(|q| q.0 == key).map(|q| &q.1[..])
+    }
+}
+
+/// A nucleotide sequence together with its name, topology and features.
+/// Sequences are made of unambiguous bases unless read as
+/// `DegenerateNucleotide`, as files with `N` or other IUPAC codes must be.
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct AnnotatedSequence<N = Nucleotide> where N: StringIO<N=N> + Clone {
+    pub name: String,
+    pub sequence: Sequence<N>,
+    pub topology: Topology,
+    pub features: Vec<Feature>,
+}
+
+impl<N> AnnotatedSequence<N> where N: StringIO<N=N> + Clone {
+    pub fn new(name: &str, sequence: Sequence<N>, topology: Topology) -> AnnotatedSequence<N> {
+        AnnotatedSequence { name: String::from(name), sequence, topology, features: Vec::new() }
+    }
+
+    /// Features covering `position`, in annotation order.
+    pub fn features_at(&self, position: usize) -> Vec<&Feature> {
+        let length = self.sequence.len();
+        self.features.iter().filter(|f| f.contains(position, length)).collect()
+    }
+}
+
+impl<N> AnnotatedSequence<N> where N: StringIO<N=N> + Complement<N=N> + Clone {
+    /// The bases a feature covers, read 5' to 3' along its own strand.
+    pub fn extract(&self, feature: &Feature) -> Sequence<N> {
+        let length = self.sequence.len();
+        let mut seq = Sequence::<N>::new();
+        for i in feature.start..feature.end {
+            seq.push(self.sequence.get(i % length).unwrap());
+        }
+        match feature.strand {
+            Strand::Forward => seq,
+            Strand::Reverse => seq.reverse_complement(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnnotatedSequence, Feature, FeatureKind, Topology};
+    use sequence::sequence::Sequence;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::strand::Strand;
+
+    #[test]
+    fn extract() {
+        let seq = Sequence::<Nucleotide>::from_str("ATGAAATAGCCC").unwrap();
+        let mut plasmid = AnnotatedSequence::new("p", seq, Topology::Circular);
+        plasmid.features.push(Feature::new(FeatureKind::Cds, "orf", 0, 9, Strand::Forward));
+        plasmid.features.push(Feature::new(FeatureKind::MiscFeature, "wrap", 10, 14,
+                                           Strand::Reverse));
+        assert_eq!(plasmid.extract(&plasmid.features[0]).to_string(), "ATGAAATAG");
+        assert_eq!(plasmid.extract(&plasmid.features[1]).to_string(), "ATGG");
+        assert_eq!(plasmid.features_at(1).len(), 2);
+        assert_eq!(plasmid.features_at(9).len(), 0);
+        assert_eq!(plasmid.features_at(11)[0].label, "wrap");
+    }
+
+    #[test]
+    fn keys() {
+        for key in ["CDS", "promoter", "RBS", "terminator", "rep_origin", "gene"].iter() {
+            assert_eq!(FeatureKind::from_key(key).to_key(), *key);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use sequence::nucleotide::Nucleotide;
+use sequence::sequence::Sequence;
+use sequence::codon::Codon;
+use sequence::amino_acid::AminoAcid;
+use sequence::string_io::StringIO;
+
+/// An NCBI translation table.
+///
+/// Tables are stored in NCBI's layout: one amino acid per codon, with the
+/// codons ordered by first, second and third base, each running T, C, A, G.
+#[derive(Debug)]
+pub struct GeneticCode {
+    pub id: u8,
+    pub name: &'static str,
+    amino_acids: &'static str,
+}
+
+static CODES: [GeneticCode; 12] = [
+    GeneticCode { id: 1, name: "Standard",
+                  amino_acids: "FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG" },
+    GeneticCode { id: 2, name: "Vertebrate Mitochondrial",
+                  amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG" },
+    GeneticCode { id: 3, name: "Yeast Mitochondrial",
+                  amino_acids: "FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG" },
+    GeneticCode { id: 4, name: "Mold, Protozoan and Coelenterate Mitochondrial; Mycoplasma",
+                  amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG" },
+    GeneticCode { id: 5, name: "Invertebrate Mitochondrial",
+                  amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG" },
+    GeneticCode { id: 6, name: "Ciliate, Dasycladacean and Hexamita Nuclear",
+                  amino_acids: "FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG" },
+    GeneticCode { id: 9, name: "Echinoderm and Flatworm Mitochondrial",
+                  amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG" },
+    GeneticCode { id: 10, name: "Euplotid Nuclear",
+                  amino_acids: "FFLLSSSSYY**CCCWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG" },
+    GeneticCode { id: 11, name: "Bacterial, Archaeal and Plant Plastid",
+                  amino_acids: "FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG" },
+    GeneticCode { id: 12, name: "Alternative Yeast Nuclear",
+                  amino_acids: "FFLLSSSSYY**CC*WLLLSPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG" },
+    GeneticCode { id: 13, name: "Ascidian Mitochondrial",
+                  amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSGGVVVVAAAADDEEGGGG" },
+    GeneticCode { id: 14, name: "Alternative Flatworm Mitochondrial",
+                  amino_acids: "FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG" },
+];
+
+fn base_order(nt: char) -> usize {
+    match nt {
+        'T' => 0,
+        'C' => 1,
+        'A' => 2,
+        _ => 3,
+    }
+}
+
+impl GeneticCode {
+    pub fn standard() -> &'static GeneticCode {
+        &CODES[0]
+    }
+    pub fn from_id(id: u8) -> Option<&'static GeneticCode> {
+        CODES.iter().find(|code| code.id == id)
+    }
+    pub fn all() -> &'static [GeneticCode] {
+        &CODES
+    }
+    pub fn translate(&self, codon: &Codon<Nucleotide>) -> AminoAcid {
+        let index = codon.to_string().chars().fold(0, |index, nt| index * 4 + base_order(nt));
+        // Every table entry is one of the 20 amino acids or a stop.
+        AminoAcid::from_char(self.amino_acids.as_bytes()[index] as char).unwrap()
+    }
+    pub fn is_stop(&self, codon: &Codon<Nucleotide>) -> bool {
+        self.translate(codon) == AminoAcid::STOP
+    }
+}
+
+/// Anything that assigns an amino acid or stop to every codon.
+pub trait CodonTable {
+    fn translate(&self, codon: &Codon<Nucleotide>) -> AminoAcid;
+    fn is_stop(&self, codon: &Codon<Nucleotide>) -> bool {
+        self.translate(codon) == AminoAcid::STOP
+    }
+}
+
+impl CodonTable for GeneticCode {
+    fn translate(&self, codon: &Codon<Nucleotide>) -> AminoAcid {
+        GeneticCode::translate(self, codon)
+    }
+}
+
+/// An NCBI table with some codons reassigned, as in genetic code expansion
+/// where an orthogonal tRNA/synthetase pair decodes a stop codon as a
+/// non-canonical amino acid.
+#[derive(Debug)]
+pub struct ExpandedCode {
+    base: &'static GeneticCode,
+    reassigned: Vec<(Codon<Nucleotide>, AminoAcid)>,
+}
+
+impl ExpandedCode {
+    pub fn new(base: &'static GeneticCode) -> ExpandedCode {
+        ExpandedCode { base, reassigned: Vec::new() }
+    }
+    /// Amber suppression: the standard code with TAG read as `amino_acid`.
+    pub fn amber_suppression(amino_acid: AminoAcid) -> ExpandedCode {
+        let amber = Codon::<Nucleotide>::from_str("TAG").unwrap();
+        ExpandedCode::new(GeneticCode::standard()).reassign(amber, amino_acid)
+    }
+    /// Read `codon` as `amino_acid`, replacing any earlier reassignment.
+    pub fn reassign(mut self, codon: Codon<Nucleotide>, amino_acid: AminoAcid) -> ExpandedCode {
+        self.reassigned.retain(|(c, _)| *c != codon);
+        self.reassigned.push((codon, amino_acid));
+        self
+    }
+}
+
+impl CodonTable for ExpandedCode {
+    fn translate(&self, codon: &Codon<Nucleotide>) -> AminoAcid {
+        match self.reassigned.iter().find(|(c, _)| c == codon) {
+            Some((_, amino_acid)) => amino_acid.clone(),
+            None => self.base.translate(codon),
+        }
+    }
+}
+
+impl Sequence<Nucleotide> {
+    /// Translate complete codons with the given table; trailing bases that
+    /// do not fill a codon are ignored.
+    pub fn translate_with<C: CodonTable + ?Sized>(&self, code: &C) -> Sequence<AminoAcid> {
+        let mut aa_seq = Sequence::<AminoAcid>::new();
+        for codon in self.codons() {
+            aa_seq.push(code.translate(&codon));
+        }
+        aa_seq
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{CodonTable, ExpandedCode, GeneticCode};
+    use sequence::nucleotide::Nucleotide;
+    use sequence::codon::Codon;
+    use sequence::sequence::Sequence;
+    use sequence::amino_acid::{AminoAcid, NonCanonical};
+
+    #[test]
+    fn standard_matches_translate() {
+        for ch1 in "ACGT".chars() {
+            for ch2 in "ACGT".chars() {
+                for ch3 in "ACGT".chars() {
+                    let codon = Codon::<Nucleotide>::from_chars(ch1, ch2, ch3).unwrap();
+                    assert_eq!(GeneticCode::standard().translate(&codon),
+                               codon.translate().unwrap());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn alternative_tables() {
+        let tga = Codon::<Nucleotide>::from_str("TGA").unwrap();
+        assert!(GeneticCode::standard().is_stop(&tga));
+        assert_eq!(GeneticCode::from_id(2).unwrap().translate(&tga), AminoAcid::W);
+        assert!(GeneticCode::from_id(7).is_none());
+        let seq = Sequence::<Nucleotide>::from_str("ATGTGAAAAC").unwrap();
+        assert_eq!(seq.translate_with(GeneticCode::from_id(4).unwrap()).to_string(), "MWK");
+    }
+
+    #[test]
+    fn amber_suppression() {
+        let azf = AminoAcid::NonCanonical(
+            Arc::new(NonCanonical::new("AzF", "p-azido-L-phenylalanine", 188.19, 188.0698)));
+        let code = ExpandedCode::amber_suppression(azf.clone());
+        let seq = Sequence::<Nucleotide>::from_str("ATGTAGAAATAA").unwrap();
+        let protein = seq.translate_with(&code);
+        assert_eq!(protein.to_string(), "MXK*");
+        assert_eq!(protein.get(1).unwrap(), azf);
+        assert!(code.is_stop(&Codon::<Nucleotide>::from_str("TAA").unwrap()));
+        assert!(!code.is_stop(&Codon::<Nucleotide>::from_str("TAG").unwrap()));
+    }
+}
@@ -1,7 +1,18 @@
-mod string_io;
+pub mod string_io;
+pub mod error;
 pub mod nucleotide;
 pub mod codon;
 pub mod amino_acid;
 pub mod degenerate_nucleotide;
 pub mod translate;
+#[allow(clippy::module_inception)]
 pub mod sequence;
+pub mod packed;
+pub mod storage;
+pub mod strand;
+pub mod feature;
+pub mod genetic_code;
+pub mod protein;
+pub mod nucleic_acid;
+#[cfg(feature = "serde")]
+mod serialize;
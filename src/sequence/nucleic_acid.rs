@@ -0,0 +1,299 @@
+use sequence::sequence::Sequence;
+use sequence::nucleotide::Nucleotide;
+use sequence::degenerate_nucleotide::DegenerateNucleotide;
+use sequence::feature::Topology;
+
+/// Anhydrous masses of the nucleotide monophosphates within a strand, in
+/// A, C, G, T order, and the correction for a strand with a 5' hydroxyl.
+const RESIDUE_MASS: [f64; 4] = [313.21, 289.18, 329.21, 304.2];
+const END_CORRECTION: f64 = -61.96;
+/// Mass added by each 5' phosphate.
+const PHOSPHATE: f64 = 79.98;
+
+/// Extinction coefficients at 260 nm in M⁻¹ cm⁻¹ of single-stranded DNA
+/// mononucleotides and dinucleotides, in A, C, G, T order, from the
+/// nearest-neighbour model of Cantor, Warshaw and Shapiro (1970).
+const EXTINCTION_BASE: [f64; 4] = [15400.0, 7400.0, 11500.0, 8700.0];
+const EXTINCTION_PAIR: [[f64; 4]; 4] = [
+    [27400.0, 21200.0, 25000.0, 22800.0],
+    [21200.0, 14600.0, 18000.0, 15200.0],
+    [25200.0, 17600.0, 21600.0, 20000.0],
+    [23400.0, 16200.0, 19000.0, 16800.0],
+];
+
+/// What is attached to each 5' end.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum FivePrime {
+    /// A free hydroxyl, as on unmodified synthetic oligos.
+    Hydroxyl,
+    Phosphate,
+    Triphosphate,
+    /// Any other modification, given as the mass it adds in daltons.
+    Modified(f64),
+}
+
+impl FivePrime {
+    fn mass(&self) -> f64 {
+        match *self {
+            FivePrime::Hydroxyl => 0.0,
+            FivePrime::Phosphate => PHOSPHATE,
+            FivePrime::Triphosphate => 3.0 * PHOSPHATE,
+            FivePrime::Modified(mass) => mass,
+        }
+    }
+}
+
+/// The range of GC content, as a fraction, over every sequence a degenerate
+/// sequence stands for, and its mean over an equimolar mixture.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct GcContent {
+    pub min: f64,
+    pub mean: f64,
+    pub max: f64,
+}
+
+/// GC content and skew of one window of a sequence.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct GcWindow {
+    pub start: usize,
+    pub gc: f64,
+    pub skew: f64,
+}
+
+fn index(nt: Nucleotide) -> usize {
+    match nt {
+        Nucleotide::A => 0,
+        Nucleotide::C => 1,
+        Nucleotide::G => 2,
+        Nucleotide::T => 3,
+    }
+}
+
+/// The fraction of each base at a position, in A, C, G, T order.
+fn weights(dnt: &DegenerateNucleotide) -> [f64; 4] {
+    let bases = dnt.expand();
+    let mut weights = [0.0; 4];
+    for &nt in bases.iter() {
+        weights[index(nt)] += 1.0 / bases.len() as f64;
+    }
+    weights
+}
+
+fn one_hot(nt: &Nucleotide) -> [f64; 4] {
+    let mut weights = [0.0; 4];
+    weights[index(*nt)] = 1.0;
+    weights
+}
+
+/// Single-stranded mass of a strand of base mixtures; zero when empty.
+fn strand_mass(strand: &[[f64; 4]], five_prime: FivePrime) -> f64 {
+    if strand.is_empty() {
+        return 0.0;
+    }
+    let residues: f64 = strand.iter()
+        .map(|w| w.iter().zip(RESIDUE_MASS.iter()).map(|(w, m)| w * m).sum::<f64>())
+        .sum();
+    residues + END_CORRECTION + five_prime.mass()
+}
+
+/// Nearest-neighbour extinction coefficient: the sum over dinucleotides
+/// less the internal mononucleotides, each term averaged over the mixtures.
+fn strand_extinction(strand: &[[f64; 4]]) -> f64 {
+    let base = |w: &[f64; 4]| -> f64 {
+        w.iter().zip(EXTINCTION_BASE.iter()).map(|(w, e)| w * e).sum()
+    };
+    match strand.len() {
+        0 => 0.0,
+        1 => base(&strand[0]),
+        len => {
+            let mut total = 0.0;
+            for pair in strand.windows(2) {
+                for i in 0..4 {
+                    for j in 0..4 {
+                        total += pair[0][i] * pair[1][j] * EXTINCTION_PAIR[i][j];
+                    }
+                }
+            }
+            total - strand[1..len-1].iter().map(base).sum::<f64>()
+        }
+    }
+}
+
+fn complement(strand: &[[f64; 4]]) -> Vec<[f64; 4]> {
+    strand.iter().rev().map(|w| [w[3], w[2], w[1], w[0]]).collect()
+}
+
+/// Physical properties of DNA. Masses are in daltons (g/mol) for linear
+/// molecules, and extinction coefficients in M⁻¹ cm⁻¹ at 260 nm for single
+/// strands.
+impl Sequence<Nucleotide> {
+    fn count(&self, nt: Nucleotide) -> usize {
+        self.iter().filter(|&&n| n == nt).count()
+    }
+
+    fn weights(&self) -> Vec<[f64; 4]> {
+        self.iter().map(one_hot).collect()
+    }
+
+    /// Fraction of G and C bases; zero for an empty sequence.
+    pub fn gc_content(&self) -> f64 {
+        let gc = self.count(Nucleotide::G) + self.count(Nucleotide::C);
+        if self.is_empty() { 0.0 } else { gc as f64 / self.len() as f64 }
+    }
+
+    /// (G - C) / (G + C), or zero without either.
+    pub fn gc_skew(&self) -> f64 {
+        skew(self.count(Nucleotide::G), self.count(Nucleotide::C))
+    }
+
+    /// GC content and skew of windows of `window` bases every `step` bases.
+    /// On a circular sequence windows start at every step round the whole
+    /// sequence and wrap across the origin; on a linear one they stop at the
+    /// last full window.
+    pub fn gc_windows(&self, window: usize, step: usize, topology: Topology) -> Vec<GcWindow> {
+        let length = self.len();
+        if window == 0 || window > length {
+            return Vec::new();
+        }
+        // Running counts of G and C, twice round for circular sequences.
+        let bases: Vec<Nucleotide> = self.iter().chain(self.iter()).cloned().collect();
+        let mut g = vec![0];
+        let mut c = vec![0];
+        for &nt in bases.iter() {
+            g.push(g[g.len()-1] + (nt == Nucleotide::G) as usize);
+            c.push(c[c.len()-1] + (nt == Nucleotide::C) as usize);
+        }
+        let last = match topology {
+            Topology::Linear => length - window,
+            Topology::Circular => length - 1,
+        };
+        (0..last + 1).step_by(step.max(1)).map(|start| {
+            let (g, c) = (g[start + window] - g[start], c[start + window] - c[start]);
+            GcWindow { start, gc: (g + c) as f64 / window as f64, skew: skew(g, c) }
+        }).collect()
+    }
+
+    /// Mass of the single strand, with the given 5' end.
+    pub fn single_stranded_mass(&self, five_prime: FivePrime) -> f64 {
+        strand_mass(&self.weights(), five_prime)
+    }
+
+    /// Mass of the duplex with its complement, both strands having the given
+    /// 5' end.
+    pub fn double_stranded_mass(&self, five_prime: FivePrime) -> f64 {
+        let strand = self.weights();
+        strand_mass(&strand, five_prime) + strand_mass(&complement(&strand), five_prime)
+    }
+
+    pub fn extinction_coefficient(&self) -> f64 {
+        strand_extinction(&self.weights())
+    }
+}
+
+/// The same properties for degenerate sequences, averaged over an equimolar
+/// mixture of every sequence they stand for, as synthesised with mixed
+/// bases.
+impl Sequence<DegenerateNucleotide> {
+    fn weights(&self) -> Vec<[f64; 4]> {
+        self.iter().map(weights).collect()
+    }
+
+    pub fn gc_content(&self) -> GcContent {
+        if self.is_empty() {
+            return GcContent { min: 0.0, mean: 0.0, max: 0.0 };
+        }
+        let (mut min, mut mean, mut max) = (0, 0.0, 0);
+        for w in self.weights() {
+            let gc = w[1] + w[2];
+            mean += gc;
+            if gc == 1.0 { min += 1; }
+            if gc > 0.0 { max += 1; }
+        }
+        let length = self.len() as f64;
+        GcContent { min: min as f64 / length, mean: mean / length, max: max as f64 / length }
+    }
+
+    pub fn single_stranded_mass(&self, five_prime: FivePrime) -> f64 {
+        strand_mass(&self.weights(), five_prime)
+    }
+
+    pub fn double_stranded_mass(&self, five_prime: FivePrime) -> f64 {
+        let strand = self.weights();
+        strand_mass(&strand, five_prime) + strand_mass(&complement(&strand), five_prime)
+    }
+
+    pub fn extinction_coefficient(&self) -> f64 {
+        strand_extinction(&self.weights())
+    }
+}
+
+fn skew(g: usize, c: usize) -> f64 {
+    if g + c == 0 { 0.0 } else { (g as f64 - c as f64) / (g + c) as f64 }
+}
+
+/// Nanomoles in `micrograms` of a molecule of the given mass in daltons.
+pub fn nmol_from_micrograms(micrograms: f64, mass: f64) -> f64 {
+    micrograms * 1000.0 / mass
+}
+
+pub fn micrograms_from_nmol(nmol: f64, mass: f64) -> f64 {
+    nmol * mass / 1000.0
+}
+
+/// Nanomoles in `od` A260 units (the absorbance of 1 ml in a 1 cm cell) of
+/// a molecule with the given extinction coefficient.
+pub fn nmol_from_od260(od: f64, extinction: f64) -> f64 {
+    od * 1e6 / extinction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FivePrime, GcContent, nmol_from_micrograms, micrograms_from_nmol,
+                nmol_from_od260};
+    use sequence::sequence::Sequence;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::degenerate_nucleotide::DegenerateNucleotide;
+    use sequence::feature::Topology;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn gc() {
+        let seq = Sequence::<Nucleotide>::from_str("GGGCAT").unwrap();
+        assert!(close(seq.gc_content(), 4.0 / 6.0));
+        assert!(close(seq.gc_skew(), 0.5));
+        let linear = seq.gc_windows(3, 2, Topology::Linear);
+        assert_eq!(linear.iter().map(|w| w.start).collect::<Vec<_>>(), vec![0, 2]);
+        assert!(close(linear[1].gc, 2.0 / 3.0) && close(linear[1].skew, 0.0));
+        let circular = seq.gc_windows(3, 2, Topology::Circular);
+        assert_eq!(circular.len(), 3);
+        assert!(close(circular[2].gc, 1.0 / 3.0));
+        let degenerate = Sequence::<DegenerateNucleotide>::from_str("SNAW").unwrap();
+        assert_eq!(degenerate.gc_content(), GcContent { min: 0.25, mean: 0.375, max: 0.5 });
+    }
+
+    #[test]
+    fn mass_and_extinction() {
+        let a = Sequence::<Nucleotide>::from_str("A").unwrap();
+        assert!(close(a.single_stranded_mass(FivePrime::Hydroxyl), 251.25));
+        assert!(close(a.single_stranded_mass(FivePrime::Phosphate), 331.23));
+        assert!(close(a.double_stranded_mass(FivePrime::Hydroxyl), 251.25 + 242.24));
+        let acg = Sequence::<Nucleotide>::from_str("ACG").unwrap();
+        assert!(close(acg.extinction_coefficient(), 21200.0 + 18000.0 - 7400.0));
+        let n = Sequence::<DegenerateNucleotide>::from_str("N").unwrap();
+        assert!(close(n.extinction_coefficient(), 43000.0 / 4.0));
+        let ag = Sequence::<DegenerateNucleotide>::from_str("AG").unwrap();
+        let ag_exact = Sequence::<Nucleotide>::from_str("AG").unwrap();
+        assert!(close(ag.single_stranded_mass(FivePrime::Hydroxyl),
+                      ag_exact.single_stranded_mass(FivePrime::Hydroxyl)));
+    }
+
+    #[test]
+    fn conversions() {
+        assert!(close(nmol_from_micrograms(1.0, 10000.0), 0.1));
+        assert!(close(micrograms_from_nmol(0.1, 10000.0), 1.0));
+        assert!(close(nmol_from_od260(1.0, 200000.0), 5.0));
+    }
+}
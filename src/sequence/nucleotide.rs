@@ -1,4 +1,5 @@
 use sequence::string_io::StringIO;
+use sequence::error::SequenceError;
 
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub enum Nucleotide {
@@ -12,15 +13,16 @@ pub trait Complement {
 
 impl StringIO for Nucleotide {
     type N = Nucleotide;
-    fn from_char(input: char) -> Result<Nucleotide, String> {
+    fn from_char(input: char) -> Result<Nucleotide, SequenceError> {
         match input {
             'A' | 'a' => Ok(Nucleotide::A),
             'C' | 'c' => Ok(Nucleotide::C),
             'G' | 'g' => Ok(Nucleotide::G),
             'T' | 't' => Ok(Nucleotide::T),
-            bad_nt => Err(format!("Bad nucleotide specifier: {}",bad_nt))
+            bad_nt => Err(SequenceError::InvalidSymbol { symbol: bad_nt, offset: 0 })
         }
     }
+    #[allow(clippy::match_ref_pats)]
     fn to_char(&self) -> char {
         match self {
             &Nucleotide::A => 'A',
@@ -33,6 +35,7 @@ impl StringIO for Nucleotide {
 
 impl Complement for Nucleotide {
     type N = Nucleotide;
+    #[allow(clippy::match_ref_pats)]
     fn complement(&self) -> Nucleotide {
         match self {
             &Nucleotide::A => Nucleotide::T,
@@ -0,0 +1,277 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use sequence::nucleotide::{Nucleotide, Complement};
+use sequence::degenerate_nucleotide::DegenerateNucleotide;
+use sequence::storage::{Storage, Stored};
+
+/// Symbols that can be stored in a fixed-width bit field.
+///
+/// `BITS` must divide 64, so that no symbol straddles two storage words.
+pub trait Pack: Sized {
+    const BITS: usize;
+    fn pack(&self) -> u64;
+    /// The symbol packed as `bits`, as a reference to a canonical copy.
+    fn unpack(bits: u64) -> &'static Self;
+    /// Complement every symbol packed into `word` at once.
+    fn complement_word(word: u64) -> u64;
+}
+
+impl Stored for Nucleotide {
+    type Storage = PackedSymbols<Nucleotide>;
+}
+
+impl Stored for DegenerateNucleotide {
+    type Storage = PackedSymbols<DegenerateNucleotide>;
+}
+
+/// Nucleotides are stored two bits per base, with A=0, C=1, G=2, T=3, so the
+/// complement of a base is its bitwise inverse.
+impl Pack for Nucleotide {
+    const BITS: usize = 2;
+    #[allow(clippy::match_ref_pats)]
+    fn pack(&self) -> u64 {
+        match self {
+            &Nucleotide::A => 0,
+            &Nucleotide::C => 1,
+            &Nucleotide::G => 2,
+            &Nucleotide::T => 3,
+        }
+    }
+    fn unpack(bits: u64) -> &'static Nucleotide {
+        static BASES: [Nucleotide; 4] = [Nucleotide::A, Nucleotide::C, Nucleotide::G,
+                                         Nucleotide::T];
+        &BASES[(bits & 0b11) as usize]
+    }
+    fn complement_word(word: u64) -> u64 {
+        !word
+    }
+}
+
+/// Degenerate nucleotides are stored as a four-bit mask of the bases they
+/// stand for (A=1, C=2, G=4, T=8), so the complement of a symbol is its mask
+/// with the bit order reversed.
+impl Pack for DegenerateNucleotide {
+    const BITS: usize = 4;
+    #[allow(clippy::match_ref_pats)]
+    fn pack(&self) -> u64 {
+        match self {
+            &DegenerateNucleotide::A => 0b0001,
+            &DegenerateNucleotide::C => 0b0010,
+            &DegenerateNucleotide::G => 0b0100,
+            &DegenerateNucleotide::T => 0b1000,
+            &DegenerateNucleotide::R => 0b0101,
+            &DegenerateNucleotide::Y => 0b1010,
+            &DegenerateNucleotide::S => 0b0110,
+            &DegenerateNucleotide::W => 0b1001,
+            &DegenerateNucleotide::K => 0b1100,
+            &DegenerateNucleotide::M => 0b0011,
+            &DegenerateNucleotide::B => 0b1110,
+            &DegenerateNucleotide::D => 0b1101,
+            &DegenerateNucleotide::H => 0b1011,
+            &DegenerateNucleotide::V => 0b0111,
+            &DegenerateNucleotide::N => 0b1111,
+        }
+    }
+    fn unpack(bits: u64) -> &'static DegenerateNucleotide {
+        use sequence::degenerate_nucleotide::DegenerateNucleotide::*;
+        // Indexed by mask; the empty mask never occurs.
+        static SYMBOLS: [DegenerateNucleotide; 16] = [N, A, C, M, G, R, S, V,
+                                                      T, W, Y, H, K, D, B, N];
+        &SYMBOLS[(bits & 0b1111) as usize]
+    }
+    fn complement_word(word: u64) -> u64 {
+        let word = ((word >> 1) & 0x5555_5555_5555_5555) | ((word & 0x5555_5555_5555_5555) << 1);
+        ((word >> 2) & 0x3333_3333_3333_3333) | ((word & 0x3333_3333_3333_3333) << 2)
+    }
+}
+
+/// Reverse the order of the `bits`-wide fields packed into `word`.
+fn reverse_fields(mut word: u64, bits: usize) -> u64 {
+    const MASKS: [u64; 6] = [0x5555_5555_5555_5555, 0x3333_3333_3333_3333,
+                             0x0f0f_0f0f_0f0f_0f0f, 0x00ff_00ff_00ff_00ff,
+                             0x0000_ffff_0000_ffff, 0x0000_0000_ffff_ffff];
+    let mut width = bits;
+    while width < 64 {
+        let mask = MASKS[width.trailing_zeros() as usize];
+        word = ((word >> width) & mask) | ((word & mask) << width);
+        width *= 2;
+    }
+    word
+}
+
+/// Symbols stored `N::BITS` bits each, which is how sequences of
+/// nucleotides are kept: a whole host genome loaded for specificity checks
+/// then takes a quarter of a byte per base.
+///
+/// Symbol `i` lives in word `i / (64 / N::BITS)`, least significant field
+/// first; unused fields in the last word are kept zero.
+pub struct PackedSymbols<N> {
+    words: Vec<u64>,
+    len: usize,
+    phantom: PhantomData<N>,
+}
+
+impl<N> Clone for PackedSymbols<N> {
+    fn clone(&self) -> PackedSymbols<N> {
+        PackedSymbols::<N> { words: self.words.clone(), len: self.len, phantom: PhantomData }
+    }
+}
+
+impl<N> Default for PackedSymbols<N> {
+    fn default() -> PackedSymbols<N> {
+        PackedSymbols::<N> { words: Vec::new(), len: 0, phantom: PhantomData }
+    }
+}
+
+impl<N> PartialEq for PackedSymbols<N> {
+    fn eq(&self, other: &PackedSymbols<N>) -> bool {
+        self.len == other.len && self.words == other.words
+    }
+}
+
+/// Lists the symbols, as a `Vec` of them would.
+impl<N> fmt::Debug for PackedSymbols<N> where N: Pack + fmt::Debug + 'static {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries((0..self.len).map(|i| N::unpack(self.field(i)))).finish()
+    }
+}
+
+impl<N> PackedSymbols<N> where N: Pack {
+    fn per_word() -> usize {
+        64 / N::BITS
+    }
+    fn field_mask() -> u64 {
+        (1 << N::BITS) - 1
+    }
+    fn field(&self, index: usize) -> u64 {
+        let per_word = Self::per_word();
+        (self.words[index / per_word] >> ((index % per_word) * N::BITS)) & Self::field_mask()
+    }
+    /// Iterate over every `k`-mer as an integer code, first symbol in the
+    /// most significant field, so codes sort in the same order as strings.
+    ///
+    /// Panics if `k` is zero or `k` symbols do not fit in 64 bits.
+    pub fn kmers(&self, k: usize) -> KmerIterator<'_,N> {
+        assert!(k > 0 && k * N::BITS <= 64, "k-mer length {} does not fit in a u64", k);
+        let mask = if k * N::BITS == 64 { !0 } else { (1 << (k * N::BITS)) - 1 };
+        KmerIterator::<N> { symbols: self, k, mask, index: 0, code: 0 }
+    }
+}
+
+impl<N> Storage<N> for PackedSymbols<N> where N: Pack + fmt::Debug + 'static {
+    fn push(&mut self, item: N) {
+        let per_word = Self::per_word();
+        if self.len.is_multiple_of(per_word) {
+            self.words.push(0);
+        }
+        let last = self.words.len() - 1;
+        self.words[last] |= item.pack() << ((self.len % per_word) * N::BITS);
+        self.len += 1;
+    }
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn get(&self, index: usize) -> Option<&N> {
+        if index < self.len { Some(N::unpack(self.field(index))) } else { None }
+    }
+    /// Reverse complement a whole word at a time rather than base by base.
+    fn reverse_complement(&self) -> PackedSymbols<N> where N: Complement<N=N> {
+        let per_word = Self::per_word();
+        let mut words: Vec<u64> = self.words.iter().rev()
+            .map(|&w| N::complement_word(reverse_fields(w, N::BITS)))
+            .collect();
+        // The padding at the end of the last word is now at the start of the
+        // first; shift everything down by that many fields.
+        let shift = ((per_word - self.len % per_word) % per_word) * N::BITS;
+        if shift > 0 {
+            for i in 0..words.len() {
+                let next = if i+1 < words.len() { words[i+1] << (64 - shift) } else { 0 };
+                words[i] = (words[i] >> shift) | next;
+            }
+        }
+        if let Some(last) = words.last_mut() {
+            let used = (self.len - 1) % per_word + 1;
+            if used < per_word {
+                *last &= (1 << (used * N::BITS)) - 1;
+            }
+        }
+        PackedSymbols::<N> { words, len: self.len, phantom: PhantomData }
+    }
+}
+
+pub struct KmerIterator<'a,N> where N: 'a {
+    symbols: &'a PackedSymbols<N>,
+    k: usize,
+    mask: u64,
+    index: usize,
+    code: u64,
+}
+
+impl<'a,N> Iterator for KmerIterator<'a,N> where N: Pack {
+    type Item = u64;
+    fn next(&mut self) -> Option<u64> {
+        while self.index < self.symbols.len {
+            let field = self.symbols.field(self.index);
+            self.code = ((self.code << N::BITS) | field) & self.mask;
+            self.index += 1;
+            if self.index >= self.k {
+                return Some(self.code);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sequence::nucleotide::{Nucleotide, Complement};
+    use sequence::degenerate_nucleotide::DegenerateNucleotide;
+    use sequence::string_io::StringIO;
+    use sequence::sequence::Sequence;
+
+    /// The reverse complement worked out a character at a time.
+    fn naive_reverse_complement<N>(s: &str) -> String where N: StringIO<N=N> + Complement<N=N> {
+        s.chars().rev().map(|ch| N::from_char(ch).unwrap().complement().to_char()).collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let s = "ACGTACGTAAGATCTCGGATCCGATTACAGATTACAGATTACA";
+        let nts = Sequence::<Nucleotide>::from_str(s).unwrap();
+        assert_eq!(nts.len(), s.len());
+        assert_eq!(nts.to_string(), s);
+        assert_eq!(nts.iter().rev().count(), s.len());
+        assert_eq!(nts.get(40).unwrap(), Nucleotide::A);
+        let dnts = Sequence::<DegenerateNucleotide>::from_str("ANYTGCYRSWKMBDHV").unwrap();
+        assert_eq!(dnts.to_string(), "ANYTGCYRSWKMBDHV");
+        assert_eq!(format!("{:?}", Sequence::<Nucleotide>::from_str("GAT").unwrap()),
+                   "Sequence { data: [G, A, T] }");
+    }
+
+    #[test]
+    fn reverse_complement() {
+        // Exercise lengths either side of the 32- and 16-symbol word boundaries.
+        let s = "GTAAAACGATTACAGGCCTTAGGCATCGATCGAATTCCGGTACCAGT";
+        for len in 0..s.len() {
+            let seq = Sequence::<Nucleotide>::from_str(&s[..len]).unwrap();
+            assert_eq!(seq.reverse_complement().to_string(),
+                       naive_reverse_complement::<Nucleotide>(&s[..len]));
+            let dseq = Sequence::<DegenerateNucleotide>::from_str(&s[..len]).unwrap();
+            assert_eq!(dseq.reverse_complement().to_string(),
+                       naive_reverse_complement::<DegenerateNucleotide>(&s[..len]));
+        }
+        let dnts = Sequence::<DegenerateNucleotide>::from_str("GTANYKR").unwrap();
+        assert_eq!("YMRNTAC", dnts.reverse_complement().to_string());
+    }
+
+    #[test]
+    fn kmers() {
+        let nts = Sequence::<Nucleotide>::from_str("ACGTT").unwrap();
+        let kmers: Vec<u64> = nts.kmers(3).collect();
+        assert_eq!(kmers, vec![0b000110, 0b011011, 0b101111]);
+        assert_eq!(nts.kmers(6).count(), 0);
+        let long = Sequence::<Nucleotide>::from_str(&"ACGT".repeat(20)).unwrap();
+        assert_eq!(long.kmers(32).count(), 80 - 32 + 1);
+    }
+}
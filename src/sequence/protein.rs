@@ -0,0 +1,355 @@
+use sequence::sequence::Sequence;
+use sequence::amino_acid::AminoAcid;
+
+/// Average and monoisotopic masses of water, lost once per peptide bond.
+const WATER_AVERAGE: f64 = 18.01524;
+const WATER_MONOISOTOPIC: f64 = 18.01056;
+
+/// Molar extinction coefficients at 280 nm in water, per Pace et al. (1995).
+const EXTINCTION_W: u32 = 5500;
+const EXTINCTION_Y: u32 = 1490;
+const EXTINCTION_CYSTINE: u32 = 125;
+
+/// The twenty standard residues in the order used by the tables below.
+const RESIDUES: [AminoAcid; 20] = [
+    AminoAcid::A, AminoAcid::C, AminoAcid::D, AminoAcid::E, AminoAcid::F,
+    AminoAcid::G, AminoAcid::H, AminoAcid::I, AminoAcid::K, AminoAcid::L,
+    AminoAcid::M, AminoAcid::N, AminoAcid::P, AminoAcid::Q, AminoAcid::R,
+    AminoAcid::S, AminoAcid::T, AminoAcid::V, AminoAcid::W, AminoAcid::Y,
+];
+
+/// Dipeptide instability weights from Guruprasad et al. (1990), indexed by
+/// the first and then the second residue in `RESIDUES` order.
+const DIWV: [[f64; 20]; 20] = [
+    // A
+    [1.0, 44.94, -7.49, 1.0, 1.0, 1.0, -7.49, 1.0, 1.0, 1.0,
+     1.0, 1.0, 20.26, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0],
+    // C
+    [1.0, 1.0, 20.26, 1.0, 1.0, 1.0, 33.60, 1.0, 1.0, 20.26,
+     33.60, 1.0, 20.26, -6.54, 1.0, 1.0, 33.60, -6.54, 24.68, 1.0],
+    // D
+    [1.0, 1.0, 1.0, 1.0, -6.54, 1.0, 1.0, 1.0, -7.49, 1.0,
+     1.0, 1.0, 1.0, 1.0, -6.54, 20.26, -14.03, 1.0, 1.0, 1.0],
+    // E
+    [1.0, 44.94, 20.26, 33.60, 1.0, 1.0, -6.54, 20.26, 1.0, 1.0,
+     1.0, 1.0, 20.26, 20.26, 1.0, 20.26, 1.0, 1.0, -14.03, 1.0],
+    // F
+    [1.0, 1.0, 13.34, 1.0, 1.0, 1.0, 1.0, 1.0, -14.03, 1.0,
+     1.0, 1.0, 20.26, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 33.601],
+    // G
+    [-7.49, 1.0, 1.0, -6.54, 1.0, 13.34, 1.0, -7.49, -7.49, 1.0,
+     1.0, -7.49, 1.0, 1.0, 1.0, 1.0, -7.49, 1.0, 13.34, -7.49],
+    // H
+    [1.0, 1.0, 1.0, 1.0, -9.37, -9.37, 1.0, 44.94, 24.68, 1.0,
+     1.0, 24.68, -1.88, 1.0, 1.0, 1.0, -6.54, 1.0, -1.88, 44.94],
+    // I
+    [1.0, 1.0, 1.0, 44.94, 1.0, 1.0, 13.34, 1.0, -7.49, 20.26,
+     1.0, 1.0, -1.88, 1.0, 1.0, 1.0, 1.0, -7.49, 1.0, 1.0],
+    // K
+    [1.0, 1.0, 1.0, 1.0, 1.0, -7.49, 1.0, -7.49, 1.0, -7.49,
+     33.60, 1.0, -6.54, 24.64, 33.60, 1.0, 1.0, -7.49, 1.0, 1.0],
+    // L
+    [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, -7.49, 1.0,
+     1.0, 1.0, 20.26, 33.60, 20.26, 1.0, 1.0, 1.0, 24.68, 1.0],
+    // M
+    [13.34, 1.0, 1.0, 1.0, 1.0, 1.0, 58.28, 1.0, 1.0, 1.0,
+     -1.88, 1.0, 44.94, -6.54, -6.54, 44.94, -1.88, 1.0, 1.0, 24.68],
+    // N
+    [1.0, -1.88, 1.0, 1.0, -14.03, -14.03, 1.0, 44.94, 24.68, 1.0,
+     1.0, 1.0, -1.88, -6.54, 1.0, 1.0, -7.49, 1.0, -9.37, 1.0],
+    // P
+    [20.26, -6.54, -6.54, 18.38, 20.26, 1.0, 1.0, 1.0, 1.0, 1.0,
+     -6.54, 1.0, 20.26, 20.26, -6.54, 20.26, 1.0, 20.26, -1.88, 1.0],
+    // Q
+    [1.0, -6.54, 20.26, 20.26, -6.54, 1.0, 1.0, 1.0, 1.0, 1.0,
+     1.0, 1.0, 20.26, 20.26, 1.0, 44.94, 1.0, -6.54, 1.0, -6.54],
+    // R
+    [1.0, 1.0, 1.0, 1.0, 1.0, -7.49, 20.26, 1.0, 1.0, 1.0,
+     1.0, 13.34, 20.26, 20.26, 58.28, 44.94, 1.0, 1.0, 58.28, -6.54],
+    // S
+    [1.0, 33.60, 1.0, 20.26, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0,
+     1.0, 1.0, 44.94, 20.26, 20.26, 20.26, 1.0, 1.0, 1.0, 1.0],
+    // T
+    [1.0, 1.0, 1.0, 20.26, 13.34, -7.49, 1.0, 1.0, 1.0, 1.0,
+     1.0, -14.03, 1.0, -6.54, 1.0, 1.0, 1.0, 1.0, -14.03, 1.0],
+    // V
+    [1.0, 1.0, -14.03, 1.0, 1.0, -7.49, 1.0, 1.0, -1.88, 1.0,
+     1.0, 1.0, 20.26, 1.0, 1.0, 1.0, -7.49, 1.0, 1.0, -6.54],
+    // W
+    [-14.03, 1.0, 1.0, 1.0, 1.0, -9.37, 24.68, 1.0, 1.0, 13.34,
+     24.68, 13.34, 1.0, 1.0, 1.0, 1.0, -14.03, -7.49, 1.0, 1.0],
+    // Y
+    [24.68, 1.0, 24.68, -6.54, 1.0, -7.49, 13.34, 1.0, 1.0, 1.0,
+     44.94, 1.0, 13.34, 1.0, -15.91, 1.0, -7.49, 1.0, -9.37, 13.34],
+];
+
+/// Average and monoisotopic residue masses. Ambiguity codes take the mean
+/// of their alternatives, and an unknown residue an average residue.
+fn masses(aa: &AminoAcid) -> (f64, f64) {
+    match *aa {
+        AminoAcid::A => (71.0788, 71.03711),
+        AminoAcid::C => (103.1388, 103.00919),
+        AminoAcid::D => (115.0886, 115.02694),
+        AminoAcid::E => (129.1155, 129.04259),
+        AminoAcid::F => (147.1766, 147.06841),
+        AminoAcid::G => (57.0519, 57.02146),
+        AminoAcid::H => (137.1411, 137.05891),
+        AminoAcid::I => (113.1594, 113.08406),
+        AminoAcid::K => (128.1741, 128.09496),
+        AminoAcid::L => (113.1594, 113.08406),
+        AminoAcid::M => (131.1926, 131.04049),
+        AminoAcid::N => (114.1038, 114.04293),
+        AminoAcid::P => (97.1167, 97.05276),
+        AminoAcid::Q => (128.1307, 128.05858),
+        AminoAcid::R => (156.1875, 156.10111),
+        AminoAcid::S => (87.0782, 87.03203),
+        AminoAcid::T => (101.1051, 101.04768),
+        AminoAcid::V => (99.1326, 99.06841),
+        AminoAcid::W => (186.2132, 186.07931),
+        AminoAcid::Y => (163.1760, 163.06333),
+        AminoAcid::U => (150.0388, 150.95364),
+        AminoAcid::O => (237.2982, 237.14773),
+        AminoAcid::B => (114.5962, 114.53494),
+        AminoAcid::Z => (128.6231, 128.55059),
+        AminoAcid::J => (113.1594, 113.08406),
+        AminoAcid::X => (110.0, 110.0),
+        AminoAcid::NonCanonical(ref nc) => (nc.average_mass, nc.monoisotopic_mass),
+        AminoAcid::STOP => (0.0, 0.0),
+    }
+}
+
+/// Kyte-Doolittle hydropathy, where defined.
+fn hydropathy(aa: &AminoAcid) -> Option<f64> {
+    match *aa {
+        AminoAcid::A => Some(1.8),
+        AminoAcid::C => Some(2.5),
+        AminoAcid::D => Some(-3.5),
+        AminoAcid::E => Some(-3.5),
+        AminoAcid::F => Some(2.8),
+        AminoAcid::G => Some(-0.4),
+        AminoAcid::H => Some(-3.2),
+        AminoAcid::I => Some(4.5),
+        AminoAcid::K => Some(-3.9),
+        AminoAcid::L => Some(3.8),
+        AminoAcid::M => Some(1.9),
+        AminoAcid::N => Some(-3.5),
+        AminoAcid::P => Some(-1.6),
+        AminoAcid::Q => Some(-3.5),
+        AminoAcid::R => Some(-4.5),
+        AminoAcid::S => Some(-0.8),
+        AminoAcid::T => Some(-0.7),
+        AminoAcid::V => Some(4.2),
+        AminoAcid::W => Some(-0.9),
+        AminoAcid::Y => Some(-1.3),
+        AminoAcid::U => Some(2.5),
+        AminoAcid::B | AminoAcid::Z => Some(-3.5),
+        AminoAcid::J => Some(4.15),
+        _ => None,
+    }
+}
+
+fn index(aa: &AminoAcid) -> Option<usize> {
+    RESIDUES.iter().position(|r| r == aa)
+}
+
+/// Bjellqvist pK values of ionisable side chains, as used by ProtParam;
+/// positive groups first.
+fn side_chain_pk(aa: &AminoAcid) -> Option<(f64, bool)> {
+    match *aa {
+        AminoAcid::K => Some((10.0, true)),
+        AminoAcid::R => Some((12.0, true)),
+        AminoAcid::H => Some((5.98, true)),
+        AminoAcid::D => Some((4.05, false)),
+        AminoAcid::E => Some((4.45, false)),
+        AminoAcid::C => Some((9.0, false)),
+        AminoAcid::Y => Some((10.0, false)),
+        _ => None,
+    }
+}
+
+fn n_terminal_pk(aa: &AminoAcid) -> f64 {
+    match *aa {
+        AminoAcid::A => 7.59,
+        AminoAcid::M => 7.0,
+        AminoAcid::S => 6.93,
+        AminoAcid::P => 8.36,
+        AminoAcid::T => 6.82,
+        AminoAcid::V => 7.44,
+        AminoAcid::E => 7.7,
+        _ => 7.5,
+    }
+}
+
+fn c_terminal_pk(aa: &AminoAcid) -> f64 {
+    match *aa {
+        AminoAcid::D => 4.55,
+        AminoAcid::E => 4.75,
+        _ => 3.55,
+    }
+}
+
+/// Molar extinction coefficients at 280 nm, in M⁻¹ cm⁻¹.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct ExtinctionCoefficients {
+    /// All cysteines reduced.
+    pub reduced: u32,
+    /// All cysteine pairs forming cystines.
+    pub oxidized: u32,
+}
+
+/// Physicochemical properties in the manner of ExPASy ProtParam.
+///
+/// Stop symbols are ignored throughout, so a translation ending in `*` gives
+/// the same results as the mature protein.
+impl Sequence<AminoAcid> {
+    fn residues(&self) -> impl Iterator<Item = &AminoAcid> {
+        self.iter().filter(|aa| **aa != AminoAcid::STOP)
+    }
+
+    fn count(&self, aa: AminoAcid) -> usize {
+        self.iter().filter(|&r| *r == aa).count()
+    }
+
+    /// Number of residues, not counting stops.
+    pub fn residue_count(&self) -> usize {
+        self.residues().count()
+    }
+
+    /// Average molecular weight in daltons; zero for an empty sequence.
+    pub fn average_mass(&self) -> f64 {
+        let residues: f64 = self.residues().map(|aa| masses(aa).0).sum();
+        if self.residue_count() == 0 { 0.0 } else { residues + WATER_AVERAGE }
+    }
+
+    /// Monoisotopic molecular weight in daltons; zero for an empty sequence.
+    pub fn monoisotopic_mass(&self) -> f64 {
+        let residues: f64 = self.residues().map(|aa| masses(aa).1).sum();
+        if self.residue_count() == 0 { 0.0 } else { residues + WATER_MONOISOTOPIC }
+    }
+
+    /// Net charge at the given pH, from the Henderson-Hasselbalch equation
+    /// over the termini and ionisable side chains.
+    pub fn charge_at(&self, ph: f64) -> f64 {
+        let (first, last) = match (self.residues().next(), self.residues().last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return 0.0,
+        };
+        let positive = |pk: f64| 1.0 / (1.0 + 10f64.powf(ph - pk));
+        let negative = |pk: f64| 1.0 / (1.0 + 10f64.powf(pk - ph));
+        let mut charge = positive(n_terminal_pk(first)) - negative(c_terminal_pk(last));
+        for aa in self.residues() {
+            match side_chain_pk(aa) {
+                Some((pk, true)) => charge += positive(pk),
+                Some((pk, false)) => charge -= negative(pk),
+                None => (),
+            }
+        }
+        charge
+    }
+
+    /// The pH at which the net charge is zero, found by bisection to within
+    /// 0.001 pH units.
+    pub fn isoelectric_point(&self) -> f64 {
+        let (mut low, mut high) = (0.0, 14.0);
+        while high - low > 0.001 {
+            let mid = (low + high) / 2.0;
+            if self.charge_at(mid) > 0.0 { low = mid; } else { high = mid; }
+        }
+        (low + high) / 2.0
+    }
+
+    pub fn extinction_coefficients(&self) -> ExtinctionCoefficients {
+        let aromatic = self.count(AminoAcid::W) as u32 * EXTINCTION_W
+            + self.count(AminoAcid::Y) as u32 * EXTINCTION_Y;
+        let cystines = self.count(AminoAcid::C) as u32 / 2;
+        ExtinctionCoefficients { reduced: aromatic,
+                                 oxidized: aromatic + cystines * EXTINCTION_CYSTINE }
+    }
+
+    /// Grand average of hydropathy: the mean Kyte-Doolittle value, over the
+    /// residues that have one. Selenocysteine counts as cysteine.
+    pub fn gravy(&self) -> f64 {
+        let values: Vec<f64> = self.residues().filter_map(hydropathy).collect();
+        mean(values.iter().sum(), values.len())
+    }
+
+    /// Guruprasad's instability index; proteins scoring above 40 are
+    /// predicted to be unstable in vitro. Dipeptides involving anything but
+    /// the twenty standard residues contribute nothing.
+    pub fn instability_index(&self) -> f64 {
+        let residues: Vec<Option<usize>> = self.residues().map(index).collect();
+        let total: f64 = residues.windows(2).map(|pair| match (pair[0], pair[1]) {
+            (Some(first), Some(second)) => DIWV[first][second],
+            _ => 0.0,
+        }).sum();
+        mean(10.0 * total, residues.len())
+    }
+
+    /// Relative volume of aliphatic side chains, from the mole percentages
+    /// of alanine, valine, isoleucine and leucine.
+    pub fn aliphatic_index(&self) -> f64 {
+        let percent = |aa: AminoAcid| mean(100.0 * self.count(aa) as f64, self.residue_count());
+        percent(AminoAcid::A) + 2.9 * percent(AminoAcid::V)
+            + 3.9 * (percent(AminoAcid::I) + percent(AminoAcid::L))
+    }
+
+    /// Count of each standard amino acid, in alphabetical order of the
+    /// one-letter codes, including those absent.
+    pub fn composition(&self) -> Vec<(AminoAcid, usize)> {
+        RESIDUES.iter().map(|aa| (aa.clone(), self.count(aa.clone()))).collect()
+    }
+}
+
+/// `total / len`, or zero when there are no residues.
+fn mean(total: f64, len: usize) -> f64 {
+    if len == 0 { 0.0 } else { total / len as f64 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExtinctionCoefficients;
+    use sequence::sequence::Sequence;
+    use sequence::amino_acid::AminoAcid;
+
+    fn protein(s: &str) -> Sequence<AminoAcid> {
+        Sequence::<AminoAcid>::from_str(s).unwrap()
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn masses() {
+        assert!(close(protein("U").average_mass(), 168.0540));
+        assert!(close(protein("G").average_mass(), 75.0671));
+        assert!(close(protein("G").monoisotopic_mass(), 75.0320));
+        assert!(close(protein("GG*").average_mass(), 132.1190));
+        assert_eq!(protein("").average_mass(), 0.0);
+    }
+
+    #[test]
+    fn charge() {
+        let p = protein("MKWVTFISLLFLFSSAYSRGVFRRDTHKSEIAHRFKDLGE");
+        let pi = p.isoelectric_point();
+        assert!(p.charge_at(pi).abs() < 0.01);
+        assert!(p.charge_at(2.0) > 0.0 && p.charge_at(12.0) < 0.0);
+        assert!(protein("DDDDE").isoelectric_point() < 4.0);
+        assert!(protein("KKRKK").isoelectric_point() > 10.0);
+    }
+
+    #[test]
+    fn indices() {
+        assert_eq!(protein("WYCCC*").extinction_coefficients(),
+                   ExtinctionCoefficients { reduced: 6990, oxidized: 7115 });
+        assert!(close(protein("IV").gravy(), 4.35));
+        assert!(close(protein("IVX").gravy(), 4.35));
+        assert!(close(protein("ACD").instability_index(), 10.0 * (44.94 + 20.26) / 3.0));
+        assert!(close(protein("AAVL").aliphatic_index(), 50.0 + 2.9 * 25.0 + 3.9 * 25.0));
+        let composition = protein("AAC").composition();
+        assert_eq!(composition.len(), 20);
+        assert_eq!(composition[0], (AminoAcid::A, 2));
+        assert_eq!(composition[1], (AminoAcid::C, 1));
+        assert_eq!(composition[2], (AminoAcid::D, 0));
+    }
+}
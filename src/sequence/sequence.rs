@@ -1,16 +1,32 @@
+use std::iter::FromIterator;
+
 use sequence::string_io::StringIO;
 use sequence::nucleotide::Complement;
 use sequence::codon::Codon;
+use sequence::error::SequenceError;
+use sequence::storage::{Storage, Stored};
+use sequence::packed::{Pack, PackedSymbols, KmerIterator};
 
-#[derive(Debug)]
-pub struct Sequence<N> {
-    data: Vec<N>,
+/// Symbols in order, kept in the storage their alphabet chooses: packed
+/// words for nucleotides, a `Vec` otherwise.
+#[derive(Clone, Debug)]
+pub struct Sequence<N> where N: Stored {
+    data: N::Storage,
 }
 
-impl<N> Sequence<N> {
+impl<N> Sequence<N> where N: Stored {
     pub fn push(&mut self, item: N) {
         self.data.push(item);
     }
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    pub fn iter(&self) -> SequenceIterator<'_,N> {
+        SequenceIterator::<N> { sequence: self, front: 0, back: self.data.len() }
+    }
 }
 
 impl<N> PartialEq for Sequence<N> where N: StringIO + PartialEq {
@@ -21,46 +37,126 @@ impl<N> PartialEq for Sequence<N> where N: StringIO + PartialEq {
 
 impl<N> Sequence<N> where N: StringIO<N=N> + Clone {
     pub fn new() -> Sequence<N> {
-        Sequence::<N>{data: Vec::new()}
+        Sequence::<N>{data: N::Storage::default()}
+    }
+    pub fn get(&self, index: usize) -> Result<N, SequenceError> {
+        match self.data.get(index) {
+            Some(item) => Ok(item.clone()),
+            None => Err(SequenceError::IndexOutOfBounds { index, length: self.data.len() }),
+        }
     }
-    pub fn codon(&self, index: usize) -> Result<Codon<N>, String> {
+    pub fn subsequence(&self, start: usize, end: usize) -> Result<Sequence<N>, SequenceError> {
+        if start <= end && end <= self.data.len()
+            { Ok(self.iter().skip(start).take(end - start).cloned().collect()) }
+        else
+            { Err(SequenceError::IndexOutOfBounds { index: end.max(start),
+                                                    length: self.data.len() }) }
+    }
+    pub fn codon(&self, index: usize) -> Result<Codon<N>, SequenceError> {
         if index+3 <= self.data.len()
-            { Ok(Codon::<N>::from_slice(&self.data[index..index+3])) }
+            { Ok(Codon::<N>::from_slice(&[self.get(index)?, self.get(index+1)?,
+                                          self.get(index+2)?])) }
+        else if index < self.data.len()
+            { Err(SequenceError::IncompleteCodon { index,
+                                                   available: self.data.len() - index }) }
         else
-            { Err(format!("Codon index out of bounds")) }
+            { Err(SequenceError::IndexOutOfBounds { index, length: self.data.len() }) }
+    }
+}
+
+impl<N> Default for Sequence<N> where N: StringIO<N=N> + Clone {
+    fn default() -> Sequence<N> {
+        Sequence::<N>::new()
     }
 }
 
 impl<N> Sequence<N> where N: StringIO<N=N> + Clone {
-    pub fn from_str(input: &str) -> Result<Sequence<N>, String> {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(input: &str) -> Result<Sequence<N>, SequenceError> {
         let mut seq = Sequence::<N>::new();
-        for ch in input.chars() {
-            seq.data.push(N::from_char(ch)?);
+        for (offset, ch) in input.chars().enumerate() {
+            seq.data.push(N::from_char(ch).map_err(|e| e.offset_by(offset))?);
         }
         Ok(seq)
     }
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
         let mut output = String::new();
-        for nt in self.data.iter() {
+        for nt in self.iter() {
             output.push(nt.to_char());
         }
         output
     }
-    pub fn codons(&self) -> SequenceIntoCodonIterator<N> {
+    pub fn codons(&self) -> SequenceIntoCodonIterator<'_,N> {
         SequenceIntoCodonIterator::<N> { sequence: self, index: 0 }
     }
 }
 
 impl<N> Sequence<N> where N: Complement<N=N> + StringIO<N=N> + Clone {
     pub fn reverse_complement(&self) -> Sequence<N> {
-        let mut rc = Sequence::<N>::new();
-        for nt in self.data.iter().rev() {
-            rc.data.push(nt.complement());
+        Sequence::<N>{data: self.data.reverse_complement()}
+    }
+}
+
+impl<N> Sequence<N> where N: Pack + Stored<Storage=PackedSymbols<N>> {
+    /// Iterate over every `k`-mer as an integer code, first symbol in the
+    /// most significant field, so codes sort in the same order as strings.
+    ///
+    /// Panics if `k` is zero or `k` symbols do not fit in 64 bits.
+    pub fn kmers(&self, k: usize) -> KmerIterator<'_,N> {
+        self.data.kmers(k)
+    }
+}
+
+impl<N> FromIterator<N> for Sequence<N> where N: StringIO<N=N> + Clone {
+    fn from_iter<I: IntoIterator<Item=N>>(items: I) -> Sequence<N> {
+        let mut seq = Sequence::<N>::new();
+        for item in items {
+            seq.push(item);
+        }
+        seq
+    }
+}
+
+pub struct SequenceIterator<'a,N> where N: Stored + 'a {
+    sequence: &'a Sequence<N>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a,N> Iterator for SequenceIterator<'a,N> where N: Stored {
+    type Item = &'a N;
+    fn next(&mut self) -> Option<&'a N> {
+        if self.front < self.back {
+            self.front += 1;
+            self.sequence.data.get(self.front - 1)
+        } else {
+            None
         }
-        rc
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.back - self.front, Some(self.back - self.front))
+    }
+    /// Jump straight to the symbol, so `skip` does not walk the storage.
+    fn nth(&mut self, n: usize) -> Option<&'a N> {
+        self.front = self.front.saturating_add(n).min(self.back);
+        self.next()
     }
 }
 
+impl<'a,N> DoubleEndedIterator for SequenceIterator<'a,N> where N: Stored {
+    fn next_back(&mut self) -> Option<&'a N> {
+        if self.front < self.back {
+            self.back -= 1;
+            self.sequence.data.get(self.back)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a,N> ExactSizeIterator for SequenceIterator<'a,N> where N: Stored {}
+
 impl<'a,N> Iterator for SequenceIntoCodonIterator<'a,N> where N: StringIO<N=N>
                                                            + Clone {
     type Item = Codon<N>;
@@ -86,8 +182,10 @@ mod tests {
     use sequence::nucleotide::Nucleotide;
     use sequence::codon::Codon;
     use sequence::degenerate_nucleotide::DegenerateNucleotide;
+    use sequence::error::SequenceError;
 
     #[test]
+    #[allow(clippy::single_element_loop)]
     fn good_nucleotide_specs() {
         for s in ["AAAAAC","ACGTACGTAAGATCTCG"].iter() {
             let nts = Sequence::<Nucleotide>::from_str(s).unwrap();
@@ -100,6 +198,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::single_match)]
     fn bad_nucleotide_specs() {
         for s in ["AAANAAC","ACGTACGTAAGATCTCGX"].iter() {
             let result = Sequence::<Nucleotide>::from_str(s);
@@ -117,6 +216,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn bad_nucleotide_position() {
+        let result = Sequence::<Nucleotide>::from_str("ACGTACGTAAGATCTCGX");
+        assert_eq!(result.unwrap_err(), SequenceError::InvalidSymbol { symbol: 'X', offset: 17 });
+    }
+
+    #[test]
+    fn nth() {
+        let nts = Sequence::<Nucleotide>::from_str("GTAAAAC").unwrap();
+        let mut iter = nts.iter();
+        assert_eq!(iter.next_back(), Some(&Nucleotide::C));
+        assert_eq!(iter.nth(1), Some(&Nucleotide::T));
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.nth(4), None);
+        assert_eq!(iter.next(), None);
+    }
+
     #[test]
     fn reverse_complement() {
         let nts = Sequence::<Nucleotide>::from_str("GTAAAAC").unwrap();
@@ -136,13 +252,23 @@ mod tests {
         assert_eq!(codon2.to_string(),"TAA");
         assert_eq!(input.codon(0).unwrap(),codon1);
         assert_eq!(input.codon(1).unwrap(),codon2);
-        match input.codon(5) {
-            Ok(_) => panic!("did not error for out-of-bounds codon"),
-            Err(_) => ()
-        }
+        assert_eq!(input.codon(5).unwrap_err(),
+                   SequenceError::IncompleteCodon { index: 5, available: 2 });
+        assert_eq!(input.codon(7).unwrap_err(),
+                   SequenceError::IndexOutOfBounds { index: 7, length: 7 });
+    }
+
+    #[test]
+    fn subsequence() {
+        let input = Sequence::<Nucleotide>::from_str("GTAAAACAG").unwrap();
+        assert_eq!(input.subsequence(2, 6).unwrap().to_string(), "AAAA");
+        assert_eq!(input.get(8).unwrap(), Nucleotide::G);
+        assert_eq!(input.subsequence(6, 10).unwrap_err(),
+                   SequenceError::IndexOutOfBounds { index: 10, length: 9 });
     }
 
     #[test]
+    #[allow(clippy::single_match)]
     fn codons() {
         let input = Sequence::<Nucleotide>::from_str("GTAAAACAG").unwrap();
         let codon1 = Codon::<Nucleotide>::from_str("GTA").unwrap();
@@ -0,0 +1,185 @@
+//! Serde support, enabled by the `serde` feature.
+//!
+//! Sequences and codons serialize as the same compact strings `to_string`
+//! gives, and single symbols as one-character strings, so a sequence stored
+//! as JSON reads as it would in a FASTA file. Features and annotated
+//! sequences derive their representations alongside their definitions.
+//!
+//! Non-canonical amino acids serialize as `X`, as they print, and so come
+//! back as an unknown residue.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{self, Visitor};
+
+use sequence::string_io::StringIO;
+use sequence::sequence::Sequence;
+use sequence::codon::Codon;
+use sequence::nucleotide::Nucleotide;
+use sequence::degenerate_nucleotide::DegenerateNucleotide;
+use sequence::amino_acid::AminoAcid;
+use sequence::feature::FeatureKind;
+use sequence::error::SequenceError;
+
+/// Reads a string and parses it with `parse`, reporting what was expected
+/// if it does not parse.
+struct ParseVisitor<T, F> {
+    expecting: &'static str,
+    parse: F,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T, F> Visitor<'de> for ParseVisitor<T, F> where F: Fn(&str) -> Result<T, SequenceError> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        (self.parse)(value).map_err(E::custom)
+    }
+}
+
+fn parse_str<'de, D, T, F>(deserializer: D, expecting: &'static str, parse: F)
+                           -> Result<T, D::Error>
+    where D: Deserializer<'de>, F: Fn(&str) -> Result<T, SequenceError> {
+    deserializer.deserialize_str(ParseVisitor { expecting, parse, marker: PhantomData })
+}
+
+impl<N> Serialize for Sequence<N> where N: StringIO<N=N> + Clone {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de, N> Deserialize<'de> for Sequence<N> where N: StringIO<N=N> + Clone {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Sequence<N>, D::Error> {
+        parse_str(deserializer, "a sequence string", Sequence::from_str)
+    }
+}
+
+impl<N> Serialize for Codon<N> where N: StringIO<N=N> + Clone {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de, N> Deserialize<'de> for Codon<N> where N: StringIO<N=N> + Clone {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Codon<N>, D::Error> {
+        parse_str(deserializer, "a three-letter codon", Codon::from_str)
+    }
+}
+
+/// Reads a single symbol of an alphabet from a one-character string,
+/// reporting any other length as such.
+struct SymbolVisitor<N> {
+    expecting: &'static str,
+    marker: PhantomData<N>,
+}
+
+impl<'de, N: StringIO<N=N>> Visitor<'de> for SymbolVisitor<N> {
+    type Value = N;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<N, E> {
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => N::from_char(ch).map_err(E::custom),
+            _ => Err(E::invalid_length(value.chars().count(), &self)),
+        }
+    }
+}
+
+macro_rules! symbol_serde {
+    ($symbol:ty, $expecting:expr) => {
+        impl Serialize for $symbol {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut buffer = [0; 4];
+                serializer.serialize_str(self.to_char().encode_utf8(&mut buffer))
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $symbol {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$symbol, D::Error> {
+                deserializer.deserialize_str(SymbolVisitor { expecting: $expecting,
+                                                             marker: PhantomData })
+            }
+        }
+    };
+}
+
+symbol_serde!(Nucleotide, "a nucleotide");
+symbol_serde!(DegenerateNucleotide, "an IUPAC nucleotide code");
+symbol_serde!(AminoAcid, "a one-letter amino-acid code");
+
+/// Feature kinds serialize as their GenBank keys.
+impl Serialize for FeatureKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.to_key())
+    }
+}
+
+impl<'de> Deserialize<'de> for FeatureKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<FeatureKind, D::Error> {
+        parse_str(deserializer, "a feature key", |key| Ok(FeatureKind::from_key(key)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+
+    use sequence::sequence::Sequence;
+    use sequence::codon::Codon;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::degenerate_nucleotide::DegenerateNucleotide;
+    use sequence::amino_acid::AminoAcid;
+    use sequence::feature::{AnnotatedSequence, Feature, FeatureKind, Topology};
+    use sequence::strand::Strand;
+
+    #[test]
+    fn compact_strings() {
+        let seq = Sequence::<Nucleotide>::from_str("ATGC").unwrap();
+        assert_eq!(serde_json::to_string(&seq).unwrap(), "\"ATGC\"");
+        let back: Sequence<Nucleotide> = serde_json::from_str("\"atgc\"").unwrap();
+        assert!(back == seq);
+        assert!(serde_json::from_str::<Sequence<Nucleotide>>("\"ATGN\"").is_err());
+
+        let codon: Codon<DegenerateNucleotide> = serde_json::from_str("\"NNK\"").unwrap();
+        assert_eq!(serde_json::to_string(&codon).unwrap(), "\"NNK\"");
+        assert!(serde_json::from_str::<Codon<Nucleotide>>("\"AT\"").is_err());
+        assert!(serde_json::from_str::<Codon<Nucleotide>>("\"ATGC\"").is_err());
+
+        let residues = vec![AminoAcid::M, AminoAcid::STOP];
+        assert_eq!(serde_json::to_string(&residues).unwrap(), "[\"M\",\"*\"]");
+        let back: Vec<AminoAcid> = serde_json::from_str("[\"m\",\"*\"]").unwrap();
+        assert_eq!(back, residues);
+        let error = serde_json::from_str::<Nucleotide>("\"AT\"").unwrap_err();
+        assert!(error.to_string().starts_with("invalid length 2, expected a nucleotide"));
+        let error = serde_json::from_str::<Nucleotide>("\"\"").unwrap_err();
+        assert!(error.to_string().starts_with("invalid length 0, expected a nucleotide"));
+        assert!(serde_json::from_str::<Nucleotide>("\"N\"").is_err());
+    }
+
+    #[test]
+    fn annotated() {
+        let seq = Sequence::<Nucleotide>::from_str("ATGAAATAG").unwrap();
+        let mut plasmid = AnnotatedSequence::new("p", seq, Topology::Circular);
+        let mut cds = Feature::new(FeatureKind::Cds, "orf", 0, 9, Strand::Forward);
+        cds.qualifiers.push((String::from("note"), String::from("short")));
+        plasmid.features.push(cds);
+        let json = serde_json::to_string(&plasmid).unwrap();
+        assert!(json.contains("\"sequence\":\"ATGAAATAG\""));
+        assert!(json.contains("\"topology\":\"circular\""));
+        assert!(json.contains("\"kind\":\"CDS\""));
+        assert!(json.contains("\"strand\":\"forward\""));
+        let back: AnnotatedSequence = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, plasmid);
+    }
+}
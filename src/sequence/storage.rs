@@ -0,0 +1,44 @@
+use std::fmt::Debug;
+
+use sequence::nucleotide::Complement;
+use sequence::amino_acid::AminoAcid;
+
+/// The container a `Sequence` keeps its symbols in.
+///
+/// Symbols are handed out by reference, so storage that does not hold
+/// them directly must be able to point at a canonical copy of each.
+pub trait Storage<N>: Clone + Debug + Default + PartialEq {
+    fn push(&mut self, item: N);
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn get(&self, index: usize) -> Option<&N>;
+    fn reverse_complement(&self) -> Self where N: Complement<N=N>;
+}
+
+/// Symbols that a `Sequence` can hold, with the storage it uses for them.
+pub trait Stored: Sized {
+    type Storage: Storage<Self>;
+}
+
+impl<N> Storage<N> for Vec<N> where N: Clone + Debug + PartialEq {
+    fn push(&mut self, item: N) {
+        Vec::push(self, item);
+    }
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+    fn get(&self, index: usize) -> Option<&N> {
+        self.as_slice().get(index)
+    }
+    fn reverse_complement(&self) -> Vec<N> where N: Complement<N=N> {
+        self.iter().rev().map(|symbol| symbol.complement()).collect()
+    }
+}
+
+/// Amino acids may carry a non-canonical residue, so they are stored as
+/// they are.
+impl Stored for AminoAcid {
+    type Storage = Vec<AminoAcid>;
+}
@@ -0,0 +1,18 @@
+/// Which strand of a double-stranded sequence a region or feature lies on.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Strand {
+    Forward,
+    Reverse,
+}
+
+impl Strand {
+    #[allow(clippy::match_ref_pats)]
+    pub fn opposite(&self) -> Strand {
+        match self {
+            &Strand::Forward => Strand::Reverse,
+            &Strand::Reverse => Strand::Forward,
+        }
+    }
+}
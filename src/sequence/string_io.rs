@@ -1,5 +1,8 @@
-pub trait StringIO {
+use sequence::error::SequenceError;
+use sequence::storage::Stored;
+
+pub trait StringIO: Stored {
     type N;
-    fn from_char(input: char) -> Result<Self::N, String>;
+    fn from_char(input: char) -> Result<Self::N, SequenceError>;
     fn to_char(&self) -> char;
 }
@@ -2,9 +2,10 @@ use sequence::nucleotide::Nucleotide;
 use sequence::sequence::Sequence;
 use sequence::codon::Codon;
 use sequence::amino_acid::AminoAcid;
+use sequence::error::SequenceError;
 
 impl Codon<Nucleotide> {
-    pub fn translate(&self) -> Result<AminoAcid, String> {
+    pub fn translate(&self) -> Result<AminoAcid, SequenceError> {
         match &self.to_string()[..] {
             "GCA" | "GCC" | "GCG" | "GCT" => Ok(AminoAcid::A),
             "TGC" | "TGT" => Ok(AminoAcid::C),
@@ -27,16 +28,17 @@ impl Codon<Nucleotide> {
             "TGG" => Ok(AminoAcid::W),
             "TAC" | "TAT" => Ok(AminoAcid::Y),
             "TAA" | "TAG" | "TGA" => Ok(AminoAcid::STOP),
-            bad_codon => Err(format!("Bad codon {}",bad_codon)),
+            bad_codon => Err(SequenceError::UntranslatableCodon { codon: String::from(bad_codon),
+                                                                  offset: 0 }),
         }
     }
 }
 
 impl Sequence<Nucleotide> {
-    pub fn translate(&self) -> Result<Sequence<AminoAcid>, String> {
+    pub fn translate(&self) -> Result<Sequence<AminoAcid>, SequenceError> {
         let mut aa_seq = Sequence::<AminoAcid>::new();
-        for codon in self.codons() {
-            aa_seq.push(codon.translate()?);
+        for (i, codon) in self.codons().enumerate() {
+            aa_seq.push(codon.translate().map_err(|e| e.offset_by(3*i))?);
         }
         Ok(aa_seq)
     }
@@ -53,6 +55,7 @@ mod tests {
 
     #[test]
     fn translate_codon() {
+        #[allow(clippy::redundant_static_lifetimes)]
         const NUCLEOTIDE_CHARS: &'static str = "ACGT";
         for ch1 in NUCLEOTIDE_CHARS.chars() {
             for ch2 in NUCLEOTIDE_CHARS.chars() {
@@ -60,7 +63,7 @@ mod tests {
                     let codon = Codon::<Nucleotide>::from_chars(ch1,ch2,ch3).unwrap();
                     match codon.translate() {
                         Ok(_) => (),
-                        Err(e) => panic!(e),
+                        Err(e) => panic!("{}", e),
                     }
                 }
             }
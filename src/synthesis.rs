@@ -0,0 +1,422 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use sequence::sequence::Sequence;
+use sequence::nucleotide::Nucleotide;
+use sequence::string_io::StringIO;
+use sequence::feature::Topology;
+use variants::{kmer_codes, reverse_complement_chars};
+
+/// Limits a vendor places on sequences it will synthesise. Every field can
+/// be changed; the presets approximate published guidelines, which vendors
+/// revise, so check a borderline sequence against their own tool.
+#[derive(Clone, Debug)]
+pub struct RuleSet {
+    pub name: String,
+    pub min_length: usize,
+    pub max_length: usize,
+    /// Bounds on GC content over the whole sequence, as fractions.
+    pub min_gc: f64,
+    pub max_gc: f64,
+    /// Bounds on GC content in every window of `gc_window` bases.
+    pub gc_window: usize,
+    pub min_window_gc: f64,
+    pub max_window_gc: f64,
+    /// Longest allowed run of a single A or T, and of a single G or C.
+    pub max_at_homopolymer: usize,
+    pub max_gc_homopolymer: usize,
+    /// Longest allowed sequence occurring twice, directly or inverted.
+    pub max_repeat: usize,
+    /// Longest allowed stretch of a 2 to 6 base unit repeated in tandem.
+    pub max_tandem_repeat: usize,
+    /// Inverted repeats with a stem of at least this many bases and a loop
+    /// of at most `max_hairpin_loop` are reported as hairpins.
+    pub min_hairpin_stem: usize,
+    pub max_hairpin_loop: usize,
+    /// DUST score above which a window of `complexity_window` bases counts
+    /// as low complexity.
+    pub complexity_window: usize,
+    pub max_dust_score: f64,
+}
+
+impl RuleSet {
+    /// Modelled on IDT's gBlocks gene fragment guidelines.
+    pub fn idt_gblocks() -> RuleSet {
+        RuleSet { name: String::from("idt"), min_length: 125, max_length: 3000,
+                  min_gc: 0.25, max_gc: 0.75, gc_window: 50, min_window_gc: 0.15,
+                  max_window_gc: 0.85, max_at_homopolymer: 9, max_gc_homopolymer: 5,
+                  max_repeat: 19, max_tandem_repeat: 14, min_hairpin_stem: 10,
+                  max_hairpin_loop: 50, complexity_window: 64, max_dust_score: 20.0 }
+    }
+
+    /// Modelled on Twist Bioscience's gene fragment guidelines.
+    pub fn twist_gene_fragments() -> RuleSet {
+        RuleSet { name: String::from("twist"), min_length: 300, max_length: 5000,
+                  min_gc: 0.25, max_gc: 0.65, gc_window: 50, min_window_gc: 0.20,
+                  max_window_gc: 0.80, max_at_homopolymer: 9, max_gc_homopolymer: 9,
+                  max_repeat: 19, max_tandem_repeat: 19, min_hairpin_stem: 20,
+                  max_hairpin_loop: 100, complexity_window: 64, max_dust_score: 20.0 }
+    }
+
+    /// A preset by name: `idt` or `twist`.
+    pub fn from_name(name: &str) -> Option<RuleSet> {
+        match &name.to_lowercase()[..] {
+            "idt" => Some(RuleSet::idt_gblocks()),
+            "twist" => Some(RuleSet::twist_gene_fragments()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Rule {
+    Length,
+    GlobalGc,
+    LocalGc,
+    Homopolymer,
+    DirectRepeat,
+    InvertedRepeat,
+    TandemRepeat,
+    Hairpin,
+    LowComplexity,
+}
+
+impl Rule {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Rule::Length => "length",
+            Rule::GlobalGc => "global-gc",
+            Rule::LocalGc => "local-gc",
+            Rule::Homopolymer => "homopolymer",
+            Rule::DirectRepeat => "direct-repeat",
+            Rule::InvertedRepeat => "inverted-repeat",
+            Rule::TandemRepeat => "tandem-repeat",
+            Rule::Hairpin => "hairpin",
+            Rule::LowComplexity => "low-complexity",
+        }
+    }
+}
+
+/// A broken rule over `start..end`, zero-based and half-open. Repeats and
+/// hairpins give the first copy here and the start of the second copy, or
+/// of the opposing stem, as `partner`. `value` is what was measured: the
+/// length for length, runs and repeats, the GC fraction, or the DUST score.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Violation {
+    pub rule: Rule,
+    pub start: usize,
+    pub end: usize,
+    pub partner: Option<usize>,
+    pub value: f64,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at {}..{}", self.rule.name(), self.start + 1, self.end)?;
+        if let Some(partner) = self.partner {
+            write!(f, " and {}..{}", partner + 1, partner + self.end - self.start)?;
+        }
+        match self.rule {
+            Rule::GlobalGc | Rule::LocalGc => write!(f, " (GC {:.1}%)", self.value * 100.0),
+            Rule::LowComplexity => write!(f, " (DUST {:.1})", self.value),
+            _ => write!(f, " ({} bp)", self.value),
+        }
+    }
+}
+
+/// Check a linear sequence against a rule set, returning every violation
+/// ordered by position.
+pub fn check(sequence: &Sequence<Nucleotide>, rules: &RuleSet) -> Vec<Violation> {
+    let seq: Vec<char> = sequence.iter().map(|nt| nt.to_char()).collect();
+    let length = seq.len();
+    let mut violations = Vec::new();
+    if length < rules.min_length || length > rules.max_length {
+        violations.push(Violation { rule: Rule::Length, start: 0, end: length, partner: None,
+                                    value: length as f64 });
+    }
+    let gc = sequence.gc_content();
+    if length > 0 && (gc < rules.min_gc || gc > rules.max_gc) {
+        violations.push(Violation { rule: Rule::GlobalGc, start: 0, end: length, partner: None,
+                                    value: gc });
+    }
+    local_gc(sequence, rules, &mut violations);
+    homopolymers(&seq, rules, &mut violations);
+    tandem_repeats(&seq, rules, &mut violations);
+    let periodic: Vec<(usize, usize)> = violations.iter()
+        .filter(|v| v.rule == Rule::TandemRepeat || v.rule == Rule::Homopolymer)
+        .map(|v| (v.start, v.end)).collect();
+    let mut copies = Vec::new();
+    repeats(&seq, rules, &mut copies);
+    // Repeats within a homopolymer or tandem repeat add nothing to it.
+    violations.extend(copies.into_iter().filter(|v| {
+        let last = v.partner.unwrap_or(v.start) + v.end - v.start;
+        !periodic.iter().any(|&(start, end)| start <= v.start && last <= end)
+    }));
+    low_complexity(&seq, rules, &mut violations);
+    violations.sort_by_key(|v| (v.start, v.end));
+    violations
+}
+
+/// Merge consecutive failing windows into one violation per region, keeping
+/// the most extreme value as measured by `badness`.
+fn merge_windows<I>(windows: I, width: usize, rule: Rule, badness: &dyn Fn(f64) -> f64,
+                    violations: &mut Vec<Violation>) where I: Iterator<Item = (usize, f64)> {
+    let mut open: Option<Violation> = None;
+    for (start, value) in windows {
+        match open {
+            Some(ref mut v) if start <= v.end => {
+                v.end = start + width;
+                if badness(value) > badness(v.value) {
+                    v.value = value;
+                }
+            },
+            _ => {
+                violations.extend(open.take());
+                open = Some(Violation { rule, start, end: start + width, partner: None, value });
+            },
+        }
+    }
+    violations.extend(open);
+}
+
+fn local_gc(sequence: &Sequence<Nucleotide>, rules: &RuleSet, violations: &mut Vec<Violation>) {
+    let (low, high) = (rules.min_window_gc, rules.max_window_gc);
+    let windows = sequence.gc_windows(rules.gc_window, 1, Topology::Linear);
+    let failing = windows.iter().filter(|w| w.gc < low || w.gc > high).map(|w| (w.start, w.gc));
+    let mid = (low + high) / 2.0;
+    merge_windows(failing, rules.gc_window, Rule::LocalGc, &|gc| (gc - mid).abs(), violations);
+}
+
+fn homopolymers(seq: &[char], rules: &RuleSet, violations: &mut Vec<Violation>) {
+    let mut start = 0;
+    for i in 1..seq.len() + 1 {
+        if i == seq.len() || seq[i] != seq[start] {
+            let limit = match seq[start] {
+                'G' | 'C' => rules.max_gc_homopolymer,
+                _ => rules.max_at_homopolymer,
+            };
+            if i - start > limit {
+                violations.push(Violation { rule: Rule::Homopolymer, start, end: i,
+                                            partner: None, value: (i - start) as f64 });
+            }
+            start = i;
+        }
+    }
+}
+
+/// Stretches where a unit of 2 to 6 bases repeats back to back. A stretch
+/// is reported once, under its shortest period.
+fn tandem_repeats(seq: &[char], rules: &RuleSet, violations: &mut Vec<Violation>) {
+    let mut found: Vec<(usize, usize)> = Vec::new();
+    for period in 2..7 {
+        let mut i = 0;
+        while i + period < seq.len() {
+            let mut j = i;
+            while j + period < seq.len() && seq[j] == seq[j + period] {
+                j += 1;
+            }
+            let (start, end) = (i, j + period);
+            let homopolymer = seq[start..end].iter().all(|&ch| ch == seq[start]);
+            if j > i && end - start > rules.max_tandem_repeat && !homopolymer
+                && !found.iter().any(|&(s, e)| s <= start && end <= e) {
+                found.push((start, end));
+                violations.push(Violation { rule: Rule::TandemRepeat, start, end,
+                                            partner: None, value: (end - start) as f64 });
+            }
+            i = j.max(i) + 1;
+        }
+    }
+}
+
+/// Occurrences of a k-mer kept per code; later copies of very common k-mers
+/// are only compared with the most recent ones, which bounds the work on
+/// low-complexity sequence that other rules already report.
+const MAX_OCCURRENCES: usize = 64;
+
+/// The longest seed a k-mer code holds in full.
+const MAX_SEED: usize = 32;
+
+fn complement(ch: char) -> char {
+    match ch {
+        'A' => 'T',
+        'C' => 'G',
+        'G' => 'C',
+        _ => 'A',
+    }
+}
+
+/// Maximal direct and inverted repeats, with inverted repeats whose arms are
+/// close together reported as hairpins.
+fn repeats(seq: &[char], rules: &RuleSet, violations: &mut Vec<Violation>) {
+    let length = seq.len();
+    let min_repeat = rules.max_repeat + 1;
+    // Seeds longer than a k-mer code holds would match on their last bases
+    // alone, so longer repeats are grown from the longest seed that fits.
+    let k = min_repeat.min(rules.min_hairpin_stem).clamp(1, MAX_SEED);
+    if length < k {
+        return;
+    }
+    let forward = kmer_codes(seq, k);
+    let mut index: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (end, code) in forward.iter().enumerate() {
+        if let Some(code) = *code {
+            index.entry(code).or_default().push(end + 1 - k);
+        }
+    }
+
+    // Direct repeats: each copy against earlier copies, extended to the
+    // right from seeds that cannot be extended to the left.
+    let seeds = |codes: Vec<Option<u64>>| -> Vec<(usize, u64)> {
+        codes.iter().enumerate().filter_map(|(end, code)| code.map(|c| (end + 1 - k, c))).collect()
+    };
+    for (i, code) in seeds(forward) {
+        let earlier = &index[&code];
+        let before = earlier.iter().position(|&j| j == i).unwrap();
+        for &j in earlier[..before].iter().rev().take(MAX_OCCURRENCES) {
+            if j > 0 && seq[j - 1] == seq[i - 1] {
+                continue;
+            }
+            let mut len = k;
+            while i + len < length && seq[j + len] == seq[i + len] {
+                len += 1;
+            }
+            // Overlapping copies with a short period are tandem repeats.
+            if len >= min_repeat && i - j > 6 {
+                violations.push(Violation { rule: Rule::DirectRepeat, start: j, end: j + len,
+                                            partner: Some(i), value: len as f64 });
+            }
+        }
+    }
+
+    // Inverted repeats: forward k-mers that occur in the reverse complement.
+    let reverse = reverse_complement_chars(seq);
+    for (p, code) in seeds(kmer_codes(&reverse, k)) {
+        let matches = match index.get(&code) {
+            Some(matches) => matches,
+            None => continue,
+        };
+        // The reverse-complement k-mer at `p` is forward bases r..r+k.
+        let r = length - p - k;
+        for &i in matches.iter().take(MAX_OCCURRENCES) {
+            // Arms must not overlap, and each pair is seen from both arms.
+            if i + k > r || (i > 0 && r + k < length && seq[i - 1] == complement(seq[r + k])) {
+                continue;
+            }
+            // Grow the arms towards each other: i rightwards, r leftwards.
+            let (mut len, mut partner) = (k, r);
+            while i + len < partner && partner > 0
+                && seq[i + len] == complement(seq[partner - 1]) {
+                len += 1;
+                partner -= 1;
+            }
+            let gap = partner.saturating_sub(i + len);
+            if len >= rules.min_hairpin_stem && gap <= rules.max_hairpin_loop {
+                violations.push(Violation { rule: Rule::Hairpin, start: i, end: i + len,
+                                            partner: Some(partner), value: len as f64 });
+            } else if len >= min_repeat {
+                violations.push(Violation { rule: Rule::InvertedRepeat, start: i, end: i + len,
+                                            partner: Some(partner), value: len as f64 });
+            }
+        }
+    }
+}
+
+/// The DUST score of a window: over its overlapping triplets, the sum of
+/// c(c - 1) / 2 for each triplet's count c, divided by one less than the
+/// number of triplets.
+fn dust_score(window: &[char]) -> f64 {
+    let mut counts: HashMap<&[char], usize> = HashMap::new();
+    for triplet in window.windows(3) {
+        *counts.entry(triplet).or_insert(0) += 1;
+    }
+    let triplets = window.len().saturating_sub(2);
+    if triplets < 2 {
+        return 0.0;
+    }
+    let total: usize = counts.values().map(|&c| c * (c - 1) / 2).sum();
+    total as f64 / (triplets - 1) as f64
+}
+
+fn low_complexity(seq: &[char], rules: &RuleSet, violations: &mut Vec<Violation>) {
+    let width = rules.complexity_window;
+    if width < 4 || width > seq.len() {
+        return;
+    }
+    let failing = seq.windows(width).enumerate()
+        .map(|(start, window)| (start, dust_score(window)))
+        .filter(|&(_, score)| score > rules.max_dust_score);
+    merge_windows(failing, width, Rule::LowComplexity, &|score| score, violations);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, RuleSet, Rule};
+    use sequence::sequence::Sequence;
+    use sequence::nucleotide::Nucleotide;
+
+    /// A varied 203 bp stretch with no problems under either preset.
+    const CLEAN: &str = "ATGGCTAGCAAAGGAGAAGAACTTTTCACTGGAGTTGTCCCAATTCTTGTTGAATTAGATGGTGATG\
+                         TTAATGGGCACAAATTTTCTGTCAGTGGAGAGGGTGAAGGTGATGCTACATACGGAAAGCTTACCCTT\
+                         AAATTTATTTGCACTACTGGAAAACTACCTGTTCCATGGCCAACACTTGTCACTACTTTCACCTATGG";
+
+    fn rules(seq: &str) -> Vec<Rule> {
+        let seq = Sequence::<Nucleotide>::from_str(seq).unwrap();
+        check(&seq, &RuleSet::idt_gblocks()).iter().map(|v| v.rule).collect()
+    }
+
+    #[test]
+    fn clean_and_length() {
+        assert!(rules(CLEAN).is_empty());
+        assert_eq!(rules(&CLEAN[..100]), vec![Rule::Length]);
+    }
+
+    #[test]
+    fn local_problems() {
+        let homopolymer = format!("{}{}{}", &CLEAN[..100], "AAAAAAAAAAAA", &CLEAN[100..]);
+        let seq = Sequence::<Nucleotide>::from_str(&homopolymer).unwrap();
+        let violations = check(&seq, &RuleSet::idt_gblocks());
+        assert_eq!(violations.len(), 1);
+        assert_eq!((violations[0].rule, violations[0].start, violations[0].end),
+                   (Rule::Homopolymer, 100, 112));
+
+        let tandem = format!("{}{}{}", &CLEAN[..100], "CAGCAGCAGCAGCAGCAG", &CLEAN[100..]);
+        assert!(rules(&tandem).contains(&Rule::TandemRepeat));
+
+        let gc_rich = format!("{}{}{}", &CLEAN[..100], "GCGGCCGCGGCGCCGGCGCCGCCGGCGCGGCCGCGCCGGC",
+                              &CLEAN[100..]);
+        assert!(rules(&gc_rich).contains(&Rule::LocalGc));
+    }
+
+    #[test]
+    fn repeats_and_hairpins() {
+        let repeat = &CLEAN[20..45];
+        let direct = format!("{}{}", CLEAN, repeat);
+        let seq = Sequence::<Nucleotide>::from_str(&direct).unwrap();
+        let violation = check(&seq, &RuleSet::idt_gblocks()).into_iter()
+            .find(|v| v.rule == Rule::DirectRepeat).unwrap();
+        assert_eq!((violation.start, violation.end, violation.partner), (20, 45, Some(203)));
+
+        let stem = "GATTACACCGTTAGG";
+        let rc = Sequence::<Nucleotide>::from_str(stem).unwrap().reverse_complement().to_string();
+        let hairpin = format!("{}{}TTTT{}{}", &CLEAN[..100], stem, rc, &CLEAN[100..]);
+        let seq = Sequence::<Nucleotide>::from_str(&hairpin).unwrap();
+        let violation = check(&seq, &RuleSet::idt_gblocks()).into_iter()
+            .find(|v| v.rule == Rule::Hairpin).unwrap();
+        assert_eq!((violation.start, violation.end, violation.partner), (100, 115, Some(119)));
+    }
+
+    #[test]
+    fn long_repeats() {
+        let mut rules = RuleSet::idt_gblocks();
+        rules.max_repeat = 40;
+        rules.min_hairpin_stem = 50;
+        let direct = |seq: &str| {
+            let seq = Sequence::<Nucleotide>::from_str(seq).unwrap();
+            check(&seq, &rules).into_iter().filter(|v| v.rule == Rule::DirectRepeat)
+                .map(|v| (v.start, v.end, v.partner)).collect::<Vec<_>>()
+        };
+        // 41 bases ending in the same 32 are not a 41 bp repeat.
+        assert!(direct(&format!("{}C{}C", CLEAN, &CLEAN[20..52])).is_empty());
+        assert_eq!(direct(&format!("{}{}", CLEAN, &CLEAN[20..65])), vec![(20, 65, Some(203))]);
+    }
+}
@@ -0,0 +1,632 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use sequence::sequence::Sequence;
+use sequence::string_io::StringIO;
+use sequence::nucleotide::{Nucleotide, Complement};
+use sequence::amino_acid::AminoAcid;
+use sequence::codon::Codon;
+use sequence::strand::Strand;
+use sequence::feature::{AnnotatedSequence, Feature, FeatureKind, Topology};
+use align::{Aligner, Alignment, AlignmentOp, Scoring, chars};
+use io::fastq::FastqRecord;
+
+/// Thresholds for mapping reads and calling variants.
+#[derive(Clone, Debug)]
+pub struct CallerParams {
+    /// Seed length used to place reads on the reference.
+    pub kmer: usize,
+    /// Minimum number of seeds supporting a placement.
+    pub min_seeds: usize,
+    /// Slack, in bases, around the seeded placement for indels.
+    pub band: usize,
+    /// Reads aligning below this identity are left unmapped.
+    pub min_identity: f64,
+    /// Bases below this Phred score are not counted towards alleles.
+    pub min_base_quality: u8,
+    pub min_reads: usize,
+    pub min_frequency: f64,
+}
+
+impl Default for CallerParams {
+    fn default() -> CallerParams {
+        CallerParams { kmer: 15, min_seeds: 2, band: 20, min_identity: 0.8,
+                       min_base_quality: 20, min_reads: 3, min_frequency: 0.1 }
+    }
+}
+
+/// Where a read landed on the reference.
+#[derive(Clone, Debug)]
+pub struct ReadMapping {
+    /// Reference position of the first aligned base; on a circular reference
+    /// the alignment may run past the end and continue from the origin.
+    pub position: usize,
+    pub strand: Strand,
+    pub alignment: Alignment,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum VariantKind {
+    Snv,
+    Insertion,
+    Deletion,
+}
+
+/// The effect of a variant on a coding sequence. Codons are numbered from
+/// one, as in protein notation.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Effect {
+    Synonymous { codon: usize, amino_acid: AminoAcid },
+    Missense { codon: usize, from: AminoAcid, to: AminoAcid },
+    Nonsense { codon: usize, from: AminoAcid },
+    StopLost { codon: usize, to: AminoAcid },
+    Frameshift { codon: usize, from: AminoAcid },
+    InframeInsertion { codon: usize, from: AminoAcid, codons: usize },
+    InframeDeletion { codon: usize, from: AminoAcid, codons: usize },
+}
+
+impl fmt::Display for Effect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Effect::Synonymous { codon, ref amino_acid } =>
+                write!(f, "p.{}{}=", amino_acid.to_char(), codon),
+            Effect::Missense { codon, ref from, ref to } =>
+                write!(f, "p.{}{}{}", from.to_char(), codon, to.to_char()),
+            Effect::Nonsense { codon, ref from } =>
+                write!(f, "p.{}{}*", from.to_char(), codon),
+            Effect::StopLost { codon, ref to } =>
+                write!(f, "p.*{}{}ext", codon, to.to_char()),
+            Effect::Frameshift { codon, ref from } =>
+                write!(f, "p.{}{}fs", from.to_char(), codon),
+            Effect::InframeInsertion { codon, ref from, codons } =>
+                write!(f, "p.{}{}ins{}", from.to_char(), codon, codons),
+            Effect::InframeDeletion { codon, ref from, codons } =>
+                write!(f, "p.{}{}del{}", from.to_char(), codon, codons),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Consequence {
+    /// Label of the CDS feature affected.
+    pub feature: String,
+    pub effect: Effect,
+}
+
+/// A called variant in VCF-style representation: indels carry the preceding
+/// reference base as an anchor, so `position` is that anchor's position. An
+/// indel at the start of a linear reference has no preceding base and is
+/// anchored on the following one instead.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Variant {
+    pub position: usize,
+    pub kind: VariantKind,
+    pub reference: String,
+    pub alternate: String,
+    /// Reads supporting the alternate allele.
+    pub count: usize,
+    /// Reads informative at this position.
+    pub depth: usize,
+    pub frequency: f64,
+    pub consequences: Vec<Consequence>,
+}
+
+/// Formats as `position reference>alternate`, with a one-based position.
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}>{}", self.position + 1, self.reference, self.alternate)
+    }
+}
+
+/// Maps reads onto a reference construct, accumulates a pileup, and calls
+/// SNVs and indels from it.
+///
+/// Reads are seeded with exact k-mer matches on both strands and then
+/// aligned semi-globally around the seeded placement. Circular references
+/// are indexed with their start appended, so reads spanning the origin
+/// align in one piece and their positions wrap back onto the construct.
+pub struct VariantCaller<'a> {
+    reference: &'a AnnotatedSequence,
+    params: CallerParams,
+    aligner: Aligner,
+    target: Vec<char>,
+    index: HashMap<u64, Vec<usize>>,
+    depth: Vec<usize>,
+    bases: Vec<[usize; 4]>,
+    insertions: HashMap<(usize, String), usize>,
+    deletions: HashMap<(usize, usize), usize>,
+}
+
+pub(crate) fn kmer_codes(seq: &[char], k: usize) -> Vec<Option<u64>> {
+    let mask = if k >= 32 { !0 } else { (1u64 << (2*k)) - 1 };
+    let (mut code, mut valid) = (0u64, 0);
+    let mut codes = Vec::with_capacity(seq.len());
+    for &ch in seq.iter() {
+        match base_index(ch) {
+            Some(b) => {
+                code = ((code << 2) | b as u64) & mask;
+                valid += 1;
+            },
+            None => valid = 0,
+        }
+        codes.push(if valid >= k { Some(code) } else { None });
+    }
+    codes
+}
+
+fn base_index(ch: char) -> Option<usize> {
+    match ch {
+        'A' => Some(0),
+        'C' => Some(1),
+        'G' => Some(2),
+        'T' => Some(3),
+        _ => None,
+    }
+}
+
+/// Start positions of every k-mer in `seq` that starts before `length`,
+/// keyed by its code. Circular sequences are passed with their start
+/// appended, so k-mers spanning the origin are indexed too.
+pub(crate) fn kmer_index(seq: &[char], k: usize, length: usize) -> HashMap<u64, Vec<usize>> {
+    let mut index: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (end, code) in kmer_codes(seq, k).into_iter().enumerate() {
+        if let Some(code) = code {
+            let start = end + 1 - k;
+            if start < length {
+                index.entry(code).or_default().push(start);
+            }
+        }
+    }
+    index
+}
+
+pub(crate) fn reverse_complement_chars(seq: &[char]) -> Vec<char> {
+    seq.iter().rev().map(|&ch| match ch {
+        'A' => 'T', 'C' => 'G', 'G' => 'C', 'T' => 'A', other => other }).collect()
+}
+
+impl<'a> VariantCaller<'a> {
+    pub fn new(reference: &'a AnnotatedSequence, params: CallerParams) -> VariantCaller<'a> {
+        let length = reference.sequence.len();
+        let mut target = chars(&reference.sequence);
+        if reference.topology == Topology::Circular {
+            target.extend_from_within(..);
+        }
+        let index = kmer_index(&target, params.kmer, length);
+        let aligner = Aligner::new(Scoring::default());
+        VariantCaller { reference, params, aligner, target, index,
+                        depth: vec![0; length], bases: vec![[0; 4]; length],
+                        insertions: HashMap::new(), deletions: HashMap::new() }
+    }
+
+    fn length(&self) -> usize {
+        self.reference.sequence.len()
+    }
+
+    /// Place a read on the reference without adding it to the pileup.
+    pub fn map_read<N>(&self, read: &Sequence<N>) -> Option<ReadMapping>
+        where N: StringIO<N=N> + Clone {
+        let forward = chars(read);
+        let reverse = reverse_complement_chars(&forward);
+        let (strand, query, diagonal, seeds) = [(Strand::Forward, forward), (Strand::Reverse, reverse)]
+            .iter()
+            .filter_map(|&(strand, ref query)| {
+                self.seed(query).map(|(diagonal, seeds)| (strand, query.clone(), diagonal, seeds))
+            })
+            .max_by_key(|candidate| candidate.3)?;
+        if seeds < self.params.min_seeds {
+            return None;
+        }
+        let mut diagonal = diagonal;
+        if self.reference.topology == Topology::Circular && diagonal < self.params.band as isize {
+            // Use the copy of the reference appended after the origin, so the
+            // window has room for the band on both sides.
+            diagonal += self.length() as isize;
+        }
+        let window_start = (diagonal - self.params.band as isize).max(0) as usize;
+        let window_end = ((diagonal + (query.len() + self.params.band) as isize).max(0) as usize)
+            .min(self.target.len());
+        if window_start >= window_end {
+            return None;
+        }
+        let mut alignment = self.aligner.align(&query, &self.target[window_start..window_end], true);
+        if alignment.identity() < self.params.min_identity {
+            return None;
+        }
+        alignment.target_start += window_start;
+        alignment.target_end += window_start;
+        Some(ReadMapping { position: alignment.target_start % self.length(), strand, alignment })
+    }
+
+    /// The most supported diagonal (reference position minus read position)
+    /// and the number of seeds on it.
+    fn seed(&self, query: &[char]) -> Option<(isize, usize)> {
+        let k = self.params.kmer;
+        let mut votes: HashMap<isize, usize> = HashMap::new();
+        for (end, code) in kmer_codes(query, k).into_iter().enumerate() {
+            if let Some(hits) = code.and_then(|code| self.index.get(&code)) {
+                for &hit in hits.iter() {
+                    let mut diagonal = hit as isize - (end + 1 - k) as isize;
+                    if self.reference.topology == Topology::Circular {
+                        diagonal = diagonal.rem_euclid(self.length() as isize);
+                    }
+                    *votes.entry(diagonal).or_insert(0) += 1;
+                }
+            }
+        }
+        votes.into_iter().max_by_key(|&(diagonal, count)| (count, -diagonal))
+    }
+
+    /// Map a read and add it to the pileup, returning where it mapped.
+    pub fn add_read<N>(&mut self, read: &FastqRecord<N>) -> Option<ReadMapping>
+        where N: StringIO<N=N> + Clone {
+        let mapping = self.map_read(&read.sequence)?;
+        let mut query = chars(&read.sequence);
+        let mut quality = read.quality.clone();
+        if mapping.strand == Strand::Reverse {
+            query = reverse_complement_chars(&query);
+            quality.reverse();
+        }
+        let length = self.length();
+        let (mut q, mut t) = (0, mapping.alignment.target_start);
+        let ops = &mapping.alignment.ops;
+        let mut k = 0;
+        while k < ops.len() {
+            match ops[k] {
+                AlignmentOp::Match | AlignmentOp::Mismatch => {
+                    if quality.get(q).map(|&bq| bq >= self.params.min_base_quality).unwrap_or(true) {
+                        if let Some(b) = base_index(query[q]) {
+                            self.bases[t % length][b] += 1;
+                            self.depth[t % length] += 1;
+                        }
+                    }
+                    q += 1;
+                    t += 1;
+                    k += 1;
+                },
+                AlignmentOp::Insertion => {
+                    let run = ops[k..].iter().take_while(|&&op| op == AlignmentOp::Insertion).count();
+                    // Insertions at the very ends of a read are alignment
+                    // artefacts rather than evidence.
+                    if t > mapping.alignment.target_start && k + run < ops.len() {
+                        let inserted: String = query[q..q+run].iter().collect();
+                        let key = self.left_align_insertion(t % length, inserted);
+                        *self.insertions.entry(key).or_insert(0) += 1;
+                    }
+                    q += run;
+                    k += run;
+                },
+                AlignmentOp::Deletion => {
+                    let run = ops[k..].iter().take_while(|&&op| op == AlignmentOp::Deletion).count();
+                    for i in 0..run {
+                        self.depth[(t + i) % length] += 1;
+                    }
+                    let key = self.left_align_deletion(t % length, run);
+                    *self.deletions.entry(key).or_insert(0) += 1;
+                    t += run;
+                    k += run;
+                },
+            }
+        }
+        Some(mapping)
+    }
+
+    /// Informative reads covering each reference position.
+    pub fn depth(&self) -> &[usize] {
+        &self.depth
+    }
+
+    fn base(&self, position: usize) -> char {
+        self.target[position % self.length()]
+    }
+
+    /// Shift an insertion before `position` as far left as the reference
+    /// allows, so equivalent placements within a repeat are counted together.
+    fn left_align_insertion(&self, mut position: usize, inserted: String) -> (usize, String) {
+        let length = self.length();
+        let circular = self.reference.topology == Topology::Circular;
+        let mut inserted: Vec<char> = inserted.chars().collect();
+        for _ in 0..length {
+            if position == 0 && !circular {
+                break;
+            }
+            let before = (position + length - 1) % length;
+            if self.base(before) != *inserted.last().unwrap() {
+                break;
+            }
+            inserted.pop();
+            inserted.insert(0, self.base(before));
+            position = before;
+        }
+        (position, inserted.into_iter().collect())
+    }
+
+    /// Shift a deletion of `run` bases starting at `position` leftwards.
+    fn left_align_deletion(&self, mut position: usize, run: usize) -> (usize, usize) {
+        let length = self.length();
+        let circular = self.reference.topology == Topology::Circular;
+        for _ in 0..length {
+            if position == 0 && !circular {
+                break;
+            }
+            let before = (position + length - 1) % length;
+            if self.base(before) != self.base(position + run - 1) {
+                break;
+            }
+            position = before;
+        }
+        (position, run)
+    }
+
+    /// Call variants from the reads added so far, ordered by position.
+    pub fn call(&self) -> Vec<Variant> {
+        let length = self.length();
+        let circular = self.reference.topology == Topology::Circular;
+        let mut variants = Vec::new();
+        let passes = |count: usize, depth: usize| {
+            depth > 0 && count >= self.params.min_reads
+                && count as f64 / depth as f64 >= self.params.min_frequency
+        };
+        for position in 0..length {
+            let reference = self.base(position);
+            for (b, &alt) in ['A', 'C', 'G', 'T'].iter().enumerate() {
+                let count = self.bases[position][b];
+                if alt != reference && passes(count, self.depth[position]) {
+                    variants.push(self.variant(position, VariantKind::Snv, reference.to_string(),
+                                               alt.to_string(), count, self.depth[position]));
+                }
+            }
+        }
+        for (&(position, ref inserted), &count) in self.insertions.iter() {
+            // Anchor on the base before the insertion, or on the base after
+            // it when nothing precedes it.
+            let anchor = (position + length - 1) % length;
+            let depth = self.depth[anchor].min(self.depth[position]);
+            if passes(count, depth) {
+                let (anchor, reference, alternate) = if position == 0 && !circular {
+                    let reference = self.base(0).to_string();
+                    (0, reference.clone(), format!("{}{}", inserted, reference))
+                } else {
+                    let reference = self.base(anchor).to_string();
+                    (anchor, reference.clone(), format!("{}{}", reference, inserted))
+                };
+                variants.push(self.variant(anchor, VariantKind::Insertion, reference, alternate,
+                                           count, depth));
+            }
+        }
+        for (&(position, run), &count) in self.deletions.iter() {
+            let depth = self.depth[position];
+            if passes(count, depth) {
+                let (anchor, reference, alternate) = if position == 0 && !circular {
+                    let reference: String = (0..run+1).map(|i| self.base(i)).collect();
+                    (0, reference, self.base(run).to_string())
+                } else {
+                    let anchor = (position + length - 1) % length;
+                    let reference: String = (0..run+1).map(|i| self.base(anchor + i)).collect();
+                    (anchor, reference, self.base(anchor).to_string())
+                };
+                variants.push(self.variant(anchor, VariantKind::Deletion, reference, alternate,
+                                           count, depth));
+            }
+        }
+        variants.sort_by(|a, b| (a.position, &a.alternate).cmp(&(b.position, &b.alternate)));
+        variants
+    }
+
+    fn variant(&self, position: usize, kind: VariantKind, reference: String, alternate: String,
+               count: usize, depth: usize) -> Variant {
+        let mut variant = Variant { position, kind, reference, alternate, count, depth,
+                                    frequency: count as f64 / depth as f64,
+                                    consequences: Vec::new() };
+        variant.consequences = annotate(self.reference, &variant);
+        variant
+    }
+}
+
+/// Work out how a variant changes each CDS it falls in.
+pub fn annotate(reference: &AnnotatedSequence, variant: &Variant) -> Vec<Consequence> {
+    let length = reference.sequence.len();
+    if length == 0 {
+        return Vec::new();
+    }
+    // The first reference base the variant changes; indels start after
+    // their anchor, unless anchored on the base after them, when the
+    // alleles no longer share their first base.
+    let leading = variant.reference.chars().next() == variant.alternate.chars().next();
+    let changed = match variant.kind {
+        VariantKind::Snv => variant.position,
+        _ if !leading => variant.position,
+        _ => (variant.position + 1) % length,
+    };
+    let mut consequences = Vec::new();
+    for feature in reference.features.iter().filter(|f| f.kind == FeatureKind::Cds) {
+        if !feature.contains(changed, length) {
+            continue;
+        }
+        // An insertion is only within a CDS if the bases on both sides are.
+        if variant.kind == VariantKind::Insertion
+            && (!leading || !feature.contains(variant.position, length)) {
+            continue;
+        }
+        let cds = reference.extract(feature);
+        let offset = cds_offset(feature, changed, length);
+        let codon = offset / 3;
+        let from = match cds.codon(codon * 3).and_then(|c| c.translate()) {
+            Ok(aa) => aa,
+            Err(_) => continue,
+        };
+        let effect = match variant.kind {
+            VariantKind::Snv => {
+                // An ambiguous alternate base has no single translation.
+                let mut alt = match variant.alternate.chars().next().map(Nucleotide::from_char) {
+                    Some(Ok(nt)) => nt,
+                    _ => continue,
+                };
+                if feature.strand == Strand::Reverse {
+                    alt = alt.complement();
+                }
+                let mut bases: Vec<Nucleotide> = (0..3).map(|i| cds.get(codon*3 + i).unwrap())
+                    .collect();
+                bases[offset % 3] = alt;
+                let to = Codon::<Nucleotide>::from_slice(&bases).translate().unwrap();
+                if to == from {
+                    Effect::Synonymous { codon: codon + 1, amino_acid: to }
+                } else if to == AminoAcid::STOP {
+                    Effect::Nonsense { codon: codon + 1, from }
+                } else if from == AminoAcid::STOP {
+                    Effect::StopLost { codon: codon + 1, to }
+                } else {
+                    Effect::Missense { codon: codon + 1, from, to }
+                }
+            },
+            VariantKind::Insertion | VariantKind::Deletion => {
+                let delta = (variant.alternate.len() as isize - variant.reference.len() as isize)
+                    .unsigned_abs();
+                if !delta.is_multiple_of(3) {
+                    Effect::Frameshift { codon: codon + 1, from }
+                } else if variant.kind == VariantKind::Insertion {
+                    Effect::InframeInsertion { codon: codon + 1, from, codons: delta / 3 }
+                } else {
+                    Effect::InframeDeletion { codon: codon + 1, from, codons: delta / 3 }
+                }
+            },
+        };
+        consequences.push(Consequence { feature: feature.label.clone(), effect });
+    }
+    consequences
+}
+
+/// Offset of reference `position` within a feature, counted along the
+/// feature's own strand.
+fn cds_offset(feature: &Feature, position: usize, length: usize) -> usize {
+    let forward = if position >= feature.start { position - feature.start }
+                  else { position + length - feature.start };
+    match feature.strand {
+        Strand::Forward => forward,
+        Strand::Reverse => feature.len() - 1 - forward,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VariantCaller, CallerParams, Variant, VariantKind, Effect, annotate};
+    use sequence::sequence::Sequence;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::degenerate_nucleotide::DegenerateNucleotide;
+    use sequence::amino_acid::AminoAcid;
+    use sequence::strand::Strand;
+    use sequence::feature::{AnnotatedSequence, Feature, FeatureKind, Topology};
+    use io::fastq::FastqRecord;
+
+    // A 120 bp construct with a forward CDS at 10..52.
+    const REFERENCE: &str = "GCGCTTAACGATGAAAGCGATTCTGGTGCTGTACGGTCAGCTGAAACGCTAATCGGATCCAAGCTTGCATGCCTGCAGGTCGACTCTAGAGGATCCCCGGGTACCGAGCTCGAATTCCAT";
+
+    fn construct() -> AnnotatedSequence {
+        let seq = Sequence::<Nucleotide>::from_str(REFERENCE).unwrap();
+        let mut construct = AnnotatedSequence::new("pTest", seq, Topology::Circular);
+        construct.features.push(Feature::new(FeatureKind::Cds, "geneA", 10, 52, Strand::Forward));
+        construct
+    }
+
+    fn read(seq: &str, strand: Strand) -> FastqRecord<DegenerateNucleotide> {
+        let mut sequence = Sequence::<DegenerateNucleotide>::from_str(seq).unwrap();
+        if strand == Strand::Reverse {
+            sequence = sequence.reverse_complement();
+        }
+        FastqRecord { id: String::from("r"), description: None,
+                      quality: vec![30; sequence.len()], sequence }
+    }
+
+    /// Reads tiling a circular sample sequence, on alternating strands, with
+    /// a few that span the origin.
+    fn reads(sample: &str) -> Vec<FastqRecord<DegenerateNucleotide>> {
+        let doubled = format!("{}{}", sample, sample);
+        (0..sample.len()).step_by(7)
+            .map(|start| read(&doubled[start..start+50],
+                              if start % 2 == 0 { Strand::Forward } else { Strand::Reverse }))
+            .collect()
+    }
+
+    #[test]
+    fn empty_reference() {
+        let empty = AnnotatedSequence::new("empty", Sequence::new(), Topology::Circular);
+        let variant = Variant { position: 0, kind: VariantKind::Insertion,
+                                reference: String::from("A"), alternate: String::from("AT"),
+                                count: 1, depth: 1, frequency: 1.0, consequences: Vec::new() };
+        assert!(annotate(&empty, &variant).is_empty());
+    }
+
+    #[test]
+    fn no_variants() {
+        let construct = construct();
+        let mut caller = VariantCaller::new(&construct, CallerParams::default());
+        for r in reads(REFERENCE).iter() {
+            assert!(caller.add_read(r).is_some());
+        }
+        assert!(caller.call().is_empty());
+        assert!(caller.depth().iter().all(|&d| d > 0));
+    }
+
+    #[test]
+    fn snv_and_consequence() {
+        let construct = construct();
+        // Codon 3 of geneA (GCG, Ala) at 16..19 becomes GAG (Glu).
+        let mut sample = String::from(REFERENCE);
+        sample.replace_range(17..18, "A");
+        let mut caller = VariantCaller::new(&construct, CallerParams::default());
+        for r in reads(&sample).iter() {
+            caller.add_read(r);
+        }
+        let variants = caller.call();
+        assert_eq!(variants.len(), 1);
+        let v = &variants[0];
+        assert_eq!((v.position, v.kind.clone()), (17, VariantKind::Snv));
+        assert_eq!((&v.reference[..], &v.alternate[..]), ("C", "A"));
+        assert!((v.frequency - 1.0).abs() < 1e-9);
+        assert_eq!(v.consequences[0].feature, "geneA");
+        assert_eq!(v.consequences[0].effect,
+                   Effect::Missense { codon: 3, from: AminoAcid::A, to: AminoAcid::E });
+        assert_eq!(v.consequences[0].effect.to_string(), "p.A3E");
+    }
+
+    #[test]
+    fn indels_across_origin() {
+        let construct = construct();
+        // Delete one base of codon 5 and insert two bases next to the origin.
+        let mut sample = String::from(REFERENCE);
+        sample.replace_range(119..119, "GT");
+        sample.remove(24);
+        let mut caller = VariantCaller::new(&construct, CallerParams::default());
+        for r in reads(&sample).iter() {
+            caller.add_read(r);
+        }
+        let variants = caller.call();
+        assert_eq!(variants.len(), 2);
+        assert_eq!(variants[0].kind, VariantKind::Deletion);
+        assert_eq!(variants[0].position, 23);
+        match variants[0].consequences[0].effect {
+            Effect::Frameshift { codon: 5, .. } => (),
+            ref other => panic!("expected a frameshift, got {:?}", other),
+        }
+        assert_eq!(variants[1].kind, VariantKind::Insertion);
+        assert_eq!(variants[1].alternate.len(), 3);
+        assert!(variants[1].consequences.is_empty());
+    }
+
+    #[test]
+    fn indels_at_start() {
+        let calls = |topology: Topology| {
+            let mut construct = construct();
+            construct.topology = topology;
+            let mut caller = VariantCaller::new(&construct, CallerParams::default());
+            caller.depth = vec![5; REFERENCE.len()];
+            caller.deletions.insert((0, 2), 5);
+            caller.insertions.insert((0, String::from("TT")), 5);
+            caller.call().iter().map(|v| v.to_string()).collect::<Vec<_>>()
+        };
+        // A linear reference has nothing before its first base, so the
+        // indels are anchored on the base after them.
+        assert_eq!(calls(Topology::Linear), vec!["1GCG>G", "1G>TTG"]);
+        assert_eq!(calls(Topology::Circular), vec!["120TGC>T", "120T>TTT"]);
+    }
+}
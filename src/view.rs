@@ -0,0 +1,344 @@
+use std::fmt;
+
+use sequence::nucleotide::{Nucleotide, Complement};
+use sequence::string_io::StringIO;
+use sequence::amino_acid::AminoAcid;
+use sequence::strand::Strand;
+use sequence::feature::{AnnotatedSequence, FeatureKind, Topology};
+use sequence::genetic_code::GeneticCode;
+use digest::{Enzyme, cut_sites};
+use map::lanes;
+
+/// Bases are printed in groups of this many, separated by a space.
+const GROUP: usize = 10;
+
+/// The column of the base `offset` bases into a line.
+fn column(offset: usize) -> usize {
+    offset + offset / GROUP
+}
+
+/// Write `text` into `row` from column `at`, growing the row as needed.
+fn put(row: &mut Vec<char>, at: usize, text: &str) {
+    for (i, ch) in text.chars().enumerate() {
+        if row.len() <= at + i {
+            row.resize(at + i + 1, ' ');
+        }
+        row[at + i] = ch;
+    }
+}
+
+/// A row of translation under the sequence: a name and, for each base, the
+/// character to print below it and whether it continues the three-letter
+/// code of the base before, so is printed straight after it even across
+/// the space between groups.
+struct Track {
+    name: String,
+    cells: Vec<char>,
+    joined: Vec<bool>,
+}
+
+/// A sequence formatted as text in the manner of classic sequence editors:
+/// numbered lines of grouped bases over the complementary strand, with
+/// translations, features and restriction sites marked underneath.
+pub struct TextView<'a> {
+    record: &'a AnnotatedSequence,
+    enzymes: Vec<&'static Enzyme>,
+    frames: Vec<i32>,
+    code: &'static GeneticCode,
+    bases_per_line: usize,
+    three_letter: bool,
+    cds: bool,
+}
+
+impl<'a> TextView<'a> {
+    pub fn new(record: &'a AnnotatedSequence) -> TextView<'a> {
+        TextView { record, enzymes: Vec::new(), frames: Vec::new(), code: GeneticCode::standard(),
+                   bases_per_line: 60, three_letter: false, cds: true }
+    }
+
+    /// Mark the cut sites of these enzymes.
+    pub fn with_enzymes(mut self, enzymes: &[&'static Enzyme]) -> TextView<'a> {
+        self.enzymes = enzymes.to_vec();
+        self
+    }
+
+    /// Also translate whole reading frames: 1, 2 or 3 on the forward strand
+    /// and -1, -2 or -3 on the reverse, counted from its 5' end. Other
+    /// values are ignored.
+    pub fn with_frames(mut self, frames: &[i32]) -> TextView<'a> {
+        self.frames = frames.iter().cloned().filter(|f| *f != 0 && f.abs() <= 3).collect();
+        self
+    }
+
+    /// Translate with this code instead of the standard one.
+    pub fn with_code(mut self, code: &'static GeneticCode) -> TextView<'a> {
+        self.code = code;
+        self
+    }
+
+    /// Print this many bases to a line, rounded up to whole groups of ten.
+    pub fn with_bases_per_line(mut self, bases: usize) -> TextView<'a> {
+        self.bases_per_line = bases.max(1).div_ceil(GROUP) * GROUP;
+        self
+    }
+
+    /// Print amino acids as three-letter codes such as `Met` spanning their
+    /// codons rather than one letter under the middle base.
+    pub fn three_letter(mut self, three_letter: bool) -> TextView<'a> {
+        self.three_letter = three_letter;
+        self
+    }
+
+    /// Whether to translate each annotated CDS, as is done by default.
+    pub fn translate_cds(mut self, cds: bool) -> TextView<'a> {
+        self.cds = cds;
+        self
+    }
+
+    /// Place a translation in a track. `codons` gives the position of the
+    /// first base of each codon on the forward strand, in the order of the
+    /// residues, which may run past the end of a circular sequence.
+    fn track(&self, name: String, codons: &[usize], residues: &[String]) -> Track {
+        let length = self.record.sequence.len();
+        let mut cells = vec![' '; length];
+        let mut joined = vec![false; length];
+        for (&first, residue) in codons.iter().zip(residues.iter()) {
+            if self.three_letter {
+                for (i, ch) in residue.chars().take(3).enumerate() {
+                    cells[(first + i) % length] = ch;
+                    joined[(first + i) % length] = i > 0;
+                }
+            } else {
+                cells[(first + 1) % length] = residue.chars().next().unwrap_or(' ');
+            }
+        }
+        Track { name, cells, joined }
+    }
+
+    fn residue(&self, aa: &AminoAcid) -> String {
+        if self.three_letter {
+            // Title case, so codes printed end to end can still be told apart.
+            let code = aa.to_three_letter_code();
+            format!("{}{}", &code[..1], code[1..].to_lowercase())
+        } else {
+            aa.to_char().to_string()
+        }
+    }
+
+    fn tracks(&self) -> Vec<Track> {
+        let record = self.record;
+        let length = record.sequence.len();
+        let mut tracks = Vec::new();
+        if length == 0 {
+            return tracks;
+        }
+        if self.cds {
+            for feature in record.features.iter().filter(|f| f.kind == FeatureKind::Cds) {
+                let protein = record.extract(feature).translate_with(self.code);
+                let residues: Vec<String> = protein.iter().map(|aa| self.residue(aa)).collect();
+                let codons: Vec<usize> = (0..residues.len()).map(|i| match feature.strand {
+                    Strand::Forward => feature.start + 3 * i,
+                    Strand::Reverse => feature.end - 3 * i - 3,
+                }).collect();
+                tracks.push(self.track(feature.label.clone(), &codons, &residues));
+            }
+        }
+        for &frame in self.frames.iter() {
+            let offset = (frame.unsigned_abs() as usize - 1).min(length);
+            let strand = if frame > 0 {
+                record.sequence.clone()
+            } else {
+                record.sequence.reverse_complement()
+            };
+            let protein = strand.subsequence(offset, length).unwrap().translate_with(self.code);
+            let residues: Vec<String> = protein.iter().map(|aa| self.residue(aa)).collect();
+            let codons: Vec<usize> = (0..residues.len()).map(|i| {
+                if frame > 0 { offset + 3 * i } else { length - offset - 3 * i - 3 }
+            }).collect();
+            tracks.push(self.track(format!("frame {}", frame), &codons, &residues));
+        }
+        tracks
+    }
+
+    /// Every cut site as a position and the names of the enzymes cutting
+    /// there.
+    fn cut_sites(&self) -> Vec<(usize, String)> {
+        cut_sites(&self.record.sequence, self.record.topology, &self.enzymes).into_iter()
+            .map(|(cut, names)| (cut, names.join(","))).collect()
+    }
+
+    /// Rows of feature bars for the bases `line_start..line_end`, each bar
+    /// drawn with `=` and an arrowhead at the feature's 3' end where that
+    /// falls on the line, and labelled inside if it fits or after it if not.
+    fn feature_rows(&self, line_start: usize, line_end: usize) -> Vec<Vec<char>> {
+        let length = self.record.sequence.len();
+        let mut bars = Vec::new();
+        for feature in self.record.features.iter() {
+            let mut pieces = vec![(feature.start, feature.end.min(length))];
+            if feature.end > length {
+                pieces.push((0, feature.end - length));
+            }
+            for (start, end) in pieces {
+                let (from, to) = (start.max(line_start), end.min(line_end));
+                if from >= to {
+                    continue;
+                }
+                let (left, right) = (column(from - line_start), column(to - 1 - line_start) + 1);
+                let mut bar: Vec<char> = vec!['='; right - left];
+                let last = to == end && end % length == feature.end % length;
+                if feature.strand == Strand::Forward && last {
+                    bar[right - left - 1] = '>';
+                }
+                if feature.strand == Strand::Reverse && from == start && start == feature.start {
+                    bar[0] = '<';
+                }
+                let label_length = feature.label.chars().count();
+                let (label_at, extent) = if label_length + 2 <= bar.len() {
+                    (left + (bar.len() - label_length) / 2, right)
+                } else {
+                    (right + 1, right + 1 + label_length)
+                };
+                bars.push((bar, left, &feature.label, label_at, extent));
+            }
+        }
+        let extents: Vec<(f64, f64)> = bars.iter()
+            .map(|&(_, left, _, _, extent)| (left as f64, extent as f64 + 1.0)).collect();
+        let mut rows: Vec<Vec<char>> = Vec::new();
+        for (&(ref bar, left, label, label_at, _), lane) in bars.iter().zip(lanes(&extents, None)) {
+            if rows.len() <= lane {
+                rows.resize(lane + 1, Vec::new());
+            }
+            put(&mut rows[lane], left, &bar.iter().collect::<String>());
+            put(&mut rows[lane], label_at, label);
+        }
+        rows
+    }
+
+    /// Rows marking the cut sites among `sites` that fall before a base of
+    /// `line_start..line_end` with a caret and the enzymes' names.
+    fn site_rows(sites: &[(usize, String)], line_start: usize, line_end: usize)
+                 -> Vec<Vec<char>> {
+        let marks: Vec<(usize, String)> = sites.iter()
+            .filter(|site| site.0 >= line_start && site.0 < line_end)
+            .map(|site| (column(site.0 - line_start), format!("^{}", site.1))).collect();
+        let extents: Vec<(f64, f64)> = marks.iter()
+            .map(|mark| (mark.0 as f64, (mark.0 + mark.1.len() + 1) as f64)).collect();
+        let mut rows: Vec<Vec<char>> = Vec::new();
+        for (mark, lane) in marks.iter().zip(lanes(&extents, None)) {
+            if rows.len() <= lane {
+                rows.resize(lane + 1, Vec::new());
+            }
+            put(&mut rows[lane], mark.0, &mark.1);
+        }
+        rows
+    }
+}
+
+impl<'a> fmt::Display for TextView<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let record = self.record;
+        let bases: Vec<Nucleotide> = record.sequence.iter().cloned().collect();
+        let length = bases.len();
+        let topology = match record.topology {
+            Topology::Circular => "circular",
+            Topology::Linear => "linear",
+        };
+        writeln!(f, "{} ({} bp, {})", record.name, length, topology)?;
+        let tracks = self.tracks();
+        let sites = self.cut_sites();
+        let number_width = length.max(1).to_string().len();
+        let margin = " ".repeat(number_width + 1);
+        let write_row = |f: &mut fmt::Formatter, row: &[char], note: &str| {
+            let text: String = row.iter().collect();
+            if note.is_empty() {
+                writeln!(f, "{}{}", margin, text.trim_end())
+            } else {
+                writeln!(f, "{}{:width$}  {}", margin, text, note,
+                         width = column(self.bases_per_line - 1) + 1)
+            }
+        };
+        for line_start in (0..length).step_by(self.bases_per_line) {
+            let line_end = (line_start + self.bases_per_line).min(length);
+            writeln!(f)?;
+            let mut top = Vec::new();
+            let mut bottom = Vec::new();
+            for (offset, nt) in bases[line_start..line_end].iter().enumerate() {
+                put(&mut top, column(offset), &nt.to_char().to_string());
+                put(&mut bottom, column(offset), &nt.complement().to_char().to_string());
+            }
+            writeln!(f, "{:>width$} {}", line_start + 1, top.iter().collect::<String>(),
+                     width = number_width)?;
+            write_row(f, &bottom, "")?;
+            for track in tracks.iter() {
+                let cells = &track.cells[line_start..line_end];
+                if cells.iter().all(|&ch| ch == ' ') {
+                    continue;
+                }
+                let mut row = Vec::new();
+                let mut at = 0;
+                for (offset, &ch) in cells.iter().enumerate() {
+                    at = if offset > 0 && track.joined[line_start + offset] { at + 1 }
+                         else { column(offset) };
+                    put(&mut row, at, &ch.to_string());
+                }
+                write_row(f, &row, &track.name)?;
+            }
+            for row in self.feature_rows(line_start, line_end) {
+                write_row(f, &row, "")?;
+            }
+            for row in TextView::site_rows(&sites, line_start, line_end) {
+                write_row(f, &row, "")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TextView;
+    use sequence::sequence::Sequence;
+    use sequence::nucleotide::Nucleotide;
+    use sequence::strand::Strand;
+    use sequence::feature::{AnnotatedSequence, Feature, FeatureKind, Topology};
+    use digest::Enzyme;
+
+    #[test]
+    fn layout() {
+        let seq = Sequence::<Nucleotide>::from_str("GAATTCATGAAACGTTAAGGATCCCCTTAGCTTCATGG")
+            .unwrap();
+        let mut record = AnnotatedSequence::new("pView", seq, Topology::Circular);
+        record.features.push(Feature::new(FeatureKind::Cds, "orf", 6, 18, Strand::Forward));
+        record.features.push(Feature::new(FeatureKind::Cds, "rev", 26, 38, Strand::Reverse));
+        record.features.push(Feature::new(FeatureKind::Origin, "origin", 34, 42, Strand::Forward));
+        let enzymes = [Enzyme::from_name("EcoRI").unwrap(), Enzyme::from_name("BamHI").unwrap()];
+        let view = TextView::new(&record).with_enzymes(&enzymes).with_bases_per_line(20);
+        let expected = "\
+pView (38 bp, circular)
+
+ 1 GAATTCATGA AACGTTAAGG
+   CTTAAGTACT TTGCAATTCC
+          M   K  R  *     orf
+         =====orf====>
+   ===> origin
+    ^EcoRI             ^BamHI
+
+21 ATCCCCTTAG CTTCATGG
+   TAGGGGAATC GAAGTACC
+          *   S  *  P     rev
+         <====rev=====
+                  ==== origin
+";
+        assert_eq!(view.to_string(), expected);
+
+        let view = TextView::new(&record).translate_cds(false).with_frames(&[-1])
+            .three_letter(true).with_bases_per_line(40);
+        let text = view.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        // Frame -1 reads CCA TGA AGC ... leftwards from the end, so its
+        // residues run right to left, each written over its codon.
+        // A code whose codon spans the gap between groups is kept whole.
+        assert_eq!(lines[4], "     AsnMetPhe Arg * Pro AspGly * Ser  * Pro    frame -1");
+        assert_eq!(lines[6], "   ===> origin                          ==== origin");
+    }
+}